@@ -0,0 +1,329 @@
+use crate::components::api_base_path;
+use crate::naming::ComponentName;
+use crate::routes::{insert_before_marker, relative_import, strip_extension};
+use crate::schema::ProjectConfig;
+use crate::templates::{BuiltinFile, RenderedFile};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// Every API component is recorded here, so other commands can find the
+/// endpoints without scanning the source.
+pub const API_MANIFEST: &str = "nexus.api.json";
+
+/// Express servers mount routers above these markers in their entry file.
+const IMPORTS_MARKER: &str = "// nexus:api-imports";
+const ROUTES_MARKER: &str = "// nexus:api-routes";
+
+const SERVER_TEMPLATES: &[BuiltinFile] = &[
+    ("express", include_str!("../templates/api/express.js.tera")),
+    ("nextjs-route", include_str!("../templates/api/nextjs-route.ts.tera")),
+    ("nextjs-item-route", include_str!("../templates/api/nextjs-item-route.ts.tera")),
+    ("nextjs-store", include_str!("../templates/api/nextjs-store.ts.tera")),
+    ("axum", include_str!("../templates/api/axum.rs.tera")),
+    ("warp", include_str!("../templates/api/warp.rs.tera")),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ApiServer {
+    Express,
+    Nextjs,
+    Axum,
+    Warp,
+}
+
+impl ApiServer {
+    pub fn parse(name: &str) -> Result<Self> {
+        match name.to_lowercase().as_str() {
+            "express" => Ok(Self::Express),
+            "nextjs" | "next" => Ok(Self::Nextjs),
+            "axum" => Ok(Self::Axum),
+            "warp" => Ok(Self::Warp),
+            other => anyhow::bail!(
+                "Unknown API server '{}' in nexus.yaml. Use express, nextjs, axum or warp",
+                other
+            ),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Express => "express",
+            Self::Nextjs => "nextjs",
+            Self::Axum => "axum",
+            Self::Warp => "warp",
+        }
+    }
+
+    /// The server from `api.server` in `nexus.yaml`, or else Next.js route
+    /// handlers for Next.js projects, a Rust crate using axum or warp at the
+    /// root or in `server/`, and Express otherwise. Returns the handler
+    /// directory with it.
+    pub fn detect(root: &Path, config: &ProjectConfig) -> Result<(Self, String)> {
+        let crate_dir = ["", "server"].into_iter().find_map(|dir| {
+            let manifest = std::fs::read_to_string(root.join(dir).join("Cargo.toml")).ok()?;
+            let server = if manifest.contains("axum") {
+                Self::Axum
+            } else if manifest.contains("warp") {
+                Self::Warp
+            } else {
+                return None;
+            };
+            Some((server, dir))
+        });
+
+        let server = match (&config.api.server, crate_dir) {
+            (Some(name), _) => Self::parse(name)?,
+            (None, _) if config.framework == "nextjs" => Self::Nextjs,
+            (None, Some((server, _))) => server,
+            (None, None) => Self::Express,
+        };
+        let dir = match (&config.api.dir, server) {
+            (Some(dir), _) => dir.to_string_lossy().trim_end_matches('/').to_string(),
+            (None, Self::Express) => "server/routes".to_string(),
+            (None, Self::Nextjs) => "app/api".to_string(),
+            (None, _) => match crate_dir {
+                Some((_, "")) => "src/handlers".to_string(),
+                _ => "server/src/handlers".to_string(),
+            },
+        };
+        Ok((server, dir))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EndpointRoute {
+    pub method: String,
+    pub path: String,
+}
+
+/// One API component in `nexus.api.json`. Paths are relative to the project root.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Endpoint {
+    pub name: String,
+    pub base_path: String,
+    pub server: ApiServer,
+    pub routes: Vec<EndpointRoute>,
+    pub handler: String,
+    pub client: String,
+    pub schema: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ApiManifest {
+    pub endpoints: Vec<Endpoint>,
+}
+
+impl ApiManifest {
+    /// The project's manifest, or an empty one if it has none yet.
+    pub fn read(root: &Path) -> Result<Self> {
+        let path = root.join(API_MANIFEST);
+        match std::fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents)
+                .with_context(|| format!("Failed to parse {}", path.display())),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    /// Adds `endpoint`, replacing an earlier entry with the same base path.
+    pub fn upsert(&mut self, endpoint: Endpoint) {
+        self.endpoints.retain(|existing| existing.base_path != endpoint.base_path);
+        self.endpoints.push(endpoint);
+        self.endpoints.sort_by(|a, b| a.base_path.cmp(&b.base_path));
+    }
+
+    pub fn render(&self) -> Result<String> {
+        Ok(format!("{}\n", serde_json::to_string_pretty(self)?))
+    }
+}
+
+/// Server files, entry-point edits and the manifest update for one API
+/// component. Nothing is written until the caller does.
+#[derive(Debug)]
+pub struct ApiPlan {
+    pub server: ApiServer,
+    pub base_path: String,
+    pub files: Vec<RenderedFile>,
+    /// Existing files to update in place, including the manifest.
+    pub edits: Vec<RenderedFile>,
+    /// How to mount the handler, when nexus could not do it.
+    pub hint: Option<String>,
+}
+
+#[derive(Serialize)]
+struct HandlerContext {
+    name: String,
+    title: String,
+    base_path: String,
+    /// Import specifier of the schema from the handler.
+    schema_module: String,
+    schema_file: String,
+    /// Rust module path of the handler, e.g. `handlers::users`.
+    module_path: String,
+    /// `"api" / "users"`, for warp's `path!` macro.
+    path_segments: String,
+}
+
+/// Plans the server side of an API component whose client and schema are
+/// `client` and `schema`.
+pub fn plan(
+    root: &Path,
+    config: &ProjectConfig,
+    name: &ComponentName,
+    client: &str,
+    schema: &str,
+) -> Result<ApiPlan> {
+    let (server, dir) = ApiServer::detect(root, config)?;
+    let base_path = api_base_path(name);
+    let route_path = name.route_path();
+    let module = route_path.trim_start_matches('/').replace(['/', '-'], "_");
+
+    let mut plan = ApiPlan {
+        server,
+        base_path: base_path.clone(),
+        files: Vec::new(),
+        edits: Vec::new(),
+        hint: None,
+    };
+    let handler_dir = match server {
+        ApiServer::Nextjs => format!("{}{}", dir, route_path),
+        ApiServer::Express => {
+            let file = format!("{}{}.js", dir, route_path);
+            file.rsplit_once('/').map(|(dir, _)| dir.to_string()).unwrap_or_default()
+        }
+        ApiServer::Axum | ApiServer::Warp => dir.clone(),
+    };
+    let context = HandlerContext {
+        name: name.pascal(),
+        title: name.title(),
+        base_path: base_path.clone(),
+        schema_module: relative_import(&handler_dir, strip_extension(schema)),
+        schema_file: schema.to_string(),
+        module_path: format!("{}::{}", dir.rsplit('/').next().unwrap_or("api"), module),
+        path_segments: base_path
+            .trim_start_matches('/')
+            .split('/')
+            .map(|segment| format!("\"{}\"", segment))
+            .collect::<Vec<_>>()
+            .join(" / "),
+    };
+
+    let handler = match server {
+        ApiServer::Express => {
+            let handler = format!("{}{}.js", dir, route_path);
+            plan.files.push(render(&handler, "express", &context)?);
+            plan.hint = mount_express(root, &dir, &handler, name, &base_path, &mut plan.edits)?;
+            handler
+        }
+        ApiServer::Nextjs => {
+            let handler = format!("{}/route.ts", handler_dir);
+            plan.files.push(render(&handler, "nextjs-route", &context)?);
+            plan.files.push(render(&format!("{}/[id]/route.ts", handler_dir), "nextjs-item-route", &context)?);
+            plan.files.push(render(&format!("{}/store.ts", handler_dir), "nextjs-store", &context)?);
+            handler
+        }
+        ApiServer::Axum | ApiServer::Warp => {
+            let handler = format!("{}/{}.rs", dir, module);
+            plan.files.push(render(&handler, server.name(), &context)?);
+
+            let mod_file = format!("{}/mod.rs", dir);
+            let line = format!("pub mod {};", module);
+            let mut contents = std::fs::read_to_string(root.join(&mod_file)).unwrap_or_default();
+            if !contents.lines().any(|existing| existing.trim() == line) {
+                if !contents.is_empty() && !contents.ends_with('\n') {
+                    contents.push('\n');
+                }
+                contents.push_str(&line);
+                contents.push('\n');
+                plan.edits.push(RenderedFile { path: mod_file, contents });
+            }
+            let mount = match server {
+                ApiServer::Axum => format!(".merge({}::router())", context.module_path),
+                _ => format!(".or({}::routes())", context.module_path),
+            };
+            plan.hint = Some(format!("Mount it in your server with {}", mount));
+            handler
+        }
+    };
+
+    let mut manifest = ApiManifest::read(root)?;
+    manifest.upsert(Endpoint {
+        name: route_path.trim_start_matches('/').to_string(),
+        base_path: base_path.clone(),
+        server,
+        routes: vec![
+            EndpointRoute { method: "GET".into(), path: base_path.clone() },
+            EndpointRoute { method: "GET".into(), path: format!("{}/:id", base_path) },
+            EndpointRoute { method: "POST".into(), path: base_path.clone() },
+        ],
+        handler,
+        client: client.to_string(),
+        schema: schema.to_string(),
+    });
+    plan.edits.push(RenderedFile {
+        path: API_MANIFEST.to_string(),
+        contents: manifest.render()?,
+    });
+    Ok(plan)
+}
+
+fn render(path: &str, template: &str, context: &HandlerContext) -> Result<RenderedFile> {
+    let source = SERVER_TEMPLATES
+        .iter()
+        .find(|(name, _)| *name == template)
+        .map(|(_, contents)| *contents)
+        .with_context(|| format!("No {} server template", template))?;
+    let rendered = tera::Tera::one_off(source, &tera::Context::from_serialize(context)?, false)?;
+    Ok(RenderedFile {
+        path: path.to_string(),
+        contents: format!("{}\n", rendered.trim_end()),
+    })
+}
+
+/// Requires and mounts an Express router in the server entry next to the
+/// routes directory. Returns a hint when the entry or its markers are missing.
+fn mount_express(
+    root: &Path,
+    dir: &str,
+    handler: &str,
+    name: &ComponentName,
+    base_path: &str,
+    edits: &mut Vec<RenderedFile>,
+) -> Result<Option<String>> {
+    let server_dir = dir.rsplit_once('/').map(|(parent, _)| parent).unwrap_or("");
+    let entry = match server_dir {
+        "" => "index.js".to_string(),
+        dir => format!("{}/index.js", dir),
+    };
+    let module = relative_import(server_dir, strip_extension(handler));
+    // Nested endpoints like `admin/users` need distinct variables.
+    let route_name = ComponentName::parse(&name.route_path().trim_start_matches('/').replace('/', "_"))?;
+    let variable = format!("{}Router", route_name.camel());
+    let require = format!("const {} = require(\"{}\");", variable, module);
+    let mount = format!("app.use(\"{}\", {});", base_path, variable);
+
+    let Ok(contents) = std::fs::read_to_string(root.join(&entry)) else {
+        return Ok(Some(format!(
+            "No {} found. Mount the router in your Express app with app.use(\"{}\", require(\"./{}\"))",
+            entry,
+            base_path,
+            strip_extension(handler)
+        )));
+    };
+    if contents.contains(&format!("require(\"{}\")", module)) {
+        return Ok(None);
+    }
+    let has_marker = |marker: &str| contents.lines().any(|line| line.trim() == marker);
+    if !has_marker(IMPORTS_MARKER) || !has_marker(ROUTES_MARKER) {
+        return Ok(Some(format!(
+            "Add '{}' and '{}' markers to {} to mount routers automatically. For now, add:\n   {}\n   {}",
+            IMPORTS_MARKER, ROUTES_MARKER, entry, require, mount
+        )));
+    }
+
+    let contents = insert_before_marker(&contents, IMPORTS_MARKER, &require);
+    let contents = insert_before_marker(&contents, ROUTES_MARKER, &mount);
+    edits.push(RenderedFile { path: entry, contents });
+    Ok(None)
+}
@@ -1,7 +1,42 @@
+use crate::cache::{BuildCache, CacheStats};
+use crate::env::ProjectEnv;
+use crate::manifest::BuildManifest;
+use crate::package_json::{PackageJson, PackageManager};
 use crate::project::Project;
-use anyhow::Result;
+use crate::pwa;
+use crate::routes::Router;
+use crate::ssg;
+use crate::static_site::{self, StaticOptions};
+use crate::types::BuildTarget;
+use crate::workspace::Workspace;
+use anyhow::{Context, Result};
+use indicatif::ProgressBar;
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
+use std::process::Stdio;
 use std::time::Instant;
+use tokio::io::{AsyncBufReadExt, BufReader};
+use tokio::process::Command;
+
+/// Lines of bundler output kept to explain a failed build.
+const OUTPUT_TAIL: usize = 20;
+
+/// Vite configs nexus builds with, written to `.nexus` and layered over the
+/// project's own `vite.config.*`.
+const VITE_CONFIG: &str = ".nexus/vite.config.mjs";
+const VITE_LIB_CONFIG: &str = ".nexus/vite.lib.config.mjs";
+
+/// Variables bundlers inherit from the shell: what node, npm and the OS need
+/// to run. Everything else, secrets included, stays out of their reach.
+const INHERITED_ENV: &[&str] = &[
+    "PATH", "PATHEXT", "HOME", "USERPROFILE", "APPDATA", "LOCALAPPDATA", "SystemRoot", "TEMP", "TMP",
+    "TMPDIR", "NODE_OPTIONS",
+];
+const INHERITED_ENV_PREFIX: &str = "npm_config_";
+
+/// JSON object of the public variables, exported to bundlers whose config
+/// passes them on itself, such as Next.js's `env`.
+const CLIENT_ENV: &str = "NEXUS_CLIENT_ENV";
 
 #[derive(Debug, Clone)]
 pub struct BuildResult {
@@ -10,38 +45,687 @@ pub struct BuildResult {
     pub file_count: usize,
     pub build_time: f64,
     pub warnings: Option<Vec<String>>,
+    pub cache: CacheStats,
+    /// Written to `nexus-manifest.json` in the output directory.
+    pub manifest: BuildManifest,
+    /// The manifest of the build this one replaced, for size deltas.
+    pub previous_manifest: Option<BuildManifest>,
+    /// `.env` files loaded for the build's mode.
+    pub env_files: Vec<String>,
+    /// Names of the variables inlined into the output.
+    pub public_env: Vec<String>,
+}
+
+/// What one build runs with, shared by its steps.
+#[derive(Clone, Copy)]
+struct BuildSettings<'a> {
+    toolchain: Toolchain,
+    mode: &'a str,
+    target: BuildTarget,
+    output_dir: &'a Path,
+    env: &'a ProjectEnv,
+}
+
+/// The bundler that builds a project.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Toolchain {
+    Vite,
+    /// Vite with SvelteKit; the adapter decides the output directory.
+    SvelteKit,
+    Next,
+    AngularCli,
+    /// No bundler: nexus builds the site itself, see `static_site`.
+    Static,
+}
+
+impl Toolchain {
+    /// Detects the toolchain from `package.json` dependencies and config files.
+    pub fn detect(root: &Path) -> Result<Self> {
+        let package = PackageJson::read(root)?;
+        let depends_on = |name: &str| {
+            package
+                .as_ref()
+                .is_some_and(|package| package.dependency(name).is_some())
+        };
+        let has_config = |stem: &str| {
+            ["js", "mjs", "cjs", "ts", "mts"]
+                .iter()
+                .any(|extension| root.join(format!("{}.{}", stem, extension)).is_file())
+        };
+
+        Ok(if depends_on("next") || has_config("next.config") {
+            Toolchain::Next
+        } else if depends_on("@angular/cli") || root.join("angular.json").is_file() {
+            Toolchain::AngularCli
+        } else if depends_on("@sveltejs/kit") || has_config("svelte.config") {
+            Toolchain::SvelteKit
+        } else if depends_on("vite") || has_config("vite.config") {
+            Toolchain::Vite
+        } else {
+            Toolchain::Static
+        })
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Toolchain::Vite => "vite",
+            Toolchain::SvelteKit => "vite (SvelteKit)",
+            Toolchain::Next => "next",
+            Toolchain::AngularCli => "angular-cli",
+            Toolchain::Static => "static",
+        }
+    }
+
+    fn binary(&self) -> Option<&'static str> {
+        match self {
+            Toolchain::Vite | Toolchain::SvelteKit => Some("vite"),
+            Toolchain::Next => Some("next"),
+            Toolchain::AngularCli => Some("ng"),
+            Toolchain::Static => None,
+        }
+    }
 }
 
 pub struct ProjectBuilder<'a> {
     project: &'a Project,
+    progress: Option<ProgressBar>,
+    use_cache: bool,
 }
 
 impl<'a> ProjectBuilder<'a> {
     pub fn new(project: &'a Project) -> Self {
-        Self { project }
+        Self {
+            project,
+            progress: None,
+            use_cache: true,
+        }
+    }
+
+    /// Shows the bundler's output on `progress` while it runs.
+    pub fn with_progress(mut self, progress: ProgressBar) -> Self {
+        self.progress = Some(progress);
+        self
     }
 
-    pub async fn build(&self, mode: &str, target: &str, out_dir: Option<&Path>) -> Result<BuildResult> {
+    /// Skips the build cache in `.nexus/cache` when `enabled` is false.
+    pub fn with_cache(mut self, enabled: bool) -> Self {
+        self.use_cache = enabled;
+        self
+    }
+
+    pub fn toolchain(&self) -> Result<Toolchain> {
+        Toolchain::detect(&self.project.root)
+    }
+
+    /// Runs the project's bundler for `mode` (`production`, `development` or
+    /// a custom Vite mode) and `target`, then measures the output. When no
+    /// input, setting or the CLI version changed since the last build, the
+    /// cached output is restored instead.
+    pub async fn build(&self, mode: &str, target: BuildTarget, out_dir: Option<&Path>) -> Result<BuildResult> {
         let start = Instant::now();
-        let output_dir = out_dir
-            .map(PathBuf::from)
-            .unwrap_or_else(|| self.project.root.join("dist"));
-        std::fs::create_dir_all(&output_dir)?;
-        let output_file = output_dir.join("index.html");
-        let contents = format!(
-            "<html><body><h1>Nexus Studio AI</h1><p>Mode: {}</p><p>Target: {}</p></body></html>",
-            mode, target
-        );
-        std::fs::write(&output_file, contents)?;
-        let file_count = 1;
-        let size_mb = (std::fs::metadata(&output_file)?.len() as f64) / (1024.0 * 1024.0);
-        let build_time = start.elapsed().as_secs_f64();
+        let root = &self.project.root;
+        let toolchain = self.toolchain()?;
+        check_target(root, toolchain, target)?;
+        let env = ProjectEnv::load(root, mode, &self.project.config.env)?;
+        let mut warnings = Vec::new();
+
+        let requested = match out_dir {
+            Some(dir) if dir.is_relative() => Some(std::env::current_dir()?.join(dir)),
+            Some(dir) => Some(dir.to_path_buf()),
+            None => None,
+        };
+        let default_dir = root.join(&self.project.config.build.out_dir);
+
+        let output_dir = match toolchain {
+            // Both write where their own config says, except for packages.
+            Toolchain::Next | Toolchain::SvelteKit if target != BuildTarget::Lib => {
+                let output_dir = root.join(configured_output(root, toolchain));
+                if let Some(requested) = requested.filter(|dir| *dir != output_dir) {
+                    warnings.push(format!(
+                        "{} decides its own output directory; ignoring --out-dir {}",
+                        toolchain.name(),
+                        requested.display()
+                    ));
+                }
+                output_dir
+            }
+            _ => requested.unwrap_or(default_dir),
+        };
+
+        // Read before the build replaces the output.
+        let previous_manifest = BuildManifest::read(&output_dir);
+
+        let mut cache = self.use_cache.then(|| BuildCache::open(root));
+        let mut stats = CacheStats::default();
+        let mut inputs = BTreeMap::new();
+        let mut key = String::new();
+        if let Some(cache) = &cache {
+            if let Some(progress) = &self.progress {
+                progress.set_message("Checking build cache...");
+            }
+            inputs = BuildCache::hash_inputs(root, &output_dir)?;
+            // Shell variables override .env files but are not input files, so
+            // the resolved values are part of the key.
+            let shell_env = format!("{:?}{:?}", env.vars, env.public);
+            let dependencies = self.dependency_inputs()?;
+            key = BuildCache::build_key(
+                &inputs,
+                &[
+                    env!("CARGO_PKG_VERSION"),
+                    toolchain.name(),
+                    mode,
+                    target.name(),
+                    &output_dir.to_string_lossy(),
+                    &shell_env,
+                    &dependencies,
+                ],
+            );
+            stats = cache.compare(&inputs);
+            if let Some(cached_warnings) = cache.restore(&key, &output_dir)? {
+                stats.restored = true;
+                warnings.extend(cached_warnings);
+            }
+        }
+
+        let manifest = match BuildManifest::read(&output_dir).filter(|_| stats.restored) {
+            Some(manifest) => manifest,
+            None => {
+                let first_build_warning = warnings.len();
+                if !stats.restored {
+                    let settings = BuildSettings {
+                        toolchain,
+                        mode,
+                        target,
+                        output_dir: &output_dir,
+                        env: &env,
+                    };
+                    self.run_toolchain(&settings, cache.as_ref(), &mut warnings).await?;
+                }
+                if !output_dir.is_dir() {
+                    anyhow::bail!(
+                        "{} finished but produced no output at {}",
+                        toolchain.name(),
+                        output_dir.display()
+                    );
+                }
+                if !stats.restored {
+                    self.finish_target(toolchain, target, &output_dir, &mut warnings)?;
+                }
+                if let Some(progress) = &self.progress {
+                    progress.set_message("Measuring output...");
+                }
+                let manifest = BuildManifest::generate(&output_dir, mode, target)?;
+                manifest.write(&output_dir)?;
+                if let Some(cache) = cache.as_mut().filter(|_| !stats.restored) {
+                    let build_warnings = warnings[first_build_warning..].to_vec();
+                    if let Err(e) = cache.store(&key, inputs, &output_dir, &build_warnings) {
+                        warnings.push(format!("Could not update the build cache: {:#}", e));
+                    }
+                }
+                manifest
+            }
+        };
+
         Ok(BuildResult {
             output_dir,
-            size_mb,
-            file_count,
-            build_time,
-            warnings: None,
+            size_mb: manifest.total.size as f64 / (1024.0 * 1024.0),
+            file_count: manifest.files.len(),
+            build_time: start.elapsed().as_secs_f64(),
+            warnings: Some(warnings),
+            cache: stats,
+            manifest,
+            previous_manifest,
+            env_files: env.files,
+            public_env: env.public.into_keys().collect(),
         })
     }
+
+    /// Hashes of the inputs of the workspace members this project depends on,
+    /// so that editing one of them also invalidates this project's cached
+    /// output. Empty outside a workspace.
+    fn dependency_inputs(&self) -> Result<String> {
+        let Some(workspace_root) = &self.project.workspace_root else {
+            return Ok(String::new());
+        };
+        let workspace = Workspace::load(workspace_root)?;
+        let mut hashes = Vec::new();
+        for dependency in workspace.dependencies_of(&self.project.config.name) {
+            let output = dependency.root.join(&dependency.config.build.out_dir);
+            let inputs = BuildCache::hash_inputs(&dependency.root, &output)?;
+            hashes.push(format!("{}:{}", dependency.config.name, BuildCache::build_key(&inputs, &[])));
+        }
+        Ok(hashes.join(","))
+    }
+
+    /// Builds into `output_dir` with the toolchain: the static site builder,
+    /// or the bundler with the arguments it takes.
+    async fn run_toolchain(
+        &self,
+        settings: &BuildSettings<'_>,
+        cache: Option<&BuildCache>,
+        warnings: &mut Vec<String>,
+    ) -> Result<()> {
+        let BuildSettings { toolchain, mode, target, output_dir, env } = *settings;
+        if target == BuildTarget::Lib {
+            return self.run_library(settings, warnings).await;
+        }
+        let mut args = vec!["build".to_string()];
+        match toolchain {
+            Toolchain::Static => {
+                if let Some(progress) = &self.progress {
+                    progress.set_message("Building static site...");
+                }
+                let mut options = StaticOptions::for_mode(&self.project.config.name, mode, env);
+                options.routes = &self.project.config.build.ssg.routes;
+                warnings.extend(static_site::build(&self.project.root, output_dir, options, cache)?);
+                return Ok(());
+            }
+            Toolchain::Vite => {
+                args.extend(["--config".into(), self.write_vite_env_config(env)?]);
+                args.extend(["--mode".into(), mode.into()]);
+                args.extend(["--outDir".into(), output_dir.to_string_lossy().into()]);
+                args.push("--emptyOutDir".into());
+            }
+            Toolchain::AngularCli => {
+                args.extend(["--configuration".into(), mode.into()]);
+                args.extend(["--output-path".into(), output_dir.to_string_lossy().into()]);
+                for (name, value) in env.defines() {
+                    args.extend(["--define".into(), format!("{}={}", name, value)]);
+                }
+            }
+            Toolchain::SvelteKit => {
+                args.extend(["--config".into(), self.write_vite_env_config(env)?]);
+                args.extend(["--mode".into(), mode.into()]);
+            }
+            Toolchain::Next => {
+                if mode != "production" {
+                    warnings.push(format!("next always builds for production; ignoring mode '{}'", mode));
+                }
+                warnings.extend(check_next_env(&self.project.root, env));
+            }
+        }
+        self.run(settings, toolchain.binary().unwrap_or_default(), &args, warnings)
+            .await
+    }
+
+    /// The Vite config for app builds: public variables are exposed on
+    /// `import.meta.env` through `envPrefix`, and `process.env.*` references
+    /// are replaced through `define`. Returns its path.
+    fn write_vite_env_config(&self, env: &ProjectEnv) -> Result<String> {
+        let mut prefixes = vec!["VITE_".to_string()];
+        prefixes.extend(env.prefixes.iter().cloned());
+        let overrides = format!(
+            "{{\n  envPrefix: {},\n  define: {},\n}}",
+            serde_json::to_string(&prefixes)?,
+            serde_json::to_string(&env.defines())?,
+        );
+        let path = write_vite_config(&self.project.root, VITE_CONFIG, &overrides)?;
+        Ok(path.to_string_lossy().to_string())
+    }
+
+    /// Adds what the target needs on top of the toolchain's output: the
+    /// service worker and web manifest of a PWA, or the app shells, sitemap
+    /// and robots.txt of a static site.
+    fn finish_target(
+        &self,
+        toolchain: Toolchain,
+        target: BuildTarget,
+        output_dir: &Path,
+        warnings: &mut Vec<String>,
+    ) -> Result<()> {
+        let config = &self.project.config;
+        match target {
+            BuildTarget::Pwa => {
+                if let Some(progress) = &self.progress {
+                    progress.set_message("Adding service worker...");
+                }
+                warnings.extend(pwa::generate(output_dir, &config.name)?);
+            }
+            BuildTarget::Ssg => {
+                if let Some(progress) = &self.progress {
+                    progress.set_message("Writing routes and sitemap...");
+                }
+                // Bundled SPAs render in the browser; each route of their
+                // router gets the app's HTML so it can be loaded directly.
+                if matches!(toolchain, Toolchain::Vite | Toolchain::AngularCli) {
+                    let router = Router::find(&self.project.root, &config.framework).ok().flatten();
+                    let mut routes = match router {
+                        Some(router) => router.paths(&self.project.root)?,
+                        None => Vec::new(),
+                    };
+                    routes.extend(config.build.ssg.routes.iter().cloned());
+                    ssg::write_app_shells(output_dir, &routes)?;
+                }
+                warnings.extend(ssg::finish(output_dir, &config.build.ssg)?);
+            }
+            BuildTarget::Web | BuildTarget::Lib => {}
+        }
+        Ok(())
+    }
+
+    /// Packages the project as an ES module with type declarations:
+    /// `svelte-package` for SvelteKit, Vite's library mode otherwise.
+    async fn run_library(&self, settings: &BuildSettings<'_>, warnings: &mut Vec<String>) -> Result<()> {
+        let root = &self.project.root;
+        let output_dir = settings.output_dir.to_string_lossy().to_string();
+        if settings.toolchain == Toolchain::SvelteKit {
+            let args = ["-o".to_string(), output_dir];
+            return self.run(settings, "svelte-package", &args, warnings).await;
+        }
+
+        let package = PackageJson::read(root)?;
+        let entry = package
+            .as_ref()
+            .and_then(|package| package.source().map(str::to_string))
+            .or_else(|| {
+                ["src/index.ts", "src/index.tsx", "src/index.js", "src/index.jsx"]
+                    .iter()
+                    .find(|file| root.join(file).is_file())
+                    .map(|file| file.to_string())
+            })
+            .context("The lib target needs an entry point: set \"source\" in package.json or add src/index.ts")?;
+        // Dependencies are imported by the package's users, not bundled.
+        let external = package
+            .as_ref()
+            .map(PackageJson::runtime_dependencies)
+            .unwrap_or_default();
+        let overrides = format!(
+            "{{\n  build: {{\n    \
+               lib: {{ entry: {}, formats: [\"es\"], fileName: \"index\" }},\n    \
+               copyPublicDir: false,\n    \
+               rollupOptions: {{ external: (id) => {}.some((name) => id === name || id.startsWith(name + \"/\")) }},\n  \
+             }},\n}}",
+            serde_json::to_string(&root.join(&entry).to_string_lossy())?,
+            serde_json::to_string(&external)?,
+        );
+        let config_path = write_vite_config(root, VITE_LIB_CONFIG, &overrides)?;
+        let args = [
+            "build".to_string(),
+            "--config".to_string(),
+            config_path.to_string_lossy().to_string(),
+            "--mode".to_string(),
+            settings.mode.to_string(),
+            "--outDir".to_string(),
+            output_dir.clone(),
+            "--emptyOutDir".to_string(),
+        ];
+        self.run(settings, "vite", &args, warnings).await?;
+
+        if !root.join("tsconfig.json").is_file() {
+            warnings.push("No tsconfig.json; the library was built without type declarations".to_string());
+        } else if local_binary(root, "tsc").is_none() {
+            warnings.push("typescript is not installed; the library was built without type declarations".to_string());
+        } else {
+            if let Some(progress) = &self.progress {
+                progress.set_message("Emitting type declarations...");
+            }
+            let args = [
+                "-p".to_string(),
+                "tsconfig.json".to_string(),
+                "--declaration".to_string(),
+                "--emitDeclarationOnly".to_string(),
+                "--noEmit".to_string(),
+                "false".to_string(),
+                "--declarationDir".to_string(),
+                settings.output_dir.join("types").to_string_lossy().to_string(),
+            ];
+            self.run(settings, "tsc", &args, warnings).await?;
+        }
+        Ok(())
+    }
+
+    /// Runs `binary` from `node_modules/.bin`, streaming its output to the
+    /// progress bar and collecting warnings.
+    async fn run(
+        &self,
+        settings: &BuildSettings<'_>,
+        binary: &str,
+        args: &[String],
+        warnings: &mut Vec<String>,
+    ) -> Result<()> {
+        let root = &self.project.root;
+        let program = local_binary(root, binary).with_context(|| {
+            format!(
+                "{} is not installed in {}. Run '{}' first",
+                binary,
+                root.display(),
+                PackageManager::detect(root).install_hint()
+            )
+        })?;
+
+        let mut command = Command::new(&program);
+        command
+            .args(args)
+            .current_dir(root)
+            // Only public variables: bundlers can put any variable they see
+            // into client code, so the shell's are not inherited either.
+            .env_clear()
+            .envs(std::env::vars_os().filter(|(name, _)| is_inherited(&name.to_string_lossy())))
+            .envs(&settings.env.public)
+            .env(CLIENT_ENV, serde_json::to_string(&settings.env.public)?)
+            .env("NEXUS_MODE", settings.mode)
+            .env("NEXUS_TARGET", settings.target.name())
+            .env("NO_COLOR", "1")
+            .env("FORCE_COLOR", "0")
+            .env("CI", "1")
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+        let mut child = command
+            .spawn()
+            .with_context(|| format!("Failed to run {}", program.display()))?;
+
+        // Both streams feed one channel so lines keep their order.
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        let stdout = child.stdout.take().context("No stdout from the bundler")?;
+        let stderr = child.stderr.take().context("No stderr from the bundler")?;
+        let out_sender = sender.clone();
+        tokio::spawn(async move {
+            let mut lines = BufReader::new(stdout).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                let _ = out_sender.send(line);
+            }
+        });
+        tokio::spawn(async move {
+            let mut lines = BufReader::new(stderr).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                let _ = sender.send(line);
+            }
+        });
+
+        let mut tail = std::collections::VecDeque::with_capacity(OUTPUT_TAIL);
+        while let Some(line) = receiver.recv().await {
+            let line = strip_ansi(&line);
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            if let Some(progress) = &self.progress {
+                progress.set_message(format!("{}: {}", binary, line));
+            }
+            // Errors that do not fail the build (type errors in some setups)
+            // are reported with the warnings.
+            if is_diagnostic(line) {
+                warnings.push(line.to_string());
+            }
+            if tail.len() == OUTPUT_TAIL {
+                tail.pop_front();
+            }
+            tail.push_back(line.to_string());
+        }
+
+        let status = child.wait().await?;
+        if !status.success() {
+            anyhow::bail!(
+                "{} failed ({}):\n  {}",
+                binary,
+                status,
+                tail.into_iter().collect::<Vec<_>>().join("\n  ")
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Rejects targets the toolchain cannot build, or cannot build as the
+/// project is configured, naming the setups that can.
+fn check_target(root: &Path, toolchain: Toolchain, target: BuildTarget) -> Result<()> {
+    let static_output = match toolchain {
+        Toolchain::Next => configured_output(root, toolchain) == "out",
+        Toolchain::SvelteKit => ["js", "mjs", "cjs", "ts"].iter().any(|extension| {
+            std::fs::read_to_string(root.join(format!("svelte.config.{}", extension)))
+                .is_ok_and(|config| config.contains("@sveltejs/adapter-static"))
+        }),
+        _ => true,
+    };
+    if target == BuildTarget::Lib && !matches!(toolchain, Toolchain::Vite | Toolchain::SvelteKit) {
+        anyhow::bail!(
+            "The lib target is not supported for {} projects, only for Vite and SvelteKit projects",
+            toolchain.name()
+        );
+    }
+    if !static_output && matches!(target, BuildTarget::Ssg | BuildTarget::Pwa) {
+        anyhow::bail!(
+            "The {} target needs static output: {}",
+            target.name(),
+            if toolchain == Toolchain::Next {
+                "set output: 'export' in next.config"
+            } else {
+                "use @sveltejs/adapter-static in svelte.config.js"
+            }
+        );
+    }
+    Ok(())
+}
+
+/// Writes `file` under the project root: a Vite config that merges
+/// `overrides`, a JS object literal, into the project's own `vite.config.*`.
+fn write_vite_config(root: &Path, file: &str, overrides: &str) -> Result<PathBuf> {
+    let user_config = ["js", "mjs", "ts", "mts"]
+        .iter()
+        .map(|extension| format!("vite.config.{}", extension))
+        .find(|file| root.join(file).is_file());
+    let config = format!(
+        "// Generated by nexus build.\n\
+         import {{ defineConfig, mergeConfig }} from \"vite\";\n\
+         {}\n\
+         export default defineConfig((env) => mergeConfig(typeof base === \"function\" ? base(env) : base, {}));\n",
+        match &user_config {
+            Some(file) => format!("import base from \"../{}\";", file),
+            None => "const base = {};".to_string(),
+        },
+        overrides,
+    );
+    let path = root.join(file);
+    std::fs::create_dir_all(root.join(".nexus"))?;
+    std::fs::write(&path, config).with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(path)
+}
+
+/// Next.js inlines `NEXT_PUBLIC_*` variables itself; others only reach client
+/// code through the `env` option of `next.config.*`, which has to read them
+/// from `NEXUS_CLIENT_ENV`.
+fn check_next_env(root: &Path, env: &ProjectEnv) -> Option<String> {
+    let missing: Vec<&str> = env
+        .public
+        .keys()
+        .filter(|name| !name.starts_with("NEXT_PUBLIC_"))
+        .map(String::as_str)
+        .collect();
+    if missing.is_empty() {
+        return None;
+    }
+    let passes_env = ["js", "mjs", "ts"].iter().any(|extension| {
+        std::fs::read_to_string(root.join(format!("next.config.{}", extension)))
+            .is_ok_and(|config| config.contains(CLIENT_ENV))
+    });
+    (!passes_env).then(|| {
+        format!(
+            "{} stay undefined in client code: add `env: JSON.parse(process.env.{} ?? \"{{}}\")` to next.config",
+            missing.join(", "),
+            CLIENT_ENV
+        )
+    })
+}
+
+fn is_inherited(name: &str) -> bool {
+    INHERITED_ENV.iter().any(|inherited| inherited.eq_ignore_ascii_case(name))
+        || name
+            .get(..INHERITED_ENV_PREFIX.len())
+            .is_some_and(|prefix| prefix.eq_ignore_ascii_case(INHERITED_ENV_PREFIX))
+}
+
+/// `node_modules/.bin/<name>` in the project or, for hoisted workspace
+/// installs, one of its parents.
+fn local_binary(root: &Path, name: &str) -> Option<PathBuf> {
+    let file = if cfg!(windows) {
+        format!("{}.cmd", name)
+    } else {
+        name.to_string()
+    };
+    let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+    root.ancestors()
+        .map(|dir| dir.join("node_modules/.bin").join(&file))
+        .find(|path| path.is_file())
+}
+
+/// Where Next.js and SvelteKit put their output: `distDir` (or `out` for a
+/// static export) in `next.config.*`, and the `pages` directory of
+/// adapter-static in `svelte.config.js`.
+fn configured_output(root: &Path, toolchain: Toolchain) -> String {
+    let (stem, key, default) = match toolchain {
+        Toolchain::Next => ("next.config", "distDir", ".next"),
+        _ => ("svelte.config", "pages", "build"),
+    };
+    let config = ["js", "mjs", "cjs", "ts"]
+        .iter()
+        .find_map(|extension| std::fs::read_to_string(root.join(format!("{}.{}", stem, extension))).ok())
+        .unwrap_or_default();
+    if let Some(value) = quoted_value(&config, key) {
+        return value;
+    }
+    if toolchain == Toolchain::Next && quoted_value(&config, "output").as_deref() == Some("export") {
+        return "out".to_string();
+    }
+    default.to_string()
+}
+
+/// The string after `key:` in a JS config, e.g. `"dist"` in `distDir: "dist"`.
+fn quoted_value(config: &str, key: &str) -> Option<String> {
+    let rest = &config[config.find(&format!("{}:", key))? + key.len() + 1..];
+    let rest = rest.trim_start();
+    let quote = rest.chars().next().filter(|c| matches!(c, '"' | '\'' | '`'))?;
+    let value = &rest[1..];
+    Some(value[..value.find(quote)?].to_string())
+}
+
+/// Recognizes the error and warning lines of Vite/Rollup, esbuild (Angular),
+/// Next.js and TypeScript.
+fn is_diagnostic(line: &str) -> bool {
+    let lower = line.to_lowercase();
+    ["error", "warn", "failed to compile", "type error", "(!)", "✘", "⨯", "⚠", "▲"]
+        .iter()
+        .any(|prefix| lower.starts_with(prefix))
+        || lower.contains("[error]")
+        || lower.contains("[warning]")
+        || lower.contains(": error ts")
+}
+
+/// Removes terminal escape sequences that slip through `NO_COLOR`.
+fn strip_ansi(line: &str) -> String {
+    let mut output = String::with_capacity(line.len());
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c == '\u{1b}' {
+            // CSI sequences end with a letter.
+            for next in chars.by_ref() {
+                if next.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        } else {
+            output.push(c);
+        }
+    }
+    output
 }
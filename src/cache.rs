@@ -0,0 +1,234 @@
+use anyhow::{Context, Result};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Build cache directory, relative to the project root.
+pub const CACHE_DIR: &str = ".nexus/cache";
+
+const MANIFEST: &str = "manifest.json";
+/// Copy of the last build's output.
+const OUTPUT: &str = "output";
+/// Per-file results of the static site builder, named by their cache key.
+const FILES: &str = "files";
+
+/// Hex FNV-1a hash of `bytes`. Stable across runs and Rust versions, unlike
+/// `DefaultHasher`.
+pub fn content_hash(bytes: &[u8]) -> String {
+    let hash = bytes.iter().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(0x100000001b3)
+    });
+    format!("{:016x}", hash)
+}
+
+/// Hash of several parts, kept apart so `("ab", "c")` and `("a", "bc")` differ.
+pub fn key_of(parts: &[&[u8]]) -> String {
+    let mut joined = Vec::new();
+    for part in parts {
+        joined.extend_from_slice(&(part.len() as u64).to_le_bytes());
+        joined.extend_from_slice(part);
+    }
+    content_hash(&joined)
+}
+
+/// Cache hits and misses reported after a build.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CacheStats {
+    pub hits: usize,
+    pub misses: usize,
+    /// Nothing changed and the previous output was reused.
+    pub restored: bool,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Manifest {
+    /// Key of the build whose output is stored.
+    key: String,
+    /// Input path to content hash.
+    inputs: BTreeMap<String, String>,
+    /// Warnings of that build, repeated when its output is restored.
+    #[serde(default)]
+    warnings: Vec<String>,
+}
+
+/// The cache of one project under `.nexus/cache`. The key of a build covers
+/// every input file, `nexus.yaml` included, plus the toolchain, mode, target,
+/// output directory and the inputs of the workspace members it depends on.
+pub struct BuildCache {
+    dir: PathBuf,
+    manifest: Manifest,
+    used_files: std::sync::Mutex<HashSet<String>>,
+}
+
+impl BuildCache {
+    pub fn open(root: &Path) -> Self {
+        let dir = root.join(CACHE_DIR);
+        let manifest = std::fs::read_to_string(dir.join(MANIFEST))
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        Self {
+            dir,
+            manifest,
+            used_files: Default::default(),
+        }
+    }
+
+    /// Content hashes of the project's files, skipping dependencies, hidden
+    /// directories and `output`.
+    pub fn hash_inputs(root: &Path, output: &Path) -> Result<BTreeMap<String, String>> {
+        let mut paths = Vec::new();
+        let entries = WalkDir::new(root).into_iter().filter_entry(|entry| {
+            let name = entry.file_name().to_string_lossy();
+            entry.depth() == 0
+                || !((entry.file_type().is_dir() && name.starts_with('.'))
+                    || name == "node_modules"
+                    || entry.path() == output)
+        });
+        for entry in entries {
+            let entry = entry?;
+            if entry.file_type().is_file() {
+                paths.push(entry.into_path());
+            }
+        }
+        paths
+            .into_par_iter()
+            .map(|path| {
+                let contents = std::fs::read(&path)
+                    .with_context(|| format!("Failed to read {}", path.display()))?;
+                let relative = path.strip_prefix(root)?.to_string_lossy().replace('\\', "/");
+                Ok((relative, content_hash(&contents)))
+            })
+            .collect()
+    }
+
+    pub fn build_key(inputs: &BTreeMap<String, String>, settings: &[&str]) -> String {
+        let mut parts: Vec<&[u8]> = settings.iter().map(|setting| setting.as_bytes()).collect();
+        for (path, hash) in inputs {
+            parts.push(path.as_bytes());
+            parts.push(hash.as_bytes());
+        }
+        key_of(&parts)
+    }
+
+    /// Inputs unchanged since the last build (hits) and new or changed ones (misses).
+    pub fn compare(&self, inputs: &BTreeMap<String, String>) -> CacheStats {
+        let hits = inputs
+            .iter()
+            .filter(|(path, hash)| self.manifest.inputs.get(*path) == Some(*hash))
+            .count();
+        CacheStats {
+            hits,
+            misses: inputs.len() - hits,
+            restored: false,
+        }
+    }
+
+    /// Replaces `output` with the stored output if the last build had `key`,
+    /// returning that build's warnings.
+    pub fn restore(&self, key: &str, output: &Path) -> Result<Option<Vec<String>>> {
+        let stored = self.dir.join(OUTPUT);
+        if self.manifest.key != key || !stored.is_dir() {
+            return Ok(None);
+        }
+        if output.exists() {
+            std::fs::remove_dir_all(output)
+                .with_context(|| format!("Failed to clear {}", output.display()))?;
+        }
+        copy_dir(&stored, output)?;
+        Ok(Some(self.manifest.warnings.clone()))
+    }
+
+    /// Records a finished build and keeps a copy of its output.
+    pub fn store(
+        &mut self,
+        key: &str,
+        inputs: BTreeMap<String, String>,
+        output: &Path,
+        warnings: &[String],
+    ) -> Result<()> {
+        let stored = self.dir.join(OUTPUT);
+        if stored.exists() {
+            std::fs::remove_dir_all(&stored)?;
+        }
+        copy_dir(output, &stored)?;
+        self.manifest = Manifest {
+            key: key.to_string(),
+            inputs,
+            warnings: warnings.to_vec(),
+        };
+        std::fs::write(
+            self.dir.join(MANIFEST),
+            serde_json::to_string_pretty(&self.manifest)?,
+        )
+        .context("Failed to write the build cache manifest")?;
+        Ok(())
+    }
+
+    /// A file result stored under `key` by an earlier build.
+    pub fn get(&self, key: &str) -> Option<Vec<u8>> {
+        let contents = std::fs::read(self.dir.join(FILES).join(key)).ok()?;
+        self.used_files.lock().unwrap().insert(key.to_string());
+        Some(contents)
+    }
+
+    pub fn put(&self, key: &str, contents: &[u8]) -> Result<()> {
+        let dir = self.dir.join(FILES);
+        std::fs::create_dir_all(&dir)?;
+        std::fs::write(dir.join(key), contents)?;
+        self.used_files.lock().unwrap().insert(key.to_string());
+        Ok(())
+    }
+
+    /// Drops file results this build did not use, so the cache does not grow
+    /// with every edit.
+    pub fn prune_files(&self) -> Result<()> {
+        let used = self.used_files.lock().unwrap();
+        let Ok(entries) = std::fs::read_dir(self.dir.join(FILES)) else {
+            return Ok(());
+        };
+        for entry in entries.filter_map(|entry| entry.ok()) {
+            if !used.contains(entry.file_name().to_string_lossy().as_ref()) {
+                std::fs::remove_file(entry.path())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Deletes the project's build cache. Returns the bytes freed.
+pub fn clear(root: &Path) -> Result<u64> {
+    let dir = root.join(CACHE_DIR);
+    if !dir.exists() {
+        return Ok(0);
+    }
+    let size = dir_size(&dir);
+    std::fs::remove_dir_all(&dir).with_context(|| format!("Failed to remove {}", dir.display()))?;
+    Ok(size)
+}
+
+/// Total size of the files under `dir`.
+pub fn dir_size(dir: &Path) -> u64 {
+    WalkDir::new(dir)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .map(|entry| entry.metadata().map(|m| m.len()).unwrap_or(0))
+        .sum()
+}
+
+fn copy_dir(from: &Path, to: &Path) -> Result<()> {
+    for entry in WalkDir::new(from) {
+        let entry = entry?;
+        let destination = to.join(entry.path().strip_prefix(from)?);
+        if entry.file_type().is_dir() {
+            std::fs::create_dir_all(&destination)?;
+        } else {
+            std::fs::copy(entry.path(), &destination)
+                .with_context(|| format!("Failed to copy {}", entry.path().display()))?;
+        }
+    }
+    Ok(())
+}
@@ -1,22 +1,49 @@
 use crate::{
     ai::AIModel,
-    builder::ProjectBuilder,
+    builder::{BuildResult, ProjectBuilder},
+    cache,
+    components,
+    manifest::{self, BuildManifest},
     config::Config,
-    project::Project,
-    types::{ApiCommands, ConfigCommands, DbCommands, DeployTarget, FsCommands, PluginCommands},
+    naming::ComponentName,
+    package_json::{self, DependencyKind, PackageJson, PackageManager, PACKAGE_JSON},
+    project::{self, Project},
+    recipes,
+    registry::{InstallSource, TemplateRegistry},
+    templates::{app_entry_file, canonical_framework, ProjectTemplate},
+    schema::{self, BudgetAction, ProjectConfig, CURRENT_SCHEMA_VERSION},
+    wizard::{self, WizardDefaults},
+    watch::{self, Watcher},
+    workspace::{select_projects, Workspace},
+    writer::{ConflictPolicy, FileWriter, WriteOptions},
+    types::{
+        ApiCommands, BuildTarget, ComponentOptions, ConfigCommands, DbCommands, DepsCommands,
+        DeployTarget, FsCommands, MemberSelection, PluginCommands, TemplateCommands,
+    },
+};
+#[cfg(feature = "web")]
+use crate::{
+    dev_server::{self, LiveReload},
+    proxy,
 };
 use anyhow::{Context, Result};
 use colored::*;
 use indicatif::{ProgressBar, ProgressStyle};
-use std::io;
+use std::collections::BTreeMap;
+use std::io::{self, IsTerminal};
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
+/// Creates a project. `ai_model` enables AI assistance with that model, and
+/// `install` runs the detected package manager once the files are written.
 pub async fn new_project(
     name: &str,
     template: &str,
     framework: &str,
-    use_ai: bool,
+    ai_model: Option<&str>,
+    variables: &BTreeMap<String, String>,
+    install: bool,
+    write_options: WriteOptions,
 ) -> Result<()> {
     println!("🚀 {}", "Creating new project:".green().bold());
     println!("   Name: {}", name.cyan());
@@ -32,7 +59,12 @@ pub async fn new_project(
 
     pb.set_message("Initializing project structure...");
 
-    let project = Project::new(name, template, framework)?;
+    let mut project =
+        Project::new(name, template, framework, variables)?.with_write_options(write_options);
+    project.config.ai.enabled = ai_model.is_some();
+    if let Some(ai_model) = ai_model {
+        project.config.ai.model = ai_model.to_string();
+    }
 
     pb.set_message("Creating directories...");
     project.create_structure()?;
@@ -40,9 +72,9 @@ pub async fn new_project(
     pb.set_message("Generating files...");
     project.generate_files()?;
 
-    if use_ai {
+    if let Some(ai_model) = ai_model {
         pb.set_message("🤖 AI is enhancing your project...");
-        let mut ai_model = AIModel::new("phi-3-mini").await?;
+        let mut ai_model = AIModel::new(ai_model).await?;
 
         let readme_prompt = format!(
             "Generate a comprehensive README.md for a {} project named {} using {} framework",
@@ -59,16 +91,32 @@ pub async fn new_project(
         );
 
         let component_code = ai_model.generate(&component_prompt, 1000).await?;
-        project.write_file("src/App.js", &component_code.content)?;
+        project.write_file(app_entry_file(&project.config.framework), &component_code.content)?;
+    }
+
+    if project.writer.is_preview() {
+        pb.finish_and_clear();
+        project.writer.finish();
+        return Ok(());
     }
 
     pb.finish_with_message("✅ Project created successfully!");
+    project.writer.finish();
 
     println!("\n📁 Project structure:");
     print_tree(Path::new(name), 0)?;
 
+    let has_package_json = project.root.join(PACKAGE_JSON).exists();
+    let package_manager = PackageManager::detect(&project.root);
+    if install && has_package_json {
+        package_manager.install(&project.root)?;
+    }
+
     println!("\n🎯 Next steps:");
     println!("   cd {}", name.cyan());
+    if has_package_json && !install {
+        println!("   {} install dependencies", package_manager.install_hint().cyan().bold());
+    }
     println!("   {} start development server", "nexus serve".cyan().bold());
     println!("   {} build for production", "nexus build".cyan().bold());
     println!("   {} deploy to cloud", "nexus deploy".cyan().bold());
@@ -76,12 +124,302 @@ pub async fn new_project(
     Ok(())
 }
 
+/// `nexus new` without a name: collects the options in the terminal wizard,
+/// using the flags as initial selections.
+pub async fn new_project_wizard(
+    template: &str,
+    framework: &str,
+    use_ai: bool,
+    ai_model: &str,
+    variables: &BTreeMap<String, String>,
+    install: bool,
+    write_options: WriteOptions,
+) -> Result<()> {
+    if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+        anyhow::bail!(
+            "Missing project name. Run 'nexus new <name>' with flags, or start the wizard from an interactive terminal."
+        );
+    }
+
+    let defaults = WizardDefaults {
+        template,
+        framework,
+        ai: use_ai,
+        ai_model,
+        variables,
+    };
+    let Some(answers) = wizard::run(&defaults)? else {
+        println!("👋 Project wizard cancelled");
+        return Ok(());
+    };
+
+    let mut variables = variables.clone();
+    variables.extend(answers.variables);
+    new_project(
+        &answers.name,
+        &answers.template,
+        &answers.framework,
+        answers.ai.then_some(answers.ai_model.as_str()),
+        &variables,
+        install,
+        write_options,
+    )
+    .await
+}
+
+/// Applies feature recipes to the current project, or lists the built-in
+/// recipes when none are named.
+pub async fn add_recipes(
+    names: &[String],
+    variables: &BTreeMap<String, String>,
+    install: bool,
+    project_dir: &Path,
+    write_options: WriteOptions,
+) -> Result<()> {
+    if names.is_empty() {
+        println!("🧩 Available recipes:");
+        for recipe in recipes::builtin()? {
+            let frameworks = if recipe.manifest.frameworks.is_empty() {
+                "any framework".to_string()
+            } else {
+                recipe.manifest.frameworks.join(", ")
+            };
+            println!(
+                "  - {} {} {}",
+                recipe.manifest.name.cyan(),
+                format!("({})", frameworks).dimmed(),
+                recipe.manifest.description
+            );
+        }
+        println!("\n💡 Use {} to add one", "nexus add <recipe>".cyan());
+        return Ok(());
+    }
+
+    let mut project = Project::discover(project_dir)?.with_write_options(write_options);
+    let recipes = names
+        .iter()
+        .map(|name| recipes::find(name))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut dependencies_added = false;
+    for recipe in &recipes {
+        let name = &recipe.manifest.name;
+        println!("🧩 {} {}", "Adding recipe:".green().bold(), name.cyan());
+        let changes = recipes::apply(&mut project, recipe, variables)
+            .with_context(|| format!("Failed to add recipe '{}'", name))?;
+
+        for file in &changes.patched_files {
+            println!("   Patched {}", file.cyan());
+        }
+        if !changes.package.dependencies.is_empty() {
+            println!("   Dependencies: {}", changes.package.dependencies.join(", ").cyan());
+            dependencies_added = true;
+        }
+        if !changes.package.scripts.is_empty() {
+            println!("   Scripts: {}", changes.package.scripts.join(", ").cyan());
+        }
+        if !changes.newly_recorded {
+            println!("   {} was already applied; only missing changes were made", name.yellow());
+        }
+    }
+
+    project.writer.finish();
+    if project.writer.is_preview() {
+        return Ok(());
+    }
+
+    println!("\n✅ Added {}", names.join(", ").cyan());
+    if dependencies_added {
+        finish_dependency_change(&project.root, install)?;
+    }
+    Ok(())
+}
+
+/// `nexus deps`: edits package.json and optionally runs the install.
+pub async fn handle_deps(
+    command: &DepsCommands,
+    project_dir: &Path,
+    write_options: WriteOptions,
+) -> Result<()> {
+    let project = Project::discover(project_dir)?.with_write_options(write_options);
+    let existing = PackageJson::read(&project.root)?;
+
+    let (mut package, install) = match (command, existing) {
+        (DepsCommands::List, existing) => return list_dependencies(&project, existing.as_ref()),
+        (DepsCommands::Add { install, .. }, existing) => (
+            existing.unwrap_or_else(|| PackageJson::new(&project.config.name)),
+            *install,
+        ),
+        (DepsCommands::Remove { install, .. } | DepsCommands::Upgrade { install, .. }, Some(package)) => {
+            (package, *install)
+        }
+        (_, None) => anyhow::bail!("No {} in {}", PACKAGE_JSON, project.root.display()),
+    };
+
+    let mut changed = false;
+    match command {
+        DepsCommands::List => {}
+        DepsCommands::Add { packages, dev, .. } => {
+            let kind = if *dev { DependencyKind::Dev } else { DependencyKind::Prod };
+            for spec in packages {
+                let (name, version) = package_json::parse_spec(spec)?;
+                let version = match version {
+                    Some(version) => version.to_string(),
+                    None => format!("^{}", package_json::latest_version(name)?),
+                };
+                if package.set_dependency(name, &version, kind)? {
+                    println!("➕ {} {} {}", name.cyan(), version, format!("({})", kind.section()).dimmed());
+                    changed = true;
+                } else {
+                    println!("✅ {} {} is already in {}", name.cyan(), version, kind.section());
+                }
+            }
+        }
+        DepsCommands::Remove { packages, .. } => {
+            for name in packages {
+                match package.remove_dependency(name) {
+                    Some(version) => {
+                        println!("➖ {} {}", name.cyan(), version.dimmed());
+                        changed = true;
+                    }
+                    None => println!("⚠️  {} is not a dependency", name.yellow()),
+                }
+            }
+        }
+        DepsCommands::Upgrade { packages, .. } => {
+            let targets = if packages.is_empty() {
+                package
+                    .dependencies()
+                    .into_iter()
+                    .map(|(_, name, _)| (name, None))
+                    .collect()
+            } else {
+                packages
+                    .iter()
+                    .map(|spec| {
+                        package_json::parse_spec(spec)
+                            .map(|(name, version)| (name.to_string(), version.map(str::to_string)))
+                    })
+                    .collect::<Result<Vec<_>>>()?
+            };
+
+            for (name, version) in targets {
+                let Some((kind, current)) = package
+                    .dependency(&name)
+                    .map(|(kind, current)| (kind, current.to_string()))
+                else {
+                    anyhow::bail!("{} is not a dependency. Add it with 'nexus deps add {}'", name, name);
+                };
+                let range = match version {
+                    Some(version) => version,
+                    None => {
+                        let latest = package_json::latest_version(&name)?;
+                        match package_json::upgraded_range(&current, &latest) {
+                            Some(range) => range,
+                            None => {
+                                println!("⏭️  {} {} is not a version range; skipped", name.cyan(), current);
+                                continue;
+                            }
+                        }
+                    }
+                };
+                if package.set_dependency(&name, &range, kind)? {
+                    println!("⬆️  {} {} → {}", name.cyan(), current.dimmed(), range.green());
+                    changed = true;
+                } else {
+                    println!("✅ {} {} is up to date", name.cyan(), current);
+                }
+            }
+        }
+    }
+
+    if !changed {
+        return Ok(());
+    }
+    project
+        .writer
+        .with_conflict(ConflictPolicy::Overwrite)
+        .write(PACKAGE_JSON, &package.render()?)?;
+    project.writer.finish();
+    if project.writer.is_preview() {
+        return Ok(());
+    }
+    finish_dependency_change(&project.root, install)
+}
+
+fn list_dependencies(project: &Project, package: Option<&PackageJson>) -> Result<()> {
+    let Some(package) = package else {
+        println!("📦 {} has no {}", project.config.name.cyan(), PACKAGE_JSON);
+        return Ok(());
+    };
+    println!(
+        "📦 Dependencies of {} {}",
+        project.config.name.cyan().bold(),
+        format!("({})", PackageManager::detect(&project.root).command()).dimmed()
+    );
+    let mut section = None;
+    for (kind, name, version) in package.dependencies() {
+        if section != Some(kind) {
+            println!("\n   {}", kind.section().bold());
+            section = Some(kind);
+        }
+        println!("   - {} {}", name.cyan(), version.dimmed());
+    }
+    Ok(())
+}
+
+/// Runs the install when asked to, and otherwise says how to.
+fn finish_dependency_change(root: &Path, install: bool) -> Result<()> {
+    let manager = PackageManager::detect(root);
+    if install {
+        return manager.install(root);
+    }
+    println!(
+        "\n💡 Run {} (or pass {}) to install the changes",
+        manager.install_hint().cyan().bold(),
+        "--install".cyan()
+    );
+    Ok(())
+}
+
+/// Points out packages that generated code imports but the enclosing project
+/// does not depend on.
+fn report_missing_packages(start: &Path, code: &str) -> Result<()> {
+    let Some(root) = project::find_root(start) else {
+        return Ok(());
+    };
+    let package = PackageJson::read(&root)?;
+    let missing: Vec<String> = package_json::imported_packages(code)
+        .into_iter()
+        .filter(|name| match &package {
+            Some(package) => package.dependency(name).is_none(),
+            None => true,
+        })
+        .collect();
+    if !missing.is_empty() {
+        println!(
+            "\n📦 The generated code imports packages missing from {}: {}",
+            PACKAGE_JSON,
+            missing.join(", ").yellow()
+        );
+        println!("   Add them with {}", format!("nexus deps add {}", missing.join(" ")).cyan());
+    }
+    Ok(())
+}
+
 pub async fn create_component(
     component_type: &crate::types::ComponentType,
     name: &str,
     use_ai: bool,
-    framework: &str,
+    framework: Option<&str>,
+    options: &ComponentOptions,
+    project_dir: &Path,
+    write_options: WriteOptions,
 ) -> Result<()> {
+    let project = Project::discover(project_dir)?.with_write_options(write_options);
+    let framework = canonical_framework(framework.unwrap_or(&project.config.framework))?;
+    let component_name = ComponentName::parse(name)?;
+
     println!("🛠️ {}", "Creating component:".green().bold());
     println!("   Type: {:?}", component_type);
     println!("   Name: {}", name.cyan());
@@ -94,12 +432,6 @@ pub async fn create_component(
             .template("{spinner} {msg}")?,
     );
 
-    if !Path::new("nexus.yaml").exists() {
-        anyhow::bail!("Not in a Nexus project directory. Run 'nexus new' first.");
-    }
-
-    let project = Project::load(".")?;
-
     if use_ai {
         pb.set_message("🤖 AI is generating component...");
         let mut ai_model = AIModel::new("phi-3-mini").await?;
@@ -113,18 +445,26 @@ pub async fn create_component(
 
         let code = ai_model.generate(&prompt, 1500).await?;
 
-        let file_extension = match framework {
-            "react" | "nextjs" => "tsx",
-            "vue" => "vue",
-            "svelte" => "svelte",
-            "angular" => "component.ts",
-            _ => "jsx",
-        };
+        let file_extension = components::main_extension(framework, component_type);
 
-        let file_path = format!("src/components/{}.{}", name, file_extension);
+        let file_name = match framework {
+            "angular" => component_name.kebab(),
+            _ => component_name.pascal(),
+        };
+        let file_path = format!(
+            "src/components/{}",
+            component_name.nested(&format!("{}.{}", file_name, file_extension))
+        );
         project.write_file(&file_path, &code.content)?;
 
+        if project.writer.is_preview() {
+            pb.finish_and_clear();
+            project.writer.finish();
+            return Ok(());
+        }
+
         pb.finish_with_message("✅ AI-generated component created!");
+        project.writer.finish();
 
         println!("\n📝 Component created at: {}", file_path.cyan());
         println!("✨ Features included:");
@@ -132,26 +472,264 @@ pub async fn create_component(
         println!("   - Modern design patterns");
         println!("   - Responsive layout");
         println!("   - Accessibility features");
+        report_missing_packages(&project.root, &code.content)?;
     } else {
         pb.set_message("Generating component from template...");
 
-        let component = project.generate_component(component_type.template_name(), name, framework)?;
+        let component = project.generate_component(component_type, &component_name, framework, options)?;
+        for flag in &component.skipped {
+            println!(
+                "⚠️  {} does not apply to {} components; skipped",
+                flag,
+                component_type.template_name()
+            );
+        }
+
+        if project.writer.is_preview() {
+            pb.finish_and_clear();
+            project.writer.finish();
+            return Ok(());
+        }
+
+        if !project.writer.wrote_anything() {
+            pb.finish_with_message("⏭️  Component left unchanged");
+            project.writer.finish();
+            return Ok(());
+        }
 
         pb.finish_with_message("✅ Component created!");
+        project.writer.finish();
 
-        println!(
-            "\n📝 Component created at: {}",
-            component.path.display().to_string().cyan()
+        println!("\n📝 Files:");
+        for file in &component.files {
+            println!("   {}", file.display().to_string().cyan());
+        }
+        let routable = matches!(
+            component_type,
+            crate::types::ComponentType::Page | crate::types::ComponentType::Layout
         );
+        match &component.route {
+            Some(route) => println!("\n🧭 Route: {}", route.cyan()),
+            None if routable && !options.no_route => println!(
+                "\nℹ️  {} projects have no router; the {} was not registered",
+                framework,
+                component_type.template_name()
+            ),
+            None => {}
+        }
+        if let Some((endpoint, hint)) = &component.endpoint {
+            println!("\n🔌 Endpoint: {}", endpoint.cyan());
+            if let Some(hint) = hint {
+                println!("💡 {}", hint);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Builds the current project, or workspace members in dependency order.
+pub async fn build_project(
+    mode: Option<&str>,
+    target: Option<BuildTarget>,
+    out_dir: Option<&Path>,
+    use_cache: bool,
+    watch: bool,
+    project_dir: &Path,
+    selection: &MemberSelection,
+) -> Result<()> {
+    let projects = select_projects(project_dir, &selection.members, selection.workspace, true)?;
+    let multiple = projects.len() > 1;
+
+    for (index, project) in projects.iter().enumerate() {
+        if multiple {
+            print_member_header(project, index, projects.len());
+        }
+        let out_dir = member_out_dir(out_dir, project, multiple);
+        match build_member(project, mode, target, out_dir.as_deref(), use_cache).await {
+            // The watcher keeps running so the next change can fix the build.
+            Err(e) if watch => println!("\n❌ {} {:#}", "Build failed:".red().bold(), e),
+            result => {
+                result?;
+            }
+        }
     }
 
+    if watch {
+        watch_builds(mode, target, out_dir, use_cache, project_dir, selection).await?;
+    }
     Ok(())
 }
 
-pub async fn build_project(mode: &str, target: &str, out_dir: Option<&Path>) -> Result<()> {
+/// Several members cannot share one output directory, so each gets a
+/// subdirectory of `--out-dir`.
+fn member_out_dir(out_dir: Option<&Path>, project: &Project, multiple: bool) -> Option<PathBuf> {
+    out_dir.map(|dir| {
+        if multiple {
+            dir.join(&project.config.name)
+        } else {
+            dir.to_path_buf()
+        }
+    })
+}
+
+/// Rebuilds after each burst of changes until Ctrl+C. Members are reloaded
+/// every time, so edits to nexus.yaml apply. Only members with a change and
+/// the members that depend on them, directly or not, are rebuilt; dependents
+/// skip the build cache, as their own files did not change.
+async fn watch_builds(
+    mode: Option<&str>,
+    target: Option<BuildTarget>,
+    out_dir: Option<&Path>,
+    use_cache: bool,
+    project_dir: &Path,
+    selection: &MemberSelection,
+) -> Result<()> {
+    let projects = select_projects(project_dir, &selection.members, selection.workspace, true)?;
+    let multiple = projects.len() > 1;
+    let roots: Vec<PathBuf> = projects.iter().map(|project| project.root.clone()).collect();
+    let mut ignored = Vec::new();
+    for project in &projects {
+        ignored.push(project.root.join(&project.config.build.out_dir));
+        if let Some(dir) = member_out_dir(out_dir, project, multiple) {
+            ignored.push(std::env::current_dir()?.join(dir));
+        }
+    }
+    let mut watcher = Watcher::new(roots, ignored);
+    println!("\n👀 {} {}", "Watching for changes...".cyan().bold(), "(Ctrl+C to stop)".dimmed());
+
+    loop {
+        let changed = tokio::select! {
+            changed = watcher.changes() => changed,
+            _ = tokio::signal::ctrl_c() => break,
+        };
+        let projects = match select_projects(project_dir, &selection.members, selection.workspace, true) {
+            Ok(projects) => projects,
+            Err(e) => {
+                println!("❌ {:#}", e);
+                continue;
+            }
+        };
+        let is_changed = |root: &Path| changed.iter().any(|path| path.starts_with(root));
+        let Some(first) = projects.iter().find(|project| is_changed(&project.root)) else {
+            continue;
+        };
+        let workspace = match projects[0].workspace_root.as_ref().map(Workspace::load).transpose() {
+            Ok(workspace) => workspace,
+            Err(e) => {
+                println!("❌ {:#}", e);
+                continue;
+            }
+        };
+
+        let shown = watch::display_path(&changed[0], &first.root);
+        let more = match changed.len() {
+            1 => String::new(),
+            count => format!(" and {} more", count - 1),
+        };
+        println!("\n🔄 {}{}", shown.cyan(), more.dimmed());
+        // In build order, so dependencies are rebuilt before their dependents.
+        for project in &projects {
+            let dependency_changed = workspace.as_ref().is_some_and(|workspace| {
+                workspace
+                    .dependencies_of(&project.config.name)
+                    .iter()
+                    .any(|dependency| is_changed(&dependency.root))
+            });
+            if !dependency_changed && !is_changed(&project.root) {
+                continue;
+            }
+            let out_dir = member_out_dir(out_dir, project, multiple);
+            let use_cache = use_cache && !dependency_changed;
+            // Already reported; the next change may fix it.
+            let _ = rebuild_member(project, mode, target, out_dir.as_deref(), use_cache).await;
+        }
+    }
+
+    println!("\n👋 Stopped watching");
+    Ok(())
+}
+
+/// A build in watch mode, reported on one line. Errors are printed as well
+/// as returned, so the watcher can keep going.
+async fn rebuild_member(
+    project: &Project,
+    mode: Option<&str>,
+    target: Option<BuildTarget>,
+    out_dir: Option<&Path>,
+    use_cache: bool,
+) -> Result<BuildResult> {
+    let mode = mode.unwrap_or(&project.config.build.mode);
+    let target = target.unwrap_or(project.config.build.target);
+    let builder = ProjectBuilder::new(project).with_cache(use_cache);
+    let result = match builder.build(mode, target, out_dir).await {
+        Ok(result) => result,
+        Err(e) => {
+            println!("❌ {} {:#}", project.config.name.bold(), e);
+            return Err(e);
+        }
+    };
+
+    let mut parts = vec![
+        format!("{} files", result.file_count),
+        format_bytes(result.manifest.total.size),
+    ];
+    if let Some(previous) = &result.previous_manifest {
+        let change = result.manifest.total.size as i64 - previous.total.size as i64;
+        if change != 0 {
+            parts.push(format_change(change));
+        }
+    }
+    if use_cache {
+        parts.push(if result.cache.restored {
+            "cached".to_string()
+        } else {
+            format!("cache {}/{}", result.cache.hits, result.cache.misses)
+        });
+    }
+    let mut line = format!(
+        "✅ {} {:.2}s · {}",
+        project.config.name.bold(),
+        result.build_time,
+        parts.join(" · ")
+    );
+    let warnings = result.warnings.as_ref().map_or(0, Vec::len);
+    if warnings > 0 {
+        line.push_str(&format!(" · {}", format!("{} warnings", warnings).yellow()));
+    }
+    match result.manifest.check_budgets(&project.config.build.budgets) {
+        Ok(violations) if !violations.is_empty() => {
+            line.push_str(&format!(" · {}", format!("{} over budget", violations.len()).red()));
+        }
+        Ok(_) => {}
+        Err(e) => line.push_str(&format!(" · {}", format!("{:#}", e).red())),
+    }
+    println!("{}", line);
+    Ok(result)
+}
+
+fn print_member_header(project: &Project, index: usize, count: usize) {
+    println!(
+        "\n📦 {} {}",
+        project.config.name.cyan().bold(),
+        format!("({}/{})", index + 1, count).dimmed()
+    );
+}
+
+/// Unset arguments fall back to the `build` section of the project's nexus.yaml.
+async fn build_member(
+    project: &Project,
+    mode: Option<&str>,
+    target: Option<BuildTarget>,
+    out_dir: Option<&Path>,
+    use_cache: bool,
+) -> Result<BuildResult> {
+    let mode = mode.unwrap_or(&project.config.build.mode);
+    let target = target.unwrap_or(project.config.build.target);
+
     println!("🔨 {}", "Building project:".green().bold());
     println!("   Mode: {}", mode.cyan());
-    println!("   Target: {}", target.cyan());
+    println!("   Target: {}", target.name().cyan());
 
     let pb = ProgressBar::new_spinner();
     pb.set_style(
@@ -160,24 +738,61 @@ pub async fn build_project(mode: &str, target: &str, out_dir: Option<&Path>) ->
             .template("{spinner} {msg}")?,
     );
 
-    pb.set_message("Loading project...");
-    let project = Project::load(".")?;
-
     pb.set_message("Initializing builder...");
-    let builder = ProjectBuilder::new(&project);
+    let builder = ProjectBuilder::new(project)
+        .with_progress(pb.clone())
+        .with_cache(use_cache);
+    println!("   Toolchain: {}", builder.toolchain()?.name().cyan());
 
     pb.set_message("Building...");
-    let build_result = builder.build(mode, target, out_dir).await?;
+    let build_result = match builder.build(mode, target, out_dir).await {
+        Ok(result) => result,
+        Err(e) => {
+            pb.finish_with_message("❌ Build failed");
+            return Err(e);
+        }
+    };
 
     pb.finish_with_message("✅ Build completed!");
 
     println!("\n📊 Build Statistics:");
     println!("   Output: {}", build_result.output_dir.display().to_string().cyan());
-    println!("   Size: {:.2} MB", build_result.size_mb);
+    let total = build_result.manifest.total;
+    println!(
+        "   Size: {:.2} MB (gzip {}, brotli {})",
+        build_result.size_mb,
+        format_bytes(total.gzip),
+        format_bytes(total.brotli)
+    );
     println!("   Files: {}", build_result.file_count);
     println!("   Time: {:.2}s", build_result.build_time);
+    if use_cache {
+        let cache = build_result.cache;
+        let reused = if cache.restored {
+            " (unchanged, previous output reused)".green().to_string()
+        } else {
+            String::new()
+        };
+        println!("   Cache: {} hits, {} misses{}", cache.hits, cache.misses, reused);
+    }
+    println!("   Manifest: {}", manifest::MANIFEST_FILE.dimmed());
+    if !build_result.env_files.is_empty() || !build_result.public_env.is_empty() {
+        let files = match build_result.env_files.as_slice() {
+            [] => "no .env files".to_string(),
+            files => files.join(", "),
+        };
+        let public = match build_result.public_env.as_slice() {
+            [] => "none public".to_string(),
+            names => format!("public: {}", names.join(", ")),
+        };
+        println!("   Env: {} ({})", files.cyan(), public.dimmed());
+    }
 
-    if let Some(warnings) = build_result.warnings {
+    if let Some(previous) = &build_result.previous_manifest {
+        print_size_changes(&build_result.manifest, previous);
+    }
+
+    if let Some(warnings) = &build_result.warnings {
         if !warnings.is_empty() {
             println!("\n⚠️  Warnings:");
             for warning in warnings {
@@ -186,41 +801,156 @@ pub async fn build_project(mode: &str, target: &str, out_dir: Option<&Path>) ->
         }
     }
 
-    Ok(())
+    let budgets = &project.config.build.budgets;
+    let violations = build_result.manifest.check_budgets(budgets)?;
+    if !violations.is_empty() {
+        let fail = budgets.on_exceed == BudgetAction::Error;
+        println!("\n{} Size budgets exceeded ({}):", if fail { "❌" } else { "⚠️ " }, budgets.measure.name());
+        for violation in &violations {
+            let subject = violation.path.as_deref().unwrap_or("total");
+            let line = format!(
+                "{} is {}, over the {} budget",
+                subject,
+                format_bytes(violation.size),
+                violation.limit
+            );
+            println!("   - {}", if fail { line.red() } else { line.yellow() });
+        }
+        if fail {
+            anyhow::bail!("{} size budget(s) exceeded", violations.len());
+        }
+    } else if !budgets.is_empty() {
+        println!("\n✅ Within size budgets ({})", budgets.measure.name());
+    }
+
+    Ok(build_result)
 }
 
-pub async fn serve_project(port: u16, host: &str, open_browser: bool) -> Result<()> {
+/// Files whose size changed since the previous build, largest change first.
+fn print_size_changes(current: &BuildManifest, previous: &BuildManifest) {
+    /// Changes listed before the rest are summarized.
+    const SHOWN: usize = 10;
+
+    let deltas = current.compare(previous);
+    if deltas.is_empty() {
+        println!("\n📏 No size changes since the last build");
+        return;
+    }
+    println!("\n📏 Size changes since the last build:");
+    for delta in deltas.iter().take(SHOWN) {
+        let change = match (delta.before, delta.after) {
+            (None, Some(after)) => format!("new ({})", format_bytes(after)).green(),
+            (Some(_), None) => "removed".dimmed(),
+            _ if delta.change() > 0 => format_change(delta.change()).yellow(),
+            _ => format_change(delta.change()).green(),
+        };
+        println!("   {} {}", delta.path, change);
+    }
+    if deltas.len() > SHOWN {
+        println!("   … and {} more", deltas.len() - SHOWN);
+    }
+    let total = current.total.size as i64 - previous.total.size as i64;
+    let gzip = current.total.gzip as i64 - previous.total.gzip as i64;
+    println!("   Total: {} (gzip {})", format_change(total), format_change(gzip));
+}
+
+fn format_change(bytes: i64) -> String {
+    let sign = if bytes < 0 { "-" } else { "+" };
+    format!("{}{}", sign, format_bytes(bytes.unsigned_abs()))
+}
+
+/// Unset arguments fall back to the `serve` section of nexus.yaml. Serving
+/// several workspace members starts one server per member on consecutive
+/// ports, beginning at `--port` or the first member's `serve.port`; the host,
+/// browser and live reload settings are each member's own.
+pub async fn serve_project(
+    port: Option<u16>,
+    host: Option<&str>,
+    open_browser: bool,
+    live_reload: bool,
+    project_dir: &Path,
+    selection: &MemberSelection,
+) -> Result<()> {
+    let projects = select_projects(project_dir, &selection.members, selection.workspace, false)?;
+    let base_port = port.unwrap_or(projects[0].config.serve.port);
+    let multiple = projects.len() > 1;
+
     println!("🌐 {}", "Starting development server:".green().bold());
-    println!("   URL: http://{}:{}", host.cyan(), port.to_string().cyan());
 
-    let _project = Project::load(".")?;
+    let mut sites = Vec::new();
+    for (index, project) in projects.iter().enumerate() {
+        let port = base_port
+            .checked_add(index as u16)
+            .context("Ran out of ports for workspace members")?;
+        if multiple {
+            print_member_header(project, index, projects.len());
+        }
+        let member_host = host.unwrap_or(&project.config.serve.host);
+        println!("   URL: http://{}:{}", member_host.cyan(), port.to_string().cyan());
+        for (prefix, rule) in &project.config.serve.proxy {
+            println!("   Proxy: {} → {}", prefix.cyan(), rule.target);
+        }
 
-    let build_dir = Path::new("dist");
-    if !build_dir.exists() {
-        println!("⚠️  No build found. Running build first...");
-        build_project("development", "web", Some(build_dir)).await?;
+        let build_dir = project.root.join(&project.config.build.out_dir);
+        // A directory without a manifest, or with an empty one, holds no
+        // finished build: an interrupted build, or `out_dir` created by hand.
+        if BuildManifest::read(&build_dir).is_none_or(|manifest| manifest.files.is_empty()) {
+            println!("⚠️  No build found. Running build first...");
+            build_member(project, Some("development"), Some(BuildTarget::Web), Some(&build_dir), true).await?;
+        }
+        sites.push((project.root.clone(), port, build_dir));
     }
 
     #[cfg(feature = "web")]
     {
-        let server = warp::serve(
-            warp::fs::dir(build_dir)
-                .or(warp::path::end().map(|| warp::reply::html("Nexus Studio AI")))
-                .with(warp::cors().allow_any_origin()),
-        );
-
-        let (addr, server_future) = server.bind_ephemeral((host.parse()?, port));
-
-        println!("\n🚀 Server running at: http://{}", addr);
-        println!("📁 Serving from: {}", build_dir.display().to_string().cyan());
-        println!("🛑 Press Ctrl+C to stop\n");
-
-        if open_browser {
-            let url = format!("http://{}:{}", host, port);
-            if let Err(e) = open::that(&url) {
-                println!("⚠️  Could not open browser: {}", e);
+        let mut servers = Vec::new();
+        let mut reloading = Vec::new();
+        for ((root, port, build_dir), project) in sites.iter().zip(&projects) {
+            let serve = &project.config.serve;
+            let host = host.unwrap_or(&serve.host);
+            let ip: std::net::IpAddr = host
+                .parse()
+                .with_context(|| format!("Invalid host address '{}'", host))?;
+            let reload = (live_reload && serve.live_reload).then(LiveReload::default);
+            if let Some(reload) = &reload {
+                reloading.push((root.clone(), build_dir.clone(), reload.clone()));
             }
+            let site = dev_server::site(build_dir.clone(), reload, serve)?;
+            // Proxied prefixes take precedence over files in the output.
+            let site = match proxy::routes(&serve.proxy)? {
+                Some(proxy) => proxy.or(site).unify().boxed(),
+                None => site,
+            };
+            let log_requests = serve.log_requests;
+            let member = multiple.then(|| project.config.name.clone());
+            let server = warp::serve(
+                site.with(warp::cors().allow_any_origin())
+                    .with(warp::log::custom(move |info| {
+                        if log_requests {
+                            dev_server::log_request(&info, member.as_deref());
+                        }
+                    })),
+            );
+
+            let (addr, server_future) = server
+                .try_bind_ephemeral((ip, *port))
+                .with_context(|| format!("Failed to bind {}:{}", host, port))?;
+
+            println!("\n🚀 Server running at: http://{}", addr);
+            println!("📁 Serving from: {}", build_dir.display().to_string().cyan());
+
+            if open_browser || serve.open {
+                let url = format!("http://{}:{}", host, port);
+                if let Err(e) = open::that(&url) {
+                    println!("⚠️  Could not open browser: {}", e);
+                }
+            }
+            servers.push(server_future);
+        }
+        if !reloading.is_empty() {
+            println!("🔄 Live reload: rebuilding and refreshing pages on changes");
         }
+        println!("🛑 Press Ctrl+C to stop\n");
 
         tokio::spawn(async {
             tokio::signal::ctrl_c().await.unwrap();
@@ -228,37 +958,96 @@ pub async fn serve_project(port: u16, host: &str, open_browser: bool) -> Result<
             std::process::exit(0);
         });
 
-        server_future.await;
+        let reloader = reload_on_change(project_dir, selection, reloading);
+        futures::future::join(futures::future::join_all(servers), reloader).await;
         return Ok(());
     }
 
     #[cfg(not(feature = "web"))]
     {
-        let _ = (host, port, open_browser);
+        let _ = (host, sites, open_browser, live_reload);
         anyhow::bail!("Web feature disabled. Rebuild with --features web.");
     }
 }
 
-pub async fn deploy_project(target: &DeployTarget, env: &str, preview: bool) -> Result<()> {
-    println!("🚀 {}", "Deploying project:".green().bold());
-    println!("   Target: {:?}", target);
-    println!("   Environment: {}", env.cyan());
-    println!("   Preview: {}", preview.to_string().cyan());
+/// Rebuilds served projects in development mode when their files change and
+/// tells their pages to reload, swap stylesheets or show the build error.
+/// Runs until the server stops.
+#[cfg(feature = "web")]
+async fn reload_on_change(
+    project_dir: &Path,
+    selection: &MemberSelection,
+    sites: Vec<(PathBuf, PathBuf, LiveReload)>,
+) {
+    if sites.is_empty() {
+        return;
+    }
+    let roots = sites.iter().map(|(root, _, _)| root.clone()).collect();
+    let ignored = sites.iter().map(|(_, build_dir, _)| build_dir.clone()).collect();
+    let mut watcher = Watcher::new(roots, ignored);
+    loop {
+        let changed = watcher.changes().await;
+        // Reloaded so edits to nexus.yaml apply.
+        let projects = match select_projects(project_dir, &selection.members, selection.workspace, false) {
+            Ok(projects) => projects,
+            Err(e) => {
+                println!("❌ {:#}", e);
+                continue;
+            }
+        };
+        for (root, build_dir, live_reload) in &sites {
+            let Some(path) = changed.iter().find(|path| path.starts_with(root)) else {
+                continue;
+            };
+            let Some(project) = projects.iter().find(|project| &project.root == root) else {
+                continue;
+            };
+            println!("🔄 {}", watch::display_path(path, root).cyan());
+            match rebuild_member(project, Some("development"), Some(BuildTarget::Web), Some(build_dir), true).await {
+                Ok(result) => live_reload.built(result.previous_manifest.as_ref(), &result.manifest),
+                Err(e) => live_reload.failed(&format!("{:#}", e)),
+            }
+        }
+    }
+}
 
-    let pb = ProgressBar::new_spinner();
-    pb.set_style(
-        ProgressStyle::default_spinner()
-            .tick_strings(&["⣾", "⣽", "⣻", "⢿", "⡿", "⣟", "⣯", "⣷"])
-            .template("{spinner} {msg}")?,
-    );
+pub async fn deploy_project(
+    target: &DeployTarget,
+    env: &str,
+    preview: bool,
+    write_options: WriteOptions,
+    project_dir: &Path,
+    selection: &MemberSelection,
+) -> Result<()> {
+    let projects = select_projects(project_dir, &selection.members, selection.workspace, false)?;
 
-    match target {
-        DeployTarget::Vercel => deploy_vercel(env, preview, pb).await,
-        DeployTarget::Netlify => deploy_netlify(env, preview, pb).await,
-        DeployTarget::Docker => deploy_docker(env, preview, pb).await,
-        DeployTarget::Static => deploy_static(env, preview, pb).await,
-        DeployTarget::Github => deploy_github(env, preview, pb).await,
+    for (index, project) in projects.iter().enumerate() {
+        if projects.len() > 1 {
+            print_member_header(project, index, projects.len());
+        }
+
+        println!("🚀 {}", "Deploying project:".green().bold());
+        println!("   Target: {:?}", target);
+        println!("   Environment: {}", env.cyan());
+        println!("   Preview: {}", preview.to_string().cyan());
+
+        let pb = ProgressBar::new_spinner();
+        pb.set_style(
+            ProgressStyle::default_spinner()
+                .tick_strings(&["⣾", "⣽", "⣻", "⢿", "⡿", "⣟", "⣯", "⣷"])
+                .template("{spinner} {msg}")?,
+        );
+
+        match target {
+            DeployTarget::Vercel => deploy_vercel(project, env, preview, pb).await?,
+            DeployTarget::Netlify => deploy_netlify(project, env, preview, pb).await?,
+            DeployTarget::Docker => deploy_docker(project, env, preview, pb, write_options).await?,
+            DeployTarget::Static => deploy_static(project, env, preview, pb).await?,
+            DeployTarget::Github => deploy_github(project, env, preview, pb).await?,
+        }
     }
+
+    Ok(())
 }
 
 pub async fn ai_generate(
@@ -266,6 +1055,7 @@ pub async fn ai_generate(
     model: &str,
     output: Option<&Path>,
     framework: &str,
+    write_options: WriteOptions,
 ) -> Result<()> {
     println!("🤖 {}", "AI Code Generation:".green().bold());
     println!("   Model: {}", model.cyan());
@@ -288,8 +1078,15 @@ pub async fn ai_generate(
     pb.finish_with_message("✅ Code generated!");
 
     if let Some(output_path) = output {
-        std::fs::write(output_path, &code.content).context("Failed to write output file")?;
-        println!("\n📝 Code saved to: {}", output_path.display().to_string().cyan());
+        let writer = FileWriter::new(".", write_options);
+        writer
+            .write(output_path, &code.content)
+            .context("Failed to write output file")?;
+        writer.finish();
+        if !writer.is_preview() {
+            println!("\n📝 Code saved to: {}", output_path.display().to_string().cyan());
+            report_missing_packages(Path::new("."), &code.content)?;
+        }
     } else {
         println!("\n{}", "=".repeat(60).cyan());
         println!("{}", code.content);
@@ -409,11 +1206,123 @@ pub async fn handle_config(command: &ConfigCommands, config: &Config) -> Result<
     Ok(())
 }
 
-pub async fn list_templates() -> Result<()> {
+pub async fn handle_templates(command: Option<&TemplateCommands>) -> Result<()> {
+    match command {
+        None | Some(TemplateCommands::List) => list_templates(),
+        Some(TemplateCommands::Show { name }) => show_template(name),
+        Some(TemplateCommands::Add { source, name, force }) => {
+            let source = InstallSource::detect(source)?;
+            let registry = TemplateRegistry::open()?;
+            let manifest = registry.add(&source, name.as_deref(), *force)?;
+            println!("✅ Installed template {}", manifest.name.cyan());
+            println!("   Location: {}", registry.root().join(&manifest.name).display());
+            Ok(())
+        }
+        Some(TemplateCommands::Remove { name }) => {
+            TemplateRegistry::open()?.remove(name)?;
+            println!("🗑️ Removed template {}", name.cyan());
+            Ok(())
+        }
+    }
+}
+
+fn list_templates() -> Result<()> {
     println!("📦 Available templates:");
-    println!("  - default");
-    println!("  - fullstack");
-    println!("  - dashboard");
+    for template in ProjectTemplate::all()? {
+        let origin = if template.is_builtin() { "built-in" } else { "installed" };
+        println!(
+            "  - {} {} {}",
+            template.manifest.name.cyan(),
+            format!("({})", origin).dimmed(),
+            template.manifest.description
+        );
+    }
+    println!("\n💡 Use {} for details", "nexus templates show <name>".cyan());
+    Ok(())
+}
+
+fn show_template(name: &str) -> Result<()> {
+    let template = ProjectTemplate::find(name)?;
+    let manifest = &template.manifest;
+
+    println!("📦 {}", manifest.name.cyan().bold());
+    if !manifest.description.is_empty() {
+        println!("   {}", manifest.description);
+    }
+    let frameworks = if manifest.frameworks.is_empty() {
+        "any".to_string()
+    } else {
+        manifest.frameworks.join(", ")
+    };
+    println!("   Frameworks: {}", frameworks.cyan());
+
+    if !manifest.variables.is_empty() {
+        println!("\n🔧 Variables:");
+        for variable in &manifest.variables {
+            let requirement = match &variable.default {
+                Some(default) => format!("default: {}", default),
+                None => "required".yellow().to_string(),
+            };
+            println!("   - {} ({}) {}", variable.name.cyan(), requirement, variable.description);
+        }
+    }
+
+    println!("\n📄 Files:");
+    for file in template.file_names()? {
+        println!("   {}", file);
+    }
+    Ok(())
+}
+
+/// Migrates nexus.yaml to the current schema version, keeping a backup of
+/// the original next to it.
+pub async fn upgrade_project(project_dir: &Path, write_options: WriteOptions) -> Result<()> {
+    println!("⬆️  {}", "Upgrading nexus.yaml:".green().bold());
+
+    // Found without loading the project: loading would reject the old schema.
+    let root = project::find_root(project_dir)
+        .context("Not in a Nexus project directory. Run 'nexus new' first.")?;
+    let config_path = root.join(project::CONFIG_FILE);
+    let contents = std::fs::read_to_string(&config_path)
+        .with_context(|| format!("Failed to read {}", config_path.display()))?;
+
+    // Fails with the precise location if the file is invalid.
+    ProjectConfig::parse(&contents, &config_path)?;
+
+    let value: serde_yaml::Value = serde_yaml::from_str(&contents)?;
+    let version = schema::schema_version(&value).map_err(anyhow::Error::msg)?;
+    if version == CURRENT_SCHEMA_VERSION {
+        println!("✅ Already at schema version {}", CURRENT_SCHEMA_VERSION);
+        return Ok(());
+    }
+
+    let (migrated, steps) = schema::migrate(value, version).map_err(anyhow::Error::msg)?;
+    for step in &steps {
+        println!("   {}", step);
+    }
+
+    let writer = FileWriter::new(
+        &root,
+        WriteOptions {
+            conflict: ConflictPolicy::Overwrite,
+            ..write_options
+        },
+    );
+    let backup = format!("nexus.yaml.v{}.bak", version);
+    writer.write(&backup, &contents)?;
+    writer.write(project::CONFIG_FILE, &serde_yaml::to_string(&migrated)?)?;
+
+    if writer.is_preview() {
+        writer.finish();
+        return Ok(());
+    }
+
+    println!(
+        "\n✅ Upgraded to schema version {} (backup: {})",
+        CURRENT_SCHEMA_VERSION,
+        backup.cyan()
+    );
+    println!("💡 Comments are not preserved; compare with the backup if you had any.");
     Ok(())
 }
 
@@ -423,11 +1332,40 @@ pub async fn show_info() -> Result<()> {
     Ok(())
 }
 
-pub async fn clean_cache() -> Result<()> {
-    println!("🧹 Clearing cache and temporary files...");
+/// Deletes `.nexus/cache` in the project, or in each member from a workspace root.
+pub async fn clean_cache(project_dir: &Path, selection: &MemberSelection) -> Result<()> {
+    let projects = select_projects(project_dir, &selection.members, selection.workspace, false)?;
+    println!("🧹 {}", "Clearing build cache...".green().bold());
+
+    let mut total = 0;
+    for project in &projects {
+        let freed = cache::clear(&project.root)?;
+        if freed > 0 || projects.len() == 1 {
+            println!(
+                "   {} {}",
+                project.config.name.cyan(),
+                format_bytes(freed).dimmed()
+            );
+        }
+        total += freed;
+    }
+
+    if total == 0 {
+        println!("{}", "✨ Nothing to clean".yellow());
+    } else {
+        println!("✅ Freed {}", format_bytes(total).green());
+    }
     Ok(())
 }
 
+fn format_bytes(bytes: u64) -> String {
+    match bytes {
+        0..=1023 => format!("{} B", bytes),
+        1024..=1_048_575 => format!("{:.1} KB", bytes as f64 / 1024.0),
+        _ => format!("{:.2} MB", bytes as f64 / (1024.0 * 1024.0)),
+    }
+}
+
 fn print_tree(path: &Path, depth: usize) -> io::Result<()> {
     let prefix = "  ".repeat(depth);
 
@@ -453,7 +1391,7 @@ fn print_tree(path: &Path, depth: usize) -> io::Result<()> {
     Ok(())
 }
 
-async fn deploy_vercel(env: &str, preview: bool, pb: ProgressBar) -> Result<()> {
+async fn deploy_vercel(project: &Project, env: &str, preview: bool, pb: ProgressBar) -> Result<()> {
     pb.set_message("Checking Vercel CLI...");
 
     Command::new("vercel")
@@ -465,7 +1403,11 @@ async fn deploy_vercel(env: &str, preview: bool, pb: ProgressBar) -> Result<()>
 
     let args = if preview { vec!["--target=preview"] } else { vec!["--prod"] };
 
-    let status = Command::new("vercel").args(args).env("VERCEL_ENV", env).status()?;
+    let status = Command::new("vercel")
+        .args(args)
+        .env("VERCEL_ENV", env)
+        .current_dir(&project.root)
+        .status()?;
 
     if status.success() {
         pb.finish_with_message("✅ Deployed to Vercel!");
@@ -477,25 +1419,101 @@ async fn deploy_vercel(env: &str, preview: bool, pb: ProgressBar) -> Result<()>
     Ok(())
 }
 
-async fn deploy_netlify(_env: &str, _preview: bool, pb: ProgressBar) -> Result<()> {
+async fn deploy_netlify(_project: &Project, _env: &str, _preview: bool, pb: ProgressBar) -> Result<()> {
     pb.finish_with_message("✅ Netlify deployment placeholder complete!");
     Ok(())
 }
 
-async fn deploy_static(_env: &str, _preview: bool, pb: ProgressBar) -> Result<()> {
-    pb.finish_with_message("✅ Static deployment placeholder complete!");
+/// Builds the `ssg` target in the deploy environment's mode and publishes it
+/// to `deploy.dir`, or leaves it in the output directory for any static host.
+async fn deploy_static(project: &Project, env: &str, preview: bool, pb: ProgressBar) -> Result<()> {
+    pb.finish_and_clear();
+    let result = build_member(project, Some(env), Some(BuildTarget::Ssg), None, true).await?;
+    let output = &result.output_dir;
+
+    let Some(dir) = &project.config.deploy.dir else {
+        println!("\n📁 Static site ready in {}", output.display().to_string().cyan());
+        println!(
+            "💡 Upload it to any static host, or set {} in nexus.yaml to publish it there",
+            "deploy.dir".cyan()
+        );
+        return Ok(());
+    };
+    let dir = project.root.join(dir);
+    if preview {
+        println!(
+            "\n👀 Preview: {} files would be published to {}",
+            result.file_count,
+            dir.display().to_string().cyan()
+        );
+        return Ok(());
+    }
+
+    publish_static(output, &dir)?;
+    println!(
+        "\n✅ Published {} files to {}",
+        result.file_count,
+        dir.display().to_string().cyan()
+    );
     Ok(())
 }
 
-async fn deploy_github(_env: &str, _preview: bool, pb: ProgressBar) -> Result<()> {
+/// Replaces the contents of `dir` with the build in `output`. A non-empty
+/// `dir` must hold an earlier deploy, recognised by its build manifest, so
+/// nothing else is deleted.
+fn publish_static(output: &Path, dir: &Path) -> Result<()> {
+    if dir.is_dir() {
+        if dir.canonicalize()? == output.canonicalize()? {
+            return Ok(());
+        }
+        let mut entries = std::fs::read_dir(dir)?.peekable();
+        if entries.peek().is_some() && !dir.join(manifest::MANIFEST_FILE).is_file() {
+            anyhow::bail!(
+                "{} is not empty and holds no earlier deploy (no {}); empty it or change deploy.dir",
+                dir.display(),
+                manifest::MANIFEST_FILE
+            );
+        }
+        for entry in entries {
+            let path = entry?.path();
+            if path.is_dir() {
+                std::fs::remove_dir_all(&path)
+            } else {
+                std::fs::remove_file(&path)
+            }
+            .with_context(|| format!("Failed to remove {}", path.display()))?;
+        }
+    }
+
+    for entry in walkdir::WalkDir::new(output) {
+        let entry = entry?;
+        let target = dir.join(entry.path().strip_prefix(output)?);
+        if entry.file_type().is_dir() {
+            std::fs::create_dir_all(&target)
+                .with_context(|| format!("Failed to create {}", target.display()))?;
+        } else {
+            std::fs::copy(entry.path(), &target)
+                .with_context(|| format!("Failed to copy {}", entry.path().display()))?;
+        }
+    }
+    Ok(())
+}
+
+async fn deploy_github(_project: &Project, _env: &str, _preview: bool, pb: ProgressBar) -> Result<()> {
     pb.finish_with_message("✅ GitHub deployment placeholder complete!");
     Ok(())
 }
 
-async fn deploy_docker(env: &str, preview: bool, pb: ProgressBar) -> Result<()> {
+async fn deploy_docker(
+    project: &Project,
+    env: &str,
+    preview: bool,
+    pb: ProgressBar,
+    write_options: WriteOptions,
+) -> Result<()> {
     pb.set_message("Building Docker image...");
 
-    if !Path::new("Dockerfile").exists() {
+    if !project.root.join("Dockerfile").exists() {
         let dockerfile = r#"FROM node:18-alpine AS builder
 WORKDIR /app
 COPY package*.json ./
@@ -508,13 +1526,24 @@ COPY --from=builder /app/dist /usr/share/nginx/html
 EXPOSE 80
 CMD ["nginx", "-g", "daemon off;"]"#;
 
-        std::fs::write("Dockerfile", dockerfile)?;
+        let writer = FileWriter::new(&project.root, write_options);
+        writer.write("Dockerfile", dockerfile)?;
+        if writer.is_preview() {
+            pb.finish_and_clear();
+            writer.finish();
+            return Ok(());
+        }
+    } else if write_options.is_preview() {
+        pb.finish_with_message("✅ Dockerfile already exists; nothing to write");
+        return Ok(());
     }
 
-    let image_name = format!("nexus-app:{}", env);
+    // Docker image names must be lowercase.
+    let image_name = format!("{}:{}", project.config.name.to_lowercase(), env);
 
     let status = Command::new("docker")
         .args(["build", "-t", &image_name, "."])
+        .current_dir(&project.root)
         .status()?;
 
     if !status.success() {
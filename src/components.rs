@@ -0,0 +1,307 @@
+use crate::naming::ComponentName;
+use crate::templates::{BuiltinFile, RenderedFile};
+use crate::types::{ComponentOptions, ComponentType};
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+/// Component templates, keyed by `<family>/<template>`. React, React + TS and
+/// Next.js share the `react` family; `shared` templates are used by every
+/// family that has no template of its own.
+const COMPONENT_TEMPLATES: &[BuiltinFile] = &[
+    ("react/component", include_str!("../templates/components/react/component.tera")),
+    ("react/test", include_str!("../templates/components/react/test.tera")),
+    ("react/story", include_str!("../templates/components/react/story.tera")),
+    ("vue/component", include_str!("../templates/components/vue/component.tera")),
+    ("vue/test", include_str!("../templates/components/vue/test.tera")),
+    ("vue/story", include_str!("../templates/components/vue/story.tera")),
+    ("svelte/component", include_str!("../templates/components/svelte/component.tera")),
+    ("svelte/test", include_str!("../templates/components/svelte/test.tera")),
+    ("svelte/story", include_str!("../templates/components/svelte/story.tera")),
+    ("angular/component", include_str!("../templates/components/angular/component.tera")),
+    ("angular/test", include_str!("../templates/components/angular/test.tera")),
+    ("angular/story", include_str!("../templates/components/angular/story.tera")),
+    ("angular/api", include_str!("../templates/components/angular/api.tera")),
+    ("html/component", include_str!("../templates/components/html/component.tera")),
+    ("html/test", include_str!("../templates/components/html/test.tera")),
+    ("html/story", include_str!("../templates/components/html/story.tera")),
+    ("shared/api", include_str!("../templates/components/shared/api.tera")),
+    ("shared/schema", include_str!("../templates/components/shared/schema.tera")),
+    ("shared/util", include_str!("../templates/components/shared/util.tera")),
+    ("shared/util.test", include_str!("../templates/components/shared/util.test.tera")),
+    ("shared/module.css", include_str!("../templates/components/shared/module.css.tera")),
+];
+
+/// The files for one component, before they are written.
+#[derive(Debug)]
+pub struct ComponentFiles {
+    pub files: Vec<RenderedFile>,
+    /// Barrel file and the export line it needs, when `--index` was given.
+    pub barrel: Option<(String, String)>,
+    /// Requested companion files that do not apply to this component type.
+    pub skipped: Vec<&'static str>,
+}
+
+#[derive(Serialize)]
+struct ComponentContext<'a> {
+    /// PascalCase name, used for components and classes.
+    name: String,
+    camel_name: String,
+    kebab_name: String,
+    title: String,
+    kind: &'static str,
+    framework: &'a str,
+    typescript: bool,
+    css_module: bool,
+    /// Main file name without its extension, for imports from companions.
+    file_stem: String,
+    story_group: &'static str,
+    /// Layouts render the router's outlet when registered as a parent route.
+    routed: bool,
+    /// URL of an API component's endpoint, e.g. `/api/users`.
+    base_path: String,
+    /// Import specifier of an API component's schema file.
+    schema_module: String,
+}
+
+fn family(framework: &str) -> &'static str {
+    match framework {
+        "vue" => "vue",
+        "svelte" => "svelte",
+        "angular" => "angular",
+        "html" => "html",
+        _ => "react",
+    }
+}
+
+fn is_typescript(framework: &str) -> bool {
+    matches!(framework, "react-ts" | "nextjs" | "vue" | "angular")
+}
+
+fn is_view(kind: &ComponentType) -> bool {
+    matches!(kind, ComponentType::Page | ComponentType::Layout | ComponentType::Ui)
+}
+
+/// Where each component type lives, following the framework's conventions.
+fn directory(framework: &str, kind: &ComponentType) -> &'static str {
+    use ComponentType::*;
+    match (framework, kind) {
+        ("nextjs", Page) => "components/pages",
+        ("nextjs", Layout) => "components/layouts",
+        ("nextjs", Ui) => "components",
+        ("nextjs", Api) => "lib/api",
+        ("nextjs", Util) => "lib",
+        ("vue", Page) => "src/views",
+        ("svelte", Page) => "src/lib/pages",
+        ("svelte", Layout) => "src/lib/layouts",
+        ("svelte", Ui) => "src/lib/components",
+        ("svelte", Api) => "src/lib/api",
+        ("svelte", Util) => "src/lib/utils",
+        ("angular", Page) => "src/app/pages",
+        ("angular", Layout) => "src/app/layouts",
+        ("angular", Ui) => "src/app/components",
+        ("angular", Api) => "src/app/services",
+        ("angular", Util) => "src/app/utils",
+        (_, Page) => "src/pages",
+        (_, Layout) => "src/layouts",
+        (_, Ui) => "src/components",
+        (_, Api) => "src/api",
+        (_, Util) => "src/utils",
+    }
+}
+
+/// Extension for script files: JSX for React views, otherwise JS or TS.
+fn script_extension(framework: &str, kind: &ComponentType) -> &'static str {
+    let typescript = is_typescript(framework);
+    match (family(framework), is_view(kind), typescript) {
+        ("react", true, true) => "tsx",
+        ("react", true, false) => "jsx",
+        (_, _, true) => "ts",
+        (_, _, false) => "js",
+    }
+}
+
+/// Extension of a component's main file: single-file components for Vue and
+/// Svelte views, `component.ts` for Angular ones, otherwise the script
+/// extension.
+pub fn main_extension(framework: &str, kind: &ComponentType) -> &'static str {
+    match (family(framework), is_view(kind)) {
+        ("vue", true) => "vue",
+        ("svelte", true) => "svelte",
+        ("angular", true) => "component.ts",
+        _ => script_extension(framework, kind),
+    }
+}
+
+/// The endpoint an API component talks to: `/api/admin/users` for `admin/users`.
+pub fn api_base_path(name: &ComponentName) -> String {
+    format!("/api{}", name.route_path())
+}
+
+fn template(family: &str, name: &str) -> Result<&'static str> {
+    let lookup = |key: String| {
+        COMPONENT_TEMPLATES
+            .iter()
+            .find(|(path, _)| *path == key)
+            .map(|(_, contents)| *contents)
+    };
+    lookup(format!("{}/{}", family, name))
+        .or_else(|| lookup(format!("shared/{}", name)))
+        .with_context(|| format!("No {} component template for {}", name, family))
+}
+
+fn render(template: &str, context: &ComponentContext) -> Result<String> {
+    let rendered = tera::Tera::one_off(template, &tera::Context::from_serialize(context)?, false)?;
+    Ok(format!("{}\n", rendered.trim_end()))
+}
+
+/// Renders a component of `kind` for `framework` plus the companion files
+/// selected in `options`. Paths are relative to the project root. `routed`
+/// layouts render the router outlet for their child routes.
+pub fn render_component(
+    kind: &ComponentType,
+    name: &ComponentName,
+    framework: &str,
+    options: &ComponentOptions,
+    routed: bool,
+) -> Result<ComponentFiles> {
+    let family = family(framework);
+    let typescript = is_typescript(framework);
+    let extension = script_extension(framework, kind);
+    let dir = std::iter::once(directory(framework, kind))
+        .chain(name.dirs.iter().map(String::as_str))
+        .collect::<Vec<_>>()
+        .join("/");
+
+    // Components are PascalCase and functions camelCase; Angular and plain
+    // HTML projects use kebab-case file names.
+    let identifier = match kind {
+        ComponentType::Util => name.camel(),
+        _ => name.pascal(),
+    };
+    if !matches!(kind, ComponentType::Api) {
+        name.check_identifier(&identifier)?;
+    }
+    let base = match (family, kind) {
+        ("angular" | "html", _) => name.kebab(),
+        (_, ComponentType::Util | ComponentType::Api) => name.camel(),
+        _ => name.pascal(),
+    };
+
+    // (main file relative to `dir`, its stem relative to its own folder)
+    let (main, stem) = match (family, kind) {
+        ("angular", ComponentType::Api) => (format!("{}.service.ts", base), format!("{}.service", base)),
+        ("angular", ComponentType::Util) => (format!("{}.ts", base), base.clone()),
+        ("angular", _) => (format!("{0}/{0}.component.ts", base), format!("{}.component", base)),
+        _ => (format!("{}.{}", base, main_extension(framework, kind)), base.clone()),
+    };
+    let main_path = format!("{}/{}", dir, main);
+    let folder = main_path
+        .rsplit_once('/')
+        .map(|(folder, _)| folder)
+        .unwrap_or(&dir);
+
+    let context = ComponentContext {
+        name: name.pascal(),
+        camel_name: name.camel(),
+        kebab_name: name.kebab(),
+        title: name.title(),
+        kind: kind.template_name(),
+        framework,
+        typescript,
+        css_module: options.css_module && is_view(kind) && family != "html",
+        file_stem: stem.clone(),
+        story_group: match kind {
+            ComponentType::Page => "Pages",
+            ComponentType::Layout => "Layouts",
+            _ => "Components",
+        },
+        routed: routed && matches!(kind, ComponentType::Layout),
+        base_path: api_base_path(name),
+        schema_module: format!(
+            "./{}.schema{}",
+            base,
+            if family == "html" { ".js" } else { "" }
+        ),
+    };
+
+    let main_template = match kind {
+        ComponentType::Api => "api",
+        ComponentType::Util => "util",
+        _ => "component",
+    };
+    let mut files = vec![RenderedFile {
+        path: main_path.clone(),
+        contents: render(template(family, main_template)?, &context)?,
+    }];
+    let mut skipped = Vec::new();
+    let mut companion = |path: String, template_name: &str| -> Result<()> {
+        files.push(RenderedFile {
+            path: format!("{}/{}", folder, path),
+            contents: render(template(family, template_name)?, &context)?,
+        });
+        Ok(())
+    };
+
+    // The client's request and response types; the server handler uses them too.
+    if matches!(kind, ComponentType::Api) {
+        let schema_extension = if typescript { "ts" } else { "js" };
+        companion(format!("{}.schema.{}", base, schema_extension), "schema")?;
+    }
+
+    if options.test {
+        let suffix = if family == "angular" { "spec" } else { "test" };
+        match kind {
+            ComponentType::Api => skipped.push("--test"),
+            ComponentType::Util => companion(format!("{}.{}.{}", stem, suffix, extension), "util.test")?,
+            _ => companion(format!("{}.{}.{}", stem, suffix, extension), "test")?,
+        }
+    }
+    if options.story {
+        if is_view(kind) {
+            companion(format!("{}.stories.{}", stem, extension), "story")?;
+        } else {
+            skipped.push("--story");
+        }
+    }
+    if options.css_module {
+        if context.css_module {
+            let css = if family == "angular" {
+                format!("{}.css", stem)
+            } else {
+                format!("{}.module.css", stem)
+            };
+            companion(css, "module.css")?;
+        } else {
+            skipped.push("--css-module");
+        }
+    }
+
+    let barrel = options.index.then(|| {
+        // Angular components sit in their own folder; the barrel is one level up.
+        let barrel_dir = if family == "angular" && is_view(kind) {
+            folder.rsplit_once('/').map(|(parent, _)| parent).unwrap_or(&dir)
+        } else {
+            folder
+        };
+        let relative = main_path[barrel_dir.len() + 1..].to_string();
+        let line = match (family, is_view(kind)) {
+            ("react", true) => {
+                let module = relative.trim_end_matches(&format!(".{}", extension));
+                format!("export {{ default as {} }} from \"./{}\"", identifier, module)
+            }
+            ("vue" | "svelte", true) => {
+                format!("export {{ default as {} }} from \"./{}\"", identifier, relative)
+            }
+            // Browsers need the extension; bundlers and TypeScript do not.
+            ("html", _) => format!("export * from \"./{}\"", relative),
+            _ => format!(
+                "export * from \"./{}\"",
+                relative.trim_end_matches(&format!(".{}", extension))
+            ),
+        };
+        let index = format!("{}/index.{}", barrel_dir, if typescript { "ts" } else { "js" });
+        (index, line)
+    });
+
+    Ok(ComponentFiles { files, barrel, skipped })
+}
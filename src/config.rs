@@ -1,6 +1,6 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
@@ -35,3 +35,30 @@ impl Config {
         Ok(())
     }
 }
+
+/// Per-user data directory holding installed templates and other state.
+/// `NEXUS_HOME` overrides the platform default.
+pub fn data_dir() -> Result<PathBuf> {
+    if let Some(home) = std::env::var_os("NEXUS_HOME") {
+        return Ok(PathBuf::from(home));
+    }
+
+    if cfg!(windows) {
+        if let Some(appdata) = std::env::var_os("APPDATA") {
+            return Ok(PathBuf::from(appdata).join("nexus"));
+        }
+    }
+
+    let home = std::env::var_os("HOME")
+        .map(PathBuf::from)
+        .context("Could not determine home directory; set NEXUS_HOME")?;
+
+    if cfg!(target_os = "macos") {
+        return Ok(home.join("Library/Application Support/nexus"));
+    }
+
+    Ok(std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| home.join(".local/share"))
+        .join("nexus"))
+}
@@ -0,0 +1,422 @@
+use crate::manifest::{is_fingerprinted, BuildManifest};
+use crate::schema::ServeConfig;
+use anyhow::{Context, Result};
+use colored::*;
+use flate2::write::GzEncoder;
+use futures::{SinkExt, StreamExt};
+use std::collections::{BTreeMap, HashSet};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tokio::sync::broadcast;
+use warp::filters::BoxedFilter;
+use warp::http::header::{ACCEPT_RANGES, CACHE_CONTROL, CONTENT_ENCODING, CONTENT_LENGTH, CONTENT_TYPE, VARY};
+use warp::http::{HeaderMap, HeaderName, HeaderValue, StatusCode};
+use warp::path::FullPath;
+use warp::reply::Response;
+use warp::ws::{WebSocket, Ws};
+use warp::{Filter, Rejection, Reply};
+
+/// The browser side, served under `/__nexus/` so it cannot clash with the
+/// site's own files.
+const CLIENT_SCRIPT: &str = include_str!("../templates/serve/livereload.js");
+const CLIENT_TAG: &str = "<script src=\"/__nexus/livereload.js\"></script>";
+
+/// For files whose name changes with their content.
+const CACHE_FOREVER: &str = "public, max-age=31536000, immutable";
+
+/// Smaller responses are not worth compressing.
+const MIN_COMPRESSED_SIZE: usize = 1024;
+
+/// Tells the browsers showing a site to reload, swap stylesheets or show a
+/// build error.
+#[derive(Clone)]
+pub struct LiveReload {
+    sender: broadcast::Sender<String>,
+    /// The last build error, shown to pages that connect while it stands.
+    error: Arc<Mutex<Option<String>>>,
+}
+
+impl Default for LiveReload {
+    fn default() -> Self {
+        let (sender, _) = broadcast::channel(16);
+        Self {
+            sender,
+            error: Arc::new(Mutex::new(None)),
+        }
+    }
+}
+
+impl LiveReload {
+    /// After a successful rebuild: swaps the stylesheets when only CSS
+    /// changed, otherwise reloads. Nothing is sent when the output is the
+    /// same, unless an error overlay has to be cleared.
+    pub fn built(&self, previous: Option<&BuildManifest>, current: &BuildManifest) {
+        let had_error = self.error.lock().map(|mut error| error.take().is_some()).unwrap_or(false);
+        let changed = previous.map(|previous| changed_files(previous, current));
+        let message = match changed {
+            Some(changed) if changed.is_empty() && !had_error => return,
+            // A stylesheet that was added or removed needs new <link> tags,
+            // so only changed ones are swapped.
+            Some(changed)
+                if !had_error
+                    && !changed.is_empty()
+                    && changed.iter().all(|path| path.ends_with(".css"))
+                    && previous.is_some_and(|previous| previous.files.len() == current.files.len()) =>
+            {
+                let paths: Vec<String> = changed.iter().map(|path| format!("/{}", path)).collect();
+                serde_json::json!({ "type": "css", "paths": paths })
+            }
+            _ => serde_json::json!({ "type": "reload" }),
+        };
+        let _ = self.sender.send(message.to_string());
+    }
+
+    /// After a failed rebuild: shows `message` over the page.
+    pub fn failed(&self, message: &str) {
+        if let Ok(mut error) = self.error.lock() {
+            *error = Some(message.to_string());
+        }
+        let _ = self
+            .sender
+            .send(serde_json::json!({ "type": "error", "message": message }).to_string());
+    }
+
+    async fn connect(self, socket: WebSocket) {
+        let (mut tx, mut rx) = socket.split();
+        let mut messages = self.sender.subscribe();
+        let error = self.error.lock().ok().and_then(|error| error.clone());
+        if let Some(message) = error {
+            let error = serde_json::json!({ "type": "error", "message": message });
+            let _ = tx.send(warp::ws::Message::text(error.to_string())).await;
+        }
+        loop {
+            tokio::select! {
+                message = messages.recv() => match message {
+                    Ok(message) => {
+                        if tx.send(warp::ws::Message::text(message)).await.is_err() {
+                            break;
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => break,
+                },
+                incoming = rx.next() => {
+                    if !matches!(incoming, Some(Ok(_))) {
+                        break;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Serves `build_dir` with the `serve` settings of nexus.yaml. With
+/// `live_reload`, HTML pages get the client script, which connects to
+/// `/__nexus/livereload`.
+pub fn site(
+    build_dir: PathBuf,
+    live_reload: Option<LiveReload>,
+    config: &ServeConfig,
+) -> Result<BoxedFilter<(Response,)>> {
+    let rules = Arc::new(header_rules(&config.headers)?);
+    let inject = live_reload.is_some();
+    let spa_fallback = config.spa_fallback;
+
+    let redirect_dir = build_dir.clone();
+    let redirects = warp::get()
+        .and(warp::path::full())
+        .and(warp::query::raw().or(warp::any().map(String::new)).unify())
+        .and_then(move |path: FullPath, query: String| {
+            let location = directory_location(&redirect_dir, path.as_str(), &query);
+            async move {
+                match location {
+                    Some(location) => Ok(warp::reply::with_header(StatusCode::MOVED_PERMANENTLY, "location", location)
+                        .into_response()),
+                    None => Err(warp::reject::not_found()),
+                }
+            }
+        });
+    let pages_dir = build_dir.clone();
+    let pages = warp::get().and(warp::path::full()).and_then(move |path: FullPath| {
+        let page = page_file(&pages_dir, path.as_str());
+        async move {
+            match page {
+                Some(page) => html_reply(&page, StatusCode::OK, inject).await,
+                None => Err(warp::reject::not_found()),
+            }
+        }
+    });
+    let files = warp::fs::dir(build_dir.clone()).map(Reply::into_response);
+    let fallback = warp::get()
+        .and(warp::header::optional::<String>("accept"))
+        .and_then(move |accept: Option<String>| {
+            let page = fallback_page(&build_dir, accept.as_deref(), spa_fallback);
+            async move {
+                match page {
+                    Some((page, status)) => html_reply(&page, status, inject).await,
+                    None => Err(warp::reject::not_found()),
+                }
+            }
+        });
+    let mut routes = redirects.or(pages).unify().or(files).unify().or(fallback).unify().boxed();
+
+    if let Some(live_reload) = live_reload {
+        let client = warp::path!("__nexus" / "livereload.js").map(|| {
+            warp::reply::with_header(CLIENT_SCRIPT, "content-type", "text/javascript; charset=utf-8").into_response()
+        });
+        let socket = warp::path!("__nexus" / "livereload").and(warp::ws()).map(move |ws: Ws| {
+            let live_reload = live_reload.clone();
+            ws.on_upgrade(move |socket| live_reload.connect(socket)).into_response()
+        });
+        routes = client.or(socket).unify().or(routes).unify().boxed();
+    }
+
+    Ok(warp::path::full()
+        .and(warp::header::optional::<String>("accept-encoding"))
+        .and(routes)
+        .then(move |path: FullPath, accept_encoding: Option<String>, response: Response| {
+            let rules = rules.clone();
+            async move {
+                let response = with_headers(response, path.as_str(), &rules);
+                compress(response, accept_encoding.as_deref()).await
+            }
+        })
+        .boxed())
+}
+
+/// Prints a request once it is answered. Live reload's own requests are left
+/// out.
+pub fn log_request(info: &warp::log::Info<'_>, member: Option<&str>) {
+    if info.path().starts_with("/__nexus/") {
+        return;
+    }
+    let status = info.status();
+    let code = status.as_u16().to_string();
+    let code = if status.is_server_error() {
+        code.red()
+    } else if status.is_client_error() {
+        code.yellow()
+    } else if status.is_redirection() {
+        code.cyan()
+    } else {
+        code.green()
+    };
+    let member = member.map(|member| format!("{} ", member.dimmed())).unwrap_or_default();
+    println!(
+        "   {}{} {} {} {}",
+        member,
+        info.method(),
+        info.path(),
+        code,
+        format!("{:.1}ms", info.elapsed().as_secs_f64() * 1000.0).dimmed()
+    );
+}
+
+/// Where `/docs` is redirected when `docs` is a directory: `/docs/`, with
+/// the query kept, so the page's relative links resolve inside it.
+fn directory_location(build_dir: &Path, url_path: &str, query: &str) -> Option<String> {
+    let relative = url_path.trim_start_matches('/');
+    if relative.is_empty() || relative.ends_with('/') || relative.split('/').any(|segment| segment == "..") {
+        return None;
+    }
+    if !build_dir.join(relative).is_dir() {
+        return None;
+    }
+    Some(if query.is_empty() { format!("{}/", url_path) } else { format!("{}/?{}", url_path, query) })
+}
+
+/// The HTML file `url_path` is served from, if it names one: `/` and
+/// `/docs/` are their `index.html`, `/about.html` is itself.
+fn page_file(build_dir: &Path, url_path: &str) -> Option<PathBuf> {
+    let relative = url_path.trim_start_matches('/');
+    if relative.split('/').any(|segment| segment == "..") {
+        return None;
+    }
+    let mut file = build_dir.join(relative);
+    if relative.is_empty() || relative.ends_with('/') {
+        file = file.join("index.html");
+    }
+    (file.extension().is_some_and(|extension| extension == "html") && file.is_file()).then_some(file)
+}
+
+/// For a request no file answers: `index.html` for page requests when
+/// `spa_fallback` is on, so the app's router takes over, otherwise the
+/// site's `404.html` if it has one.
+fn fallback_page(build_dir: &Path, accept: Option<&str>, spa_fallback: bool) -> Option<(PathBuf, StatusCode)> {
+    // Browsers name text/html when navigating, so `/users/jane.doe` loads the
+    // app. Scripts, images and fetch() calls only send `*/*` and get a 404.
+    let wants_page = accept.is_some_and(|accept| accept.contains("text/html"));
+    let index = build_dir.join("index.html");
+    if spa_fallback && wants_page && index.is_file() {
+        return Some((index, StatusCode::OK));
+    }
+    let not_found = build_dir.join("404.html");
+    not_found.is_file().then_some((not_found, StatusCode::NOT_FOUND))
+}
+
+/// An HTML page, never cached so edits show on reload, with the live reload
+/// client when `inject` is set.
+async fn html_reply(page: &Path, status: StatusCode, inject: bool) -> Result<Response, Rejection> {
+    let html = tokio::fs::read_to_string(page).await.map_err(|_| warp::reject::not_found())?;
+    let html = if inject { inject_client(&html) } else { html };
+    let reply = warp::reply::with_header(warp::reply::html(html), "cache-control", "no-store");
+    Ok(warp::reply::with_status(reply, status).into_response())
+}
+
+struct HeaderRule {
+    pattern: glob::Pattern,
+    headers: HeaderMap,
+}
+
+/// The `serve.headers` rules, least specific first so longer patterns win
+/// when rules set the same header.
+fn header_rules(config: &BTreeMap<String, BTreeMap<String, String>>) -> Result<Vec<HeaderRule>> {
+    let mut rules = Vec::new();
+    for (pattern, headers) in config {
+        let compiled = glob::Pattern::new(pattern)
+            .with_context(|| format!("Invalid header pattern '{}' in nexus.yaml", pattern))?;
+        let mut map = HeaderMap::new();
+        for (name, value) in headers {
+            let name = HeaderName::from_bytes(name.as_bytes())
+                .with_context(|| format!("Invalid header name '{}' for {}", name, pattern))?;
+            let value = HeaderValue::from_str(value)
+                .with_context(|| format!("Invalid value for header {} of {}", name, pattern))?;
+            map.insert(name, value);
+        }
+        rules.push(HeaderRule {
+            pattern: compiled,
+            headers: map,
+        });
+    }
+    rules.sort_by_key(|rule| rule.pattern.as_str().len());
+    Ok(rules)
+}
+
+/// Adds a `cache-control` header to responses without one, so fingerprinted
+/// assets are cached for good and everything else is revalidated, and then
+/// the headers of matching rules.
+fn with_headers(mut response: Response, url_path: &str, rules: &[HeaderRule]) -> Response {
+    if response.status() == StatusCode::SWITCHING_PROTOCOLS {
+        return response;
+    }
+    let headers = response.headers_mut();
+    if !headers.contains_key(CACHE_CONTROL) {
+        let cache = if is_fingerprinted(url_path) { CACHE_FOREVER } else { "no-cache" };
+        headers.insert(CACHE_CONTROL, HeaderValue::from_static(cache));
+    }
+    for rule in rules.iter().filter(|rule| rule.pattern.matches(url_path)) {
+        for (name, value) in &rule.headers {
+            headers.insert(name, value.clone());
+        }
+    }
+    response
+}
+
+#[derive(Clone, Copy)]
+enum Encoding {
+    Brotli,
+    Gzip,
+}
+
+/// The encoding to use from an `Accept-Encoding` header, brotli first.
+fn preferred_encoding(accept_encoding: &str) -> Option<Encoding> {
+    let accepted: Vec<&str> = accept_encoding
+        .split(',')
+        .filter_map(|entry| {
+            let mut parts = entry.split(';').map(str::trim);
+            let name = parts.next()?;
+            // `br;q=0` refuses brotli.
+            let refused = parts
+                .filter_map(|param| param.strip_prefix("q="))
+                .any(|quality| quality.parse::<f32>().is_ok_and(|quality| quality == 0.0));
+            (!refused).then_some(name)
+        })
+        .collect();
+    if accepted.contains(&"br") {
+        Some(Encoding::Brotli)
+    } else if accepted.contains(&"gzip") {
+        Some(Encoding::Gzip)
+    } else {
+        None
+    }
+}
+
+/// Compresses text responses the browser accepts compressed. Partial,
+/// encoded and small responses are sent as they are.
+async fn compress(response: Response, accept_encoding: Option<&str>) -> Response {
+    let Some(encoding) = accept_encoding.and_then(preferred_encoding) else {
+        return response;
+    };
+    let compressible = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|content_type| {
+            content_type.starts_with("text/")
+                || ["javascript", "json", "xml", "svg", "wasm"]
+                    .iter()
+                    .any(|kind| content_type.contains(kind))
+        });
+    if response.status() != StatusCode::OK || response.headers().contains_key(CONTENT_ENCODING) || !compressible {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let Ok(bytes) = warp::hyper::body::to_bytes(body).await else {
+        let mut response = Response::new("Failed to read the file".into());
+        *response.status_mut() = StatusCode::INTERNAL_SERVER_ERROR;
+        return response;
+    };
+    if bytes.len() < MIN_COMPRESSED_SIZE {
+        return Response::from_parts(parts, bytes.into());
+    }
+    let (name, compressed) = match encoding {
+        Encoding::Brotli => {
+            let mut compressed = Vec::new();
+            {
+                // A low quality, as the output changes with every rebuild.
+                let mut writer = brotli::CompressorWriter::new(&mut compressed, 4096, 5, 22);
+                let _ = writer.write_all(&bytes);
+            }
+            ("br", compressed)
+        }
+        Encoding::Gzip => {
+            let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::fast());
+            let _ = encoder.write_all(&bytes);
+            ("gzip", encoder.finish().unwrap_or_default())
+        }
+    };
+    parts.headers.insert(CONTENT_ENCODING, HeaderValue::from_static(name));
+    parts.headers.insert(CONTENT_LENGTH, HeaderValue::from(compressed.len()));
+    parts.headers.append(VARY, HeaderValue::from_static("accept-encoding"));
+    // Byte ranges would refer to the uncompressed file.
+    parts.headers.remove(ACCEPT_RANGES);
+    Response::from_parts(parts, compressed.into())
+}
+
+/// Paths added, removed or changed between two builds.
+fn changed_files<'a>(previous: &'a BuildManifest, current: &'a BuildManifest) -> Vec<&'a str> {
+    let before: HashSet<(&str, &str)> = previous
+        .files
+        .iter()
+        .map(|file| (file.path.as_str(), file.hash.as_str()))
+        .collect();
+    let after: HashSet<(&str, &str)> = current
+        .files
+        .iter()
+        .map(|file| (file.path.as_str(), file.hash.as_str()))
+        .collect();
+    let mut paths: Vec<&str> = before.symmetric_difference(&after).map(|(path, _)| *path).collect();
+    paths.sort();
+    paths.dedup();
+    paths
+}
+
+/// Adds the client script before `</body>`, or at the end without one.
+fn inject_client(html: &str) -> String {
+    let mut html = html.to_string();
+    let index = html.rfind("</body>").unwrap_or(html.len());
+    html.insert_str(index, CLIENT_TAG);
+    html
+}
@@ -0,0 +1,266 @@
+use crate::schema::EnvConfig;
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Variables with this prefix are public unless `env.public_prefixes` is set.
+pub const DEFAULT_PUBLIC_PREFIX: &str = "NEXUS_PUBLIC_";
+
+/// The environment of a build in one mode.
+#[derive(Debug, Clone, Default)]
+pub struct ProjectEnv {
+    /// `.env` files that exist, lowest precedence first.
+    pub files: Vec<String>,
+    /// Variables from the files. Variables already set in the shell win.
+    pub vars: BTreeMap<String, String>,
+    /// Variables with a public prefix, from the files or the shell. Only
+    /// these reach the bundler and the built output.
+    pub public: BTreeMap<String, String>,
+    /// The prefixes that make a variable public.
+    pub prefixes: Vec<String>,
+}
+
+impl ProjectEnv {
+    /// Loads `.env`, `.env.local`, `.env.<mode>` and `.env.<mode>.local`, each
+    /// overriding the ones before, and checks the variables `config` requires.
+    pub fn load(root: &Path, mode: &str, config: &EnvConfig) -> Result<Self> {
+        let mut env = ProjectEnv::default();
+        let names = [
+            ".env".to_string(),
+            ".env.local".to_string(),
+            format!(".env.{}", mode),
+            format!(".env.{}.local", mode),
+        ];
+        for name in names {
+            let path = root.join(&name);
+            if !path.is_file() {
+                continue;
+            }
+            let contents = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            for (key, value) in parse(&contents, &env.vars).with_context(|| format!("Invalid {}", name))? {
+                env.vars.insert(key, value);
+            }
+            env.files.push(name);
+        }
+        for (key, value) in env.vars.iter_mut() {
+            if let Ok(shell) = std::env::var(key) {
+                *value = shell;
+            }
+        }
+
+        env.prefixes = if config.public_prefixes.is_empty() {
+            vec![DEFAULT_PUBLIC_PREFIX.to_string()]
+        } else {
+            config.public_prefixes.clone()
+        };
+        let prefixes = env.prefixes.clone();
+        let is_public = |key: &str| prefixes.iter().any(|prefix| key.starts_with(prefix.as_str()));
+        env.public = std::env::vars()
+            .chain(env.vars.clone())
+            .filter(|(key, _)| is_public(key))
+            .collect();
+
+        let missing: Vec<&str> = config
+            .required
+            .iter()
+            .filter(|key| env.get(key).unwrap_or_default().is_empty())
+            .map(String::as_str)
+            .collect();
+        if !missing.is_empty() {
+            let searched = if env.files.is_empty() {
+                format!("no .env files for mode '{}' were found", mode)
+            } else {
+                format!("searched the shell and {}", env.files.join(", "))
+            };
+            anyhow::bail!(
+                "Missing required environment variable{} {} ({}). Set {} in a .env file or the shell",
+                if missing.len() == 1 { "" } else { "s" },
+                missing.join(", "),
+                searched,
+                if missing.len() == 1 { "it" } else { "them" }
+            );
+        }
+        Ok(env)
+    }
+
+    /// A variable from the shell or the `.env` files.
+    pub fn get(&self, key: &str) -> Option<String> {
+        std::env::var(key).ok().or_else(|| self.vars.get(key).cloned())
+    }
+
+    /// `process.env.NAME` and `import.meta.env.NAME` in the script `text`
+    /// replaced by the JSON string of public variable `NAME`. The replacement
+    /// is textual, so it also applies inside string literals and comments.
+    pub fn inline(&self, text: &str) -> String {
+        if self.public.is_empty() {
+            return text.to_string();
+        }
+        let mut output = String::with_capacity(text.len());
+        let mut rest = text;
+        while let Some((start, access)) = ["process.env.", "import.meta.env."]
+            .iter()
+            .filter_map(|access| rest.find(access).map(|start| (start, *access)))
+            .min()
+        {
+            let name_start = start + access.len();
+            let name_end = rest[name_start..]
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '$'))
+                .map_or(rest.len(), |end| name_start + end);
+            let preceded = rest[..start]
+                .chars()
+                .next_back()
+                .is_some_and(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '$' | '.'));
+            match self.public.get(&rest[name_start..name_end]) {
+                Some(value) if !preceded => {
+                    output.push_str(&rest[..start]);
+                    output.push_str(&serde_json::to_string(value).unwrap_or_default());
+                }
+                _ => output.push_str(&rest[..name_end]),
+            }
+            rest = &rest[name_end..];
+        }
+        output.push_str(rest);
+        output
+    }
+
+    /// `inline` applied to the inline `<script>` elements of an HTML page, so
+    /// prose and `<code>` examples that mention `process.env` are kept.
+    pub fn inline_scripts(&self, html: &str) -> String {
+        if self.public.is_empty() {
+            return html.to_string();
+        }
+        // ASCII lowercasing keeps byte offsets, so they apply to `html`.
+        let lower = html.to_ascii_lowercase();
+        let mut output = String::with_capacity(html.len());
+        let mut last = 0;
+        while let Some(open) = lower[last..].find("<script").map(|i| last + i) {
+            let Some(body_start) = lower[open..].find('>').map(|i| open + i + 1) else {
+                break;
+            };
+            let body_end = lower[body_start..]
+                .find("</script")
+                .map_or(html.len(), |i| body_start + i);
+            output.push_str(&html[last..body_start]);
+            output.push_str(&self.inline(&html[body_start..body_end]));
+            last = body_end;
+        }
+        output.push_str(&html[last..]);
+        output
+    }
+
+    /// Compile-time constants for bundlers with a `define` option:
+    /// `process.env.NAME` and `import.meta.env.NAME` of each public variable,
+    /// mapped to its value as a JS string literal.
+    pub fn defines(&self) -> BTreeMap<String, String> {
+        let mut defines = BTreeMap::new();
+        for (name, value) in &self.public {
+            let literal = serde_json::to_string(value).unwrap_or_default();
+            defines.insert(format!("process.env.{}", name), literal.clone());
+            defines.insert(format!("import.meta.env.{}", name), literal);
+        }
+        defines
+    }
+}
+
+/// `KEY=value` lines of a `.env` file. Supports `export KEY=...`, comments,
+/// single quotes (literal), double quotes (with `\n` escapes, spanning lines)
+/// and `${OTHER}` references to earlier variables or the shell.
+pub fn parse(contents: &str, defined: &BTreeMap<String, String>) -> Result<Vec<(String, String)>> {
+    let mut vars: Vec<(String, String)> = Vec::new();
+    let mut lines = contents.lines().enumerate();
+    while let Some((index, line)) = lines.next() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let line = line.strip_prefix("export ").unwrap_or(line);
+        let (key, value) = line
+            .split_once('=')
+            .with_context(|| format!("line {}: expected KEY=value", index + 1))?;
+        let key = key.trim();
+        if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            anyhow::bail!("line {}: invalid variable name '{}'", index + 1, key);
+        }
+
+        let lookup = |name: &str| {
+            std::env::var(name)
+                .ok()
+                .or_else(|| vars.iter().rev().find(|(key, _)| key == name).map(|(_, value)| value.clone()))
+                .or_else(|| defined.get(name).cloned())
+                .unwrap_or_default()
+        };
+        let value = value.trim();
+        let value = if let Some(quoted) = value.strip_prefix('\'') {
+            match quoted.find('\'') {
+                Some(end) => quoted[..end].to_string(),
+                None => anyhow::bail!("line {}: unterminated single quote", index + 1),
+            }
+        } else if let Some(quoted) = value.strip_prefix('"') {
+            let mut raw = quoted.to_string();
+            while closing_quote(&raw).is_none() {
+                let (_, next) = lines
+                    .next()
+                    .with_context(|| format!("line {}: unterminated double quote", index + 1))?;
+                raw.push('\n');
+                raw.push_str(next);
+            }
+            let raw = &raw[..closing_quote(&raw).unwrap_or(raw.len())];
+            expand(&unescape(raw), lookup)
+        } else {
+            // Unquoted values end at a ` #` comment.
+            let value = value.split(" #").next().unwrap_or_default().trim_end();
+            expand(value, lookup)
+        };
+        vars.push((key.to_string(), value));
+    }
+    Ok(vars)
+}
+
+/// Index of the first unescaped `"`.
+fn closing_quote(text: &str) -> Option<usize> {
+    let mut escaped = false;
+    for (index, c) in text.char_indices() {
+        match c {
+            '\\' if !escaped => escaped = true,
+            '"' if !escaped => return Some(index),
+            _ => escaped = false,
+        }
+    }
+    None
+}
+
+fn unescape(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            output.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => output.push('\n'),
+            Some('r') => output.push('\r'),
+            Some('t') => output.push('\t'),
+            Some(other) => output.push(other),
+            None => output.push('\\'),
+        }
+    }
+    output
+}
+
+/// Replaces `${NAME}` with `lookup(NAME)`.
+fn expand(text: &str, lookup: impl Fn(&str) -> String) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("${") {
+        let Some(end) = rest[start..].find('}') else {
+            break;
+        };
+        output.push_str(&rest[..start]);
+        output.push_str(&lookup(&rest[start + 2..start + end]));
+        rest = &rest[start + end + 1..];
+    }
+    output.push_str(rest);
+    output
+}
@@ -6,17 +6,41 @@ use std::io::Write;
 use std::path::PathBuf;
 
 mod commands;
+mod components;
 mod config;
+#[cfg(feature = "web")]
+mod dev_server;
+mod env;
+mod manifest;
+mod naming;
+mod package_json;
 mod project;
+#[cfg(feature = "web")]
+mod proxy;
+mod pwa;
+mod recipes;
+mod routes;
 mod ai;
+mod api;
 mod web;
 mod builder;
+mod cache;
+mod registry;
+mod schema;
+mod ssg;
+mod static_site;
+mod templates;
 mod types;
+mod watch;
+mod wizard;
+mod workspace;
+mod writer;
 
 use config::Config;
+use writer::{ConflictPolicy, WriteOptions};
 use types::{
-    ApiCommands, ConfigCommands, DbCommands, DeployTarget, FsCommands, PluginCommands,
-    ComponentType,
+    ApiCommands, BuildTarget, ComponentOptions, ConfigCommands, DbCommands, DepsCommands, DeployTarget,
+    FsCommands, MemberSelection, PluginCommands, ComponentType, TemplateCommands,
 };
 
 #[derive(Parser)]
@@ -38,8 +62,10 @@ Features:
 
 Examples:
   nexus new my-app                    # Create new project
+  nexus new                           # Create a project with the wizard
   nexus component button PrimaryButton  # Generate component
   nexus build                         # Build project
+  nexus build --workspace             # Build every workspace member in order
   nexus serve                         # Start development server
   nexus ai "create login form"        # Generate code with AI
   nexus web                           # Launch web interface
@@ -54,26 +80,75 @@ struct Cli {
 
     #[arg(short, long, global = true)]
     config: Option<PathBuf>,
+
+    /// Project directory or nexus.yaml to use; searched upwards like the
+    /// current directory [default: .]
+    #[arg(long, global = true, value_name = "PATH")]
+    project: Option<PathBuf>,
+
+    /// Print the files a command would write without writing them
+    #[arg(long, global = true)]
+    dry_run: bool,
+
+    /// Show unified diffs against existing files without writing them
+    #[arg(long, global = true)]
+    diff: bool,
+
+    /// What to do when a file to be written already exists
+    #[arg(long, global = true, value_enum, default_value = "prompt")]
+    on_conflict: ConflictPolicy,
 }
 
 #[derive(Subcommand)]
 enum Commands {
     /// Create a new project
     New {
-        /// Project name
-        name: String,
+        /// Project name. Omit it to start the interactive wizard
+        name: Option<String>,
 
-        /// Template to use
+        /// Template name or path to a template directory
         #[arg(short, long, default_value = "default")]
         template: String,
 
-        /// Framework to use
+        /// Framework to use (react, react-ts, vue, svelte, angular, nextjs, html)
         #[arg(short, long, default_value = "react")]
         framework: String,
 
         /// Initialize with AI assistance
         #[arg(short = 'a', long)]
         ai: bool,
+
+        /// AI model used with --ai
+        #[arg(long, default_value = "phi-3-mini")]
+        ai_model: String,
+
+        /// Template variable as key=value (repeatable)
+        #[arg(long = "var", value_name = "KEY=VALUE", value_parser = parse_key_value)]
+        vars: Vec<(String, String)>,
+
+        /// Install dependencies after creating the project
+        #[arg(long)]
+        install: bool,
+    },
+
+    /// Add a feature recipe (tailwind, router, state, testing, lint, auth, pwa)
+    Add {
+        /// Recipe names or paths to recipe directories. Omit to list built-in recipes
+        recipes: Vec<String>,
+
+        /// Recipe variable as key=value (repeatable)
+        #[arg(long = "var", value_name = "KEY=VALUE", value_parser = parse_key_value)]
+        vars: Vec<(String, String)>,
+
+        /// Install new dependencies afterwards
+        #[arg(long)]
+        install: bool,
+    },
+
+    /// Manage package.json dependencies
+    Deps {
+        #[command(subcommand)]
+        deps_command: DepsCommands,
     },
 
     /// Generate a component
@@ -89,39 +164,60 @@ enum Commands {
         #[arg(short = 'a', long)]
         ai: bool,
 
-        /// Framework for the component
-        #[arg(short, long, default_value = "react")]
-        framework: String,
+        /// Framework for the component. Defaults to the project's framework
+        #[arg(short, long)]
+        framework: Option<String>,
+
+        #[command(flatten)]
+        options: ComponentOptions,
     },
 
     /// Build project
     Build {
-        /// Build mode
-        #[arg(short, long, default_value = "production")]
-        mode: String,
+        /// Build mode [default: build.mode from nexus.yaml]
+        #[arg(short, long)]
+        mode: Option<String>,
 
-        /// Target platform
-        #[arg(short, long, default_value = "web")]
-        target: String,
+        /// What to build [default: build.target from nexus.yaml]
+        #[arg(short, long, value_enum)]
+        target: Option<BuildTarget>,
 
         /// Output directory
         #[arg(short, long)]
         out_dir: Option<PathBuf>,
+
+        /// Build from scratch without reading or updating .nexus/cache
+        #[arg(long)]
+        no_cache: bool,
+
+        /// Rebuild when project files change, until Ctrl+C
+        #[arg(short, long)]
+        watch: bool,
+
+        #[command(flatten)]
+        selection: MemberSelection,
     },
 
     /// Serve project locally
     Serve {
-        /// Port to serve on
-        #[arg(short, long, default_value_t = 3000)]
-        port: u16,
+        /// Port to serve on [default: serve.port from nexus.yaml]
+        #[arg(short, long)]
+        port: Option<u16>,
 
-        /// Host to bind to
-        #[arg(long, default_value = "127.0.0.1")]
-        host: String,
+        /// Host to bind to [default: serve.host from nexus.yaml]
+        #[arg(long)]
+        host: Option<String>,
 
         /// Open browser automatically
         #[arg(short, long)]
         open: bool,
+
+        /// Do not rebuild and reload pages when files change
+        #[arg(long)]
+        no_reload: bool,
+
+        #[command(flatten)]
+        selection: MemberSelection,
     },
 
     /// Deploy project
@@ -137,6 +233,9 @@ enum Commands {
         /// Deploy with preview
         #[arg(short, long)]
         preview: bool,
+
+        #[command(flatten)]
+        selection: MemberSelection,
     },
 
     /// AI code generation
@@ -202,14 +301,30 @@ enum Commands {
         config_command: ConfigCommands,
     },
 
-    /// List available templates
-    Templates,
+    /// List and manage project templates
+    Templates {
+        #[command(subcommand)]
+        template_command: Option<TemplateCommands>,
+    },
+
+    /// Migrate nexus.yaml to the current schema version
+    Upgrade,
 
     /// Show version and information
     Info,
 
-    /// Clear cache and temporary files
-    Clean,
+    /// Clear the build cache in .nexus/cache
+    Clean {
+        #[command(flatten)]
+        selection: MemberSelection,
+    },
+}
+
+fn parse_key_value(raw: &str) -> Result<(String, String), String> {
+    raw.split_once('=')
+        .map(|(key, value)| (key.trim().to_string(), value.to_string()))
+        .filter(|(key, _)| !key.is_empty())
+        .ok_or_else(|| format!("expected KEY=VALUE, got '{}'", raw))
 }
 
 #[tokio::main]
@@ -236,29 +351,82 @@ async fn main() -> Result<()> {
         Config::default()
     };
 
+    let project_dir = cli.project.clone().unwrap_or_else(|| PathBuf::from("."));
+    if !project_dir.exists() {
+        anyhow::bail!("--project path {} does not exist", project_dir.display());
+    }
+
+    let write_options = WriteOptions {
+        dry_run: cli.dry_run,
+        diff: cli.diff,
+        conflict: cli.on_conflict,
+    };
+
     match &cli.command {
-        Commands::New { name, template, framework, ai } => {
-            commands::new_project(name, template, framework, *ai).await?;
+        Commands::New { name, template, framework, ai, ai_model, vars, install } => {
+            let vars = vars.iter().cloned().collect();
+            match name {
+                Some(name) => {
+                    let ai_model = ai.then_some(ai_model.as_str());
+                    commands::new_project(name, template, framework, ai_model, &vars, *install, write_options)
+                        .await?;
+                }
+                None => {
+                    commands::new_project_wizard(
+                        template, framework, *ai, ai_model, &vars, *install, write_options,
+                    )
+                    .await?;
+                }
+            }
         }
 
-        Commands::Component { component_type, name, ai, framework } => {
-            commands::create_component(component_type, name, *ai, framework).await?;
+        Commands::Add { recipes, vars, install } => {
+            let vars = vars.iter().cloned().collect();
+            commands::add_recipes(recipes, &vars, *install, &project_dir, write_options).await?;
         }
 
-        Commands::Build { mode, target, out_dir } => {
-            commands::build_project(mode, target, out_dir.as_ref()).await?;
+        Commands::Deps { deps_command } => {
+            commands::handle_deps(deps_command, &project_dir, write_options).await?;
         }
 
-        Commands::Serve { port, host, open } => {
-            commands::serve_project(*port, host, *open).await?;
+        Commands::Component { component_type, name, ai, framework, options } => {
+            commands::create_component(
+                component_type,
+                name,
+                *ai,
+                framework.as_deref(),
+                options,
+                &project_dir,
+                write_options,
+            )
+            .await?;
         }
 
-        Commands::Deploy { target, env, preview } => {
-            commands::deploy_project(target, env, *preview).await?;
+        Commands::Build { mode, target, out_dir, no_cache, watch, selection } => {
+            commands::build_project(
+                mode.as_deref(),
+                *target,
+                out_dir.as_deref(),
+                !no_cache,
+                *watch,
+                &project_dir,
+                selection,
+            )
+            .await?;
+        }
+
+        Commands::Serve { port, host, open, no_reload, selection } => {
+            commands::serve_project(*port, host.as_deref(), *open, !no_reload, &project_dir, selection)
+                .await?;
+        }
+
+        Commands::Deploy { target, env, preview, selection } => {
+            commands::deploy_project(target, env, *preview, write_options, &project_dir, selection)
+                .await?;
         }
 
         Commands::Ai { prompt, model, output, framework } => {
-            commands::ai_generate(prompt, model, output.as_ref(), framework).await?;
+            commands::ai_generate(prompt, model, output.as_ref(), framework, write_options).await?;
         }
 
         Commands::Db { db_command } => {
@@ -285,16 +453,20 @@ async fn main() -> Result<()> {
             commands::handle_config(config_command, &config).await?;
         }
 
-        Commands::Templates => {
-            commands::list_templates().await?;
+        Commands::Templates { template_command } => {
+            commands::handle_templates(template_command.as_ref()).await?;
+        }
+
+        Commands::Upgrade => {
+            commands::upgrade_project(&project_dir, write_options).await?;
         }
 
         Commands::Info => {
             commands::show_info().await?;
         }
 
-        Commands::Clean => {
-            commands::clean_cache().await?;
+        Commands::Clean { selection } => {
+            commands::clean_cache(&project_dir, selection).await?;
         }
     }
 
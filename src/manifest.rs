@@ -0,0 +1,345 @@
+use crate::cache::content_hash;
+use crate::schema::{BudgetConfig, ByteSize, SizeMeasure};
+use crate::types::BuildTarget;
+use anyhow::{Context, Result};
+use flate2::write::GzEncoder;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
+use std::path::Path;
+use walkdir::WalkDir;
+
+/// Written to the root of the build output.
+pub const MANIFEST_FILE: &str = "nexus-manifest.json";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Sizes {
+    pub size: u64,
+    pub gzip: u64,
+    pub brotli: u64,
+}
+
+impl Sizes {
+    pub fn get(&self, measure: SizeMeasure) -> u64 {
+        match measure {
+            SizeMeasure::Raw => self.size,
+            SizeMeasure::Gzip => self.gzip,
+            SizeMeasure::Brotli => self.brotli,
+        }
+    }
+}
+
+impl std::ops::Add for Sizes {
+    type Output = Sizes;
+
+    fn add(self, other: Sizes) -> Sizes {
+        Sizes {
+            size: self.size + other.size,
+            gzip: self.gzip + other.gzip,
+            brotli: self.brotli + other.brotli,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestFile {
+    /// `/`-separated path relative to the output directory.
+    pub path: String,
+    #[serde(flatten)]
+    pub sizes: Sizes,
+    pub hash: String,
+}
+
+/// Every file a build emitted, with its raw and compressed sizes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BuildManifest {
+    pub mode: String,
+    pub target: BuildTarget,
+    pub total: Sizes,
+    #[serde(default)]
+    pub entries: TargetEntries,
+    pub files: Vec<ManifestFile>,
+}
+
+/// The files that make up the build's target, relative to the output.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TargetEntries {
+    /// The app's HTML entry point.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub index: Option<String>,
+    /// Route to HTML file, for `ssg`.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub pages: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sitemap: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub service_worker: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub web_manifest: Option<String>,
+    /// The ES module of a `lib` build.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub module: Option<String>,
+    /// Its type declarations.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub types: Option<String>,
+}
+
+impl TargetEntries {
+    fn find(target: BuildTarget, paths: &[String]) -> Self {
+        let first = |candidates: &[&str]| {
+            candidates
+                .iter()
+                .find(|candidate| paths.iter().any(|path| path == *candidate))
+                .map(|candidate| candidate.to_string())
+        };
+        let mut entries = TargetEntries::default();
+        match target {
+            BuildTarget::Lib => {
+                entries.module = first(&["index.js", "index.mjs"]);
+                entries.types = first(&["index.d.ts", "types/index.d.ts", "types/src/index.d.ts"]);
+            }
+            _ => entries.index = first(&["index.html"]),
+        }
+        if target == BuildTarget::Ssg {
+            entries.pages = paths
+                .iter()
+                .filter(|path| path.ends_with(".html"))
+                .map(|path| (route_of(path), path.clone()))
+                .collect();
+            entries.sitemap = first(&["sitemap.xml"]);
+        }
+        if target == BuildTarget::Pwa {
+            entries.service_worker = first(&["sw.js", "service-worker.js"]);
+            entries.web_manifest = first(&["manifest.webmanifest", "manifest.json"]);
+        }
+        entries
+    }
+}
+
+/// The URL path an HTML file is served at: `index.html` is `/`,
+/// `blog/index.html` is `/blog/` and `about.html` is `/about.html`.
+pub fn route_of(path: &str) -> String {
+    match path.strip_suffix("index.html") {
+        Some(dir) if dir.is_empty() || dir.ends_with('/') => format!("/{}", dir),
+        _ => format!("/{}", path),
+    }
+}
+
+/// How one file's size changed between two builds.
+#[derive(Debug, Clone)]
+pub struct SizeDelta {
+    pub path: String,
+    pub before: Option<u64>,
+    pub after: Option<u64>,
+}
+
+impl SizeDelta {
+    pub fn change(&self) -> i64 {
+        self.after.unwrap_or(0) as i64 - self.before.unwrap_or(0) as i64
+    }
+}
+
+/// A file, or the whole build (`path` is `None`), over its budget.
+#[derive(Debug, Clone)]
+pub struct BudgetViolation {
+    pub path: Option<String>,
+    pub size: u64,
+    pub limit: ByteSize,
+}
+
+impl BuildManifest {
+    /// Measures every file in `output`, compressing them in parallel.
+    pub fn generate(output: &Path, mode: &str, target: BuildTarget) -> Result<Self> {
+        let mut paths = Vec::new();
+        for entry in WalkDir::new(output) {
+            let entry = entry?;
+            if entry.file_type().is_file() {
+                let relative = entry.path().strip_prefix(output)?.to_string_lossy().replace('\\', "/");
+                if relative != MANIFEST_FILE {
+                    paths.push(relative);
+                }
+            }
+        }
+        paths.sort();
+
+        let entries = TargetEntries::find(target, &paths);
+        let files = paths
+            .into_par_iter()
+            .map(|path| {
+                let contents = std::fs::read(output.join(&path))
+                    .with_context(|| format!("Failed to read {}", output.join(&path).display()))?;
+                Ok(ManifestFile {
+                    sizes: Sizes {
+                        size: contents.len() as u64,
+                        gzip: gzip_size(&contents)?,
+                        brotli: brotli_size(&contents)?,
+                    },
+                    hash: content_hash(&contents),
+                    path,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            mode: mode.to_string(),
+            target,
+            total: files.iter().fold(Sizes::default(), |total, file| total + file.sizes),
+            entries,
+            files,
+        })
+    }
+
+    /// The manifest in `output`, if a build left a readable one there.
+    pub fn read(output: &Path) -> Option<Self> {
+        let contents = std::fs::read_to_string(output.join(MANIFEST_FILE)).ok()?;
+        serde_json::from_str(&contents).ok()
+    }
+
+    pub fn write(&self, output: &Path) -> Result<()> {
+        std::fs::write(output.join(MANIFEST_FILE), serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write {}", output.join(MANIFEST_FILE).display()))
+    }
+
+    /// Files whose raw size changed since `previous`, largest change first.
+    /// Fingerprinted files are paired by their name without the hash, so
+    /// `app.1a2b3c4d.js` is compared with the previous `app.5e6f7a8b.js`.
+    pub fn compare(&self, previous: &BuildManifest) -> Vec<SizeDelta> {
+        let mut unmatched: HashMap<&str, u64> = previous
+            .files
+            .iter()
+            .map(|file| (file.path.as_str(), file.sizes.size))
+            .collect();
+        // Same path first, then the same name without a hash.
+        let mut before: Vec<Option<u64>> = self
+            .files
+            .iter()
+            .map(|file| unmatched.remove(file.path.as_str()))
+            .collect();
+        for (file, before) in self.files.iter().zip(before.iter_mut()) {
+            if before.is_some() {
+                continue;
+            }
+            let name = unhashed_name(&file.path);
+            let renamed = unmatched
+                .keys()
+                .find(|path| unhashed_name(path) == name)
+                .copied();
+            if let Some(path) = renamed {
+                *before = unmatched.remove(path);
+            }
+        }
+
+        let mut deltas: Vec<SizeDelta> = self
+            .files
+            .iter()
+            .zip(before)
+            .filter(|(file, before)| *before != Some(file.sizes.size))
+            .map(|(file, before)| SizeDelta {
+                path: file.path.clone(),
+                before,
+                after: Some(file.sizes.size),
+            })
+            .collect();
+        deltas.extend(unmatched.into_iter().map(|(path, size)| SizeDelta {
+            path: path.to_string(),
+            before: Some(size),
+            after: None,
+        }));
+
+        deltas.sort_by(|a, b| {
+            b.change()
+                .abs()
+                .cmp(&a.change().abs())
+                .then_with(|| a.path.cmp(&b.path))
+        });
+        deltas
+    }
+
+    /// Files and totals over the limits in `budgets`.
+    pub fn check_budgets(&self, budgets: &BudgetConfig) -> Result<Vec<BudgetViolation>> {
+        let measure = budgets.measure;
+        let patterns = budgets
+            .files
+            .iter()
+            .map(|(pattern, limit)| {
+                glob::Pattern::new(pattern)
+                    .map(|compiled| (compiled, *limit))
+                    .with_context(|| format!("Invalid budget pattern '{}' in nexus.yaml", pattern))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut violations = Vec::new();
+        for file in &self.files {
+            let size = file.sizes.get(measure);
+            // The tightest limit that applies to the file.
+            let limit = patterns
+                .iter()
+                .filter(|(pattern, _)| pattern.matches(&file.path))
+                .map(|(_, limit)| *limit)
+                .chain(budgets.per_file)
+                .min();
+            if let Some(limit) = limit.filter(|limit| size > limit.0) {
+                violations.push(BudgetViolation {
+                    path: Some(file.path.clone()),
+                    size,
+                    limit,
+                });
+            }
+        }
+        if let Some(limit) = budgets.total.filter(|limit| self.total.get(measure) > limit.0) {
+            violations.push(BudgetViolation {
+                path: None,
+                size: self.total.get(measure),
+                limit,
+            });
+        }
+        Ok(violations)
+    }
+}
+
+fn gzip_size(contents: &[u8]) -> Result<u64> {
+    let mut encoder = GzEncoder::new(Vec::new(), flate2::Compression::best());
+    encoder.write_all(contents)?;
+    Ok(encoder.finish()?.len() as u64)
+}
+
+fn brotli_size(contents: &[u8]) -> Result<u64> {
+    let mut compressed = Vec::new();
+    {
+        let mut writer = brotli::CompressorWriter::new(&mut compressed, 4096, 11, 22);
+        writer.write_all(contents)?;
+    }
+    Ok(compressed.len() as u64)
+}
+
+/// Whether `path` names its content, so it can be cached for good: a hashed
+/// file name, or a directory Next.js and SvelteKit keep such files in.
+pub fn is_fingerprinted(path: &str) -> bool {
+    let path = path.trim_start_matches('/');
+    path.starts_with("_next/static/") || path.contains("/immutable/") || unhashed_name(path) != path
+}
+
+/// `assets/index-B2xk9aQ1.js` and `css/app.1a2b3c4d.css` without the content
+/// hash Vite and the static builder add: `assets/index.js`, `css/app.css`.
+fn unhashed_name(path: &str) -> String {
+    let (dir, file) = match path.rsplit_once('/') {
+        Some((dir, file)) => (format!("{}/", dir), file),
+        None => (String::new(), path),
+    };
+    let Some((stem, extension)) = file.rsplit_once('.') else {
+        return path.to_string();
+    };
+    for separator in ['.', '-'] {
+        if let Some((name, hash)) = stem.rsplit_once(separator) {
+            let looks_hashed = hash.len() == 8
+                && hash.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+                && hash.chars().any(|c| c.is_ascii_digit());
+            if looks_hashed && !name.is_empty() {
+                return format!("{}{}.{}", dir, name, extension);
+            }
+        }
+    }
+    path.to_string()
+}
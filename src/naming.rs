@@ -0,0 +1,162 @@
+use anyhow::Result;
+
+/// JavaScript reserved words, which cannot name a function or variable.
+const RESERVED_WORDS: &[&str] = &[
+    "await", "break", "case", "catch", "class", "const", "continue", "debugger", "default",
+    "delete", "do", "else", "enum", "export", "extends", "false", "finally", "for", "function",
+    "if", "implements", "import", "in", "instanceof", "interface", "let", "new", "null",
+    "package", "private", "protected", "public", "return", "static", "super", "switch", "this",
+    "throw", "true", "try", "typeof", "var", "void", "while", "with", "yield",
+];
+
+/// Component names that shadow globals or framework built-ins.
+const RESERVED_COMPONENTS: &[&str] = &[
+    "Array", "Boolean", "Date", "Error", "Function", "Map", "Math", "Number", "Object",
+    "Promise", "Set", "String", "Symbol", "Component", "Fragment", "KeepAlive", "Slot",
+    "Suspense", "Teleport", "Template", "Transition", "TransitionGroup",
+];
+
+/// A component name as typed, e.g. `forms/login-form`, split into its folder
+/// and the name in each casing the frameworks use.
+#[derive(Debug, Clone)]
+pub struct ComponentName {
+    /// Subdirectories below the component type's folder.
+    pub dirs: Vec<String>,
+    words: Vec<String>,
+}
+
+impl ComponentName {
+    pub fn parse(input: &str) -> Result<Self> {
+        let input = input.trim().trim_matches('/');
+        let mut segments: Vec<&str> = input.split('/').collect();
+        let name = segments.pop().unwrap_or_default();
+
+        for dir in &segments {
+            let valid = !dir.is_empty()
+                && dir
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+            if !valid {
+                anyhow::bail!(
+                    "Invalid folder '{}' in '{}'. Use letters, digits, '-' and '_'",
+                    dir,
+                    input
+                );
+            }
+        }
+
+        if !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | ' '))
+        {
+            anyhow::bail!(
+                "Invalid component name '{}'. Use letters, digits, '-', '_' or spaces",
+                name
+            );
+        }
+        if !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            anyhow::bail!("Component name '{}' must start with a letter", name);
+        }
+
+        Ok(Self {
+            dirs: segments.into_iter().map(str::to_string).collect(),
+            words: split_words(name),
+        })
+    }
+
+    /// `LoginForm`, for components and classes.
+    pub fn pascal(&self) -> String {
+        self.words.iter().map(|word| capitalize(word)).collect()
+    }
+
+    /// `loginForm`, for functions and variables.
+    pub fn camel(&self) -> String {
+        let pascal = self.pascal();
+        let mut chars = pascal.chars();
+        match chars.next() {
+            Some(first) => first.to_lowercase().chain(chars).collect(),
+            None => String::new(),
+        }
+    }
+
+    /// `login-form`, for Angular files, selectors and URLs.
+    pub fn kebab(&self) -> String {
+        self.words.join("-")
+    }
+
+    /// `Login Form`, for headings.
+    pub fn title(&self) -> String {
+        self.words
+            .iter()
+            .map(|word| capitalize(word))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// `/admin/user-list` for `admin/UserList`, the default route of a page.
+    pub fn route_path(&self) -> String {
+        self.dirs
+            .iter()
+            .map(|dir| split_words(dir).join("-"))
+            .chain(std::iter::once(self.kebab()))
+            .fold(String::new(), |path, segment| format!("{}/{}", path, segment))
+    }
+
+    /// `file_name` inside the name's subdirectories, e.g. `forms/LoginForm.tsx`.
+    pub fn nested(&self, file_name: &str) -> String {
+        self.dirs
+            .iter()
+            .map(String::as_str)
+            .chain(std::iter::once(file_name))
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    /// Rejects identifiers that are reserved words or shadow built-ins.
+    pub fn check_identifier(&self, identifier: &str) -> Result<()> {
+        if RESERVED_WORDS.contains(&identifier) {
+            anyhow::bail!("'{}' is a reserved word in JavaScript; choose another name", identifier);
+        }
+        if RESERVED_COMPONENTS.contains(&identifier) {
+            anyhow::bail!(
+                "'{}' shadows a JavaScript global or framework built-in; choose another name",
+                identifier
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Lowercase words of a name in any casing: `loginForm`, `LoginForm`,
+/// `login-form`, `login_form` and `HTTPClient` (`http`, `client`).
+fn split_words(name: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    for part in name.split(['-', '_', ' ']).filter(|part| !part.is_empty()) {
+        let chars: Vec<char> = part.chars().collect();
+        let mut word = String::new();
+        for (i, c) in chars.iter().enumerate() {
+            let boundary = i > 0
+                && c.is_ascii_uppercase()
+                && (chars[i - 1].is_ascii_lowercase()
+                    || chars[i - 1].is_ascii_digit()
+                    || (chars[i - 1].is_ascii_uppercase()
+                        && chars.get(i + 1).is_some_and(|next| next.is_ascii_lowercase())));
+            if boundary && !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            word.push(c.to_ascii_lowercase());
+        }
+        if !word.is_empty() {
+            words.push(word);
+        }
+    }
+    words
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
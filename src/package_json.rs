@@ -0,0 +1,407 @@
+use crate::templates::DependencySpec;
+use anyhow::{Context, Result};
+use colored::*;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::process::Command;
+
+pub const PACKAGE_JSON: &str = "package.json";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DependencyKind {
+    Prod,
+    Dev,
+}
+
+impl DependencyKind {
+    pub const ALL: [DependencyKind; 2] = [DependencyKind::Prod, DependencyKind::Dev];
+
+    pub fn section(self) -> &'static str {
+        match self {
+            DependencyKind::Prod => "dependencies",
+            DependencyKind::Dev => "devDependencies",
+        }
+    }
+}
+
+/// A parsed package.json. Key order, indentation, line endings and the final
+/// newline are kept, so an edit only changes the lines it touches.
+#[derive(Debug, Clone)]
+pub struct PackageJson {
+    fields: Map<String, Value>,
+    indent: String,
+    crlf: bool,
+    trailing_newline: bool,
+}
+
+/// Dependencies and scripts added by [`PackageJson::declare`].
+#[derive(Debug, Default)]
+pub struct Declared {
+    pub dependencies: Vec<String>,
+    pub scripts: Vec<String>,
+}
+
+impl Declared {
+    pub fn is_empty(&self) -> bool {
+        self.dependencies.is_empty() && self.scripts.is_empty()
+    }
+}
+
+impl PackageJson {
+    pub fn new(name: &str) -> Self {
+        let mut fields = Map::new();
+        fields.insert("name".to_string(), Value::String(name.to_string()));
+        fields.insert("private".to_string(), Value::Bool(true));
+        Self {
+            fields,
+            indent: "  ".to_string(),
+            crlf: false,
+            trailing_newline: true,
+        }
+    }
+
+    pub fn parse(contents: &str) -> Result<Self> {
+        let value: Value = serde_json::from_str(contents).context("Invalid package.json")?;
+        let Value::Object(fields) = value else {
+            anyhow::bail!("package.json must contain an object");
+        };
+        let indent = contents
+            .lines()
+            .skip(1)
+            .find(|line| !line.trim().is_empty())
+            .map(|line| {
+                line.chars()
+                    .take_while(|c| *c == ' ' || *c == '\t')
+                    .collect::<String>()
+            })
+            .filter(|indent| !indent.is_empty())
+            .unwrap_or_else(|| "  ".to_string());
+        Ok(Self {
+            fields,
+            indent,
+            crlf: contents.contains("\r\n"),
+            trailing_newline: contents.ends_with('\n'),
+        })
+    }
+
+    /// Reads `package.json` from a project root, if there is one.
+    pub fn read(root: &Path) -> Result<Option<Self>> {
+        let path = root.join(PACKAGE_JSON);
+        if !path.is_file() {
+            return Ok(None);
+        }
+        let contents = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        Self::parse(&contents)
+            .with_context(|| format!("Failed to parse {}", path.display()))
+            .map(Some)
+    }
+
+    pub fn render(&self) -> Result<String> {
+        let mut buffer = Vec::new();
+        let formatter = serde_json::ser::PrettyFormatter::with_indent(self.indent.as_bytes());
+        let mut serializer = serde_json::Serializer::with_formatter(&mut buffer, formatter);
+        self.fields.serialize(&mut serializer)?;
+        let mut rendered = String::from_utf8(buffer)?;
+        if self.trailing_newline {
+            rendered.push('\n');
+        }
+        if self.crlf {
+            rendered = rendered.replace('\n', "\r\n");
+        }
+        Ok(rendered)
+    }
+
+    /// The `packageManager` field, e.g. `pnpm@8.15.0`.
+    pub fn package_manager(&self) -> Option<&str> {
+        self.fields.get("packageManager").and_then(Value::as_str)
+    }
+
+    /// The `source` field: the entry point library bundlers start from.
+    pub fn source(&self) -> Option<&str> {
+        self.fields.get("source").and_then(Value::as_str)
+    }
+
+    /// Names in `dependencies` and `peerDependencies`, which a library
+    /// leaves for its users to install.
+    pub fn runtime_dependencies(&self) -> Vec<String> {
+        ["dependencies", "peerDependencies"]
+            .iter()
+            .filter_map(|section| self.fields.get(*section).and_then(Value::as_object))
+            .flat_map(|deps| deps.keys().cloned())
+            .collect()
+    }
+
+    pub fn dependency(&self, name: &str) -> Option<(DependencyKind, &str)> {
+        DependencyKind::ALL.into_iter().find_map(|kind| {
+            self.section(kind)
+                .and_then(|deps| deps.get(name))
+                .and_then(Value::as_str)
+                .map(|version| (kind, version))
+        })
+    }
+
+    /// Every dependency as (kind, name, version), in file order.
+    pub fn dependencies(&self) -> Vec<(DependencyKind, String, String)> {
+        DependencyKind::ALL
+            .into_iter()
+            .flat_map(|kind| {
+                self.section(kind)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(move |(name, version)| {
+                        version
+                            .as_str()
+                            .map(|version| (kind, name.clone(), version.to_string()))
+                    })
+            })
+            .collect()
+    }
+
+    /// Adds a dependency or changes its version. A dependency listed under
+    /// the other kind is moved, like `npm install --save-dev` does. Returns
+    /// false when nothing changed.
+    pub fn set_dependency(&mut self, name: &str, version: &str, kind: DependencyKind) -> Result<bool> {
+        if self.dependency(name) == Some((kind, version)) {
+            return Ok(false);
+        }
+        for other in DependencyKind::ALL.into_iter().filter(|other| *other != kind) {
+            if let Some(Value::Object(deps)) = self.fields.get_mut(other.section()) {
+                deps.shift_remove(name);
+            }
+        }
+        self.section_mut(kind.section())?
+            .insert(name.to_string(), Value::String(version.to_string()));
+        Ok(true)
+    }
+
+    /// Removes a dependency, returning the version it had.
+    pub fn remove_dependency(&mut self, name: &str) -> Option<String> {
+        let mut removed = None;
+        for kind in DependencyKind::ALL {
+            if let Some(Value::Object(deps)) = self.fields.get_mut(kind.section()) {
+                if let Some(Value::String(version)) = deps.shift_remove(name) {
+                    removed = Some(version);
+                }
+            }
+        }
+        removed
+    }
+
+    /// Adds what a template or recipe declares for `framework`. Dependencies
+    /// the project already has keep their version and kind, and existing
+    /// scripts are left alone.
+    pub fn declare<'a>(
+        &mut self,
+        framework: &str,
+        dependencies: impl IntoIterator<Item = &'a DependencySpec>,
+        scripts: &BTreeMap<String, String>,
+    ) -> Result<Declared> {
+        let mut declared = Declared::default();
+        for dependency in dependencies {
+            if !dependency.applies_to(framework) || self.dependency(&dependency.name).is_some() {
+                continue;
+            }
+            let kind = if dependency.dev { DependencyKind::Dev } else { DependencyKind::Prod };
+            self.set_dependency(&dependency.name, &dependency.version, kind)?;
+            declared
+                .dependencies
+                .push(format!("{}@{}", dependency.name, dependency.version));
+        }
+        for (name, command) in scripts {
+            let section = self.section_mut("scripts")?;
+            if !section.contains_key(name) {
+                section.insert(name.clone(), Value::String(command.clone()));
+                declared.scripts.push(name.clone());
+            }
+        }
+        Ok(declared)
+    }
+
+    fn section(&self, kind: DependencyKind) -> Option<&Map<String, Value>> {
+        self.fields.get(kind.section()).and_then(Value::as_object)
+    }
+
+    fn section_mut(&mut self, key: &str) -> Result<&mut Map<String, Value>> {
+        self.fields
+            .entry(key)
+            .or_insert_with(|| Value::Object(Map::new()))
+            .as_object_mut()
+            .with_context(|| format!("\"{}\" in package.json must be an object", key))
+    }
+}
+
+/// Splits `name@version` (including scoped names like `@types/node@20`).
+pub fn parse_spec(spec: &str) -> Result<(&str, Option<&str>)> {
+    let (name, version) = match spec.get(1..).and_then(|rest| rest.find('@')) {
+        Some(at) => (&spec[..at + 1], Some(&spec[at + 2..])),
+        None => (spec, None),
+    };
+    if name.is_empty() || version == Some("") {
+        anyhow::bail!("Invalid package '{}'. Use name or name@version", spec);
+    }
+    Ok((name, version))
+}
+
+/// Latest published version of a package, from `npm view`.
+pub fn latest_version(name: &str) -> Result<String> {
+    let output = Command::new("npm")
+        .args(["view", name, "version"])
+        .output()
+        .with_context(|| {
+            format!("Failed to run npm to look up {}. Pass a version as {}@<version>", name, name)
+        })?;
+    let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if !output.status.success() || version.is_empty() {
+        anyhow::bail!(
+            "Could not find the latest version of {}: {}",
+            name,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(version)
+}
+
+/// The range to upgrade `current` to, keeping its `^` or `~` prefix. Ranges
+/// that are not a plain version (tags, URLs, `workspace:*`) are not upgraded.
+pub fn upgraded_range(current: &str, latest: &str) -> Option<String> {
+    let prefix = current
+        .chars()
+        .next()
+        .filter(|c| *c == '^' || *c == '~')
+        .map(String::from)
+        .unwrap_or_default();
+    let version = &current[prefix.len()..];
+    let plain = !version.is_empty()
+        && version
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-' || c == '+');
+    let first = version.chars().next().is_some_and(|c| c.is_ascii_digit());
+    (plain && first).then(|| format!("{}{}", prefix, latest))
+}
+
+/// Node built-in modules, which never belong in package.json.
+const NODE_BUILTINS: &[&str] = &[
+    "assert", "buffer", "child_process", "crypto", "events", "fs", "http", "https", "net",
+    "os", "path", "process", "querystring", "readline", "stream", "url", "util", "worker_threads",
+    "zlib",
+];
+
+/// Packages imported or required by JavaScript or TypeScript source, in
+/// order of first use. Relative paths, aliases and Node built-ins are skipped.
+pub fn imported_packages(source: &str) -> Vec<String> {
+    let mut packages: Vec<String> = Vec::new();
+    for keyword in ["from ", "import ", "require(", "import("] {
+        for (at, _) in source.match_indices(keyword) {
+            let rest = source[at + keyword.len()..].trim_start();
+            let Some(quote) = rest.chars().next().filter(|c| matches!(c, '"' | '\'' | '`')) else {
+                continue;
+            };
+            let Some(end) = rest[1..].find(quote) else {
+                continue;
+            };
+            if let Some(package) = package_name(&rest[1..end + 1]) {
+                if !packages.contains(&package) {
+                    packages.push(package);
+                }
+            }
+        }
+    }
+    packages
+}
+
+fn package_name(specifier: &str) -> Option<String> {
+    if specifier.is_empty()
+        || specifier.starts_with(['.', '/', '~', '$', '#'])
+        || specifier.starts_with("@/")
+        || specifier.contains(':')
+    {
+        return None;
+    }
+    let mut segments = specifier.split('/');
+    let name = if specifier.starts_with('@') {
+        format!("{}/{}", segments.next()?, segments.next()?)
+    } else {
+        segments.next()?.to_string()
+    };
+    (!NODE_BUILTINS.contains(&name.as_str())).then_some(name)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PackageManager {
+    Npm,
+    Pnpm,
+    Yarn,
+    Bun,
+}
+
+const LOCKFILES: &[(&str, PackageManager)] = &[
+    ("pnpm-lock.yaml", PackageManager::Pnpm),
+    ("yarn.lock", PackageManager::Yarn),
+    ("bun.lockb", PackageManager::Bun),
+    ("bun.lock", PackageManager::Bun),
+    ("package-lock.json", PackageManager::Npm),
+];
+
+impl PackageManager {
+    /// Uses the nearest lockfile in `root` or a parent directory (workspaces
+    /// usually keep one at the top), then the `packageManager` field, and
+    /// falls back to npm.
+    pub fn detect(root: &Path) -> Self {
+        let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+        for dir in root.ancestors() {
+            if let Some((_, manager)) = LOCKFILES.iter().find(|(file, _)| dir.join(file).is_file()) {
+                return *manager;
+            }
+        }
+        PackageJson::read(&root)
+            .ok()
+            .flatten()
+            .and_then(|package| package.package_manager().and_then(Self::from_field))
+            .unwrap_or(PackageManager::Npm)
+    }
+
+    fn from_field(field: &str) -> Option<Self> {
+        match field.split('@').next()? {
+            "npm" => Some(PackageManager::Npm),
+            "pnpm" => Some(PackageManager::Pnpm),
+            "yarn" => Some(PackageManager::Yarn),
+            "bun" => Some(PackageManager::Bun),
+            _ => None,
+        }
+    }
+
+    pub fn command(self) -> &'static str {
+        match self {
+            PackageManager::Npm => "npm",
+            PackageManager::Pnpm => "pnpm",
+            PackageManager::Yarn => "yarn",
+            PackageManager::Bun => "bun",
+        }
+    }
+
+    pub fn install_hint(self) -> String {
+        format!("{} install", self.command())
+    }
+
+    /// Runs `<manager> install` in `root`, streaming its output.
+    pub fn install(self, root: &Path) -> Result<()> {
+        println!("\n📦 Running {} in {}", self.install_hint().cyan(), root.display());
+        let status = Command::new(self.command())
+            .arg("install")
+            .current_dir(root)
+            .status()
+            .with_context(|| {
+                format!(
+                    "Failed to run {}. Is it installed and on PATH?",
+                    self.command()
+                )
+            })?;
+        if !status.success() {
+            anyhow::bail!("{} failed with {}", self.install_hint(), status);
+        }
+        Ok(())
+    }
+}
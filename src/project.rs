@@ -1,104 +1,326 @@
-use crate::types::ComponentType;
+use crate::api;
+use crate::components;
+use crate::naming::ComponentName;
+use crate::package_json::{PackageJson, PACKAGE_JSON};
+use crate::routes::Router;
+use crate::schema::ProjectConfig;
+use crate::templates::{canonical_framework, ProjectTemplate, TemplateVars};
+use crate::types::{ComponentOptions, ComponentType};
+use crate::workspace;
+use crate::writer::{ConflictPolicy, FileWriter, WriteOptions};
 use anyhow::{Context, Result};
-use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct ProjectConfig {
-    pub name: String,
-    pub template: String,
-    pub framework: String,
-}
+pub const CONFIG_FILE: &str = "nexus.yaml";
 
 #[derive(Debug, Clone)]
 pub struct Project {
     pub root: PathBuf,
     pub config: ProjectConfig,
+    pub writer: FileWriter,
+    /// Root of the enclosing workspace, if the project belongs to one.
+    pub workspace_root: Option<PathBuf>,
 }
 
 pub struct GeneratedComponent {
-    pub path: PathBuf,
+    /// Every file written, including the barrel when it changed.
+    pub files: Vec<PathBuf>,
+    /// Requested companion flags that do not apply to the component type.
+    pub skipped: Vec<&'static str>,
+    /// Where a page or layout was registered with the router.
+    pub route: Option<String>,
+    /// An API component's endpoint, and how to mount its handler when
+    /// that could not be done automatically.
+    pub endpoint: Option<(String, Option<String>)>,
 }
 
 impl Project {
-    pub fn new(name: &str, template: &str, framework: &str) -> Result<Self> {
+    pub fn new(
+        name: &str,
+        template: &str,
+        framework: &str,
+        variables: &BTreeMap<String, String>,
+    ) -> Result<Self> {
+        validate_name(name)?;
+        let framework = canonical_framework(framework)?;
+        let variables = ProjectTemplate::find(template)?.resolve_variables(framework, variables)?;
         Ok(Self {
             root: PathBuf::from(name),
-            config: ProjectConfig {
-                name: name.to_string(),
-                template: template.to_string(),
-                framework: framework.to_string(),
-            },
+            writer: FileWriter::new(name, WriteOptions::default()),
+            config: ProjectConfig::new(name, template, framework, variables),
+            workspace_root: None,
         })
     }
 
+    /// Loads the project containing `start`, which may be any directory
+    /// inside it or its `nexus.yaml`.
+    pub fn discover(start: impl AsRef<Path>) -> Result<Self> {
+        let start = start.as_ref();
+        let root = find_root(start).with_context(|| {
+            format!(
+                "Not in a Nexus project: no {} in {} or any parent directory. Run 'nexus new' first.",
+                CONFIG_FILE,
+                start.display()
+            )
+        })?;
+        Self::load(root)
+    }
+
     pub fn load(path: impl AsRef<Path>) -> Result<Self> {
         let root = path.as_ref().to_path_buf();
-        let config_path = root.join("nexus.yaml");
+        let config_path = root.join(CONFIG_FILE);
         let contents = std::fs::read_to_string(&config_path)
             .with_context(|| format!("Missing config at {}", config_path.display()))?;
-        let config: ProjectConfig = serde_yaml::from_str(&contents)
-            .with_context(|| format!("Invalid config at {}", config_path.display()))?;
-        Ok(Self { root, config })
+        let config = ProjectConfig::parse(&contents, &config_path)?;
+        Ok(Self {
+            writer: FileWriter::new(&root, WriteOptions::default()),
+            workspace_root: workspace::find_root(&root),
+            root,
+            config,
+        })
+    }
+
+    /// Routes every file this project writes through the given options.
+    pub fn with_write_options(mut self, options: WriteOptions) -> Self {
+        self.writer = FileWriter::new(&self.root, options);
+        self
     }
 
     pub fn create_structure(&self) -> Result<()> {
-        std::fs::create_dir_all(self.root.join("src"))?;
-        std::fs::create_dir_all(self.root.join("dist"))?;
-        std::fs::create_dir_all(self.root.join("templates"))?;
+        self.writer.create_dir("src")?;
+        self.writer.create_dir("dist")?;
+        self.writer.create_dir("templates")?;
         Ok(())
     }
 
     pub fn generate_files(&self) -> Result<()> {
-        let config_path = self.root.join("nexus.yaml");
         let config_contents = serde_yaml::to_string(&self.config)?;
-        std::fs::write(&config_path, config_contents)?;
-        self.write_file(
-            "README.md",
-            &format!(
-                "# {}\n\nGenerated with Nexus Studio AI.\n",
-                self.config.name
-            ),
-        )?;
-        self.write_file(
-            "src/App.js",
-            "export default function App() {\n  return <h1>Nexus Studio AI</h1>;\n}\n",
-        )?;
+        self.write_file(CONFIG_FILE, &config_contents)?;
+
+        let vars = TemplateVars::from_config(&self.config);
+        let template = ProjectTemplate::find(&self.config.template)?;
+
+        // The template is rendered over the framework starter pack, so a
+        // template file replaces the pack file at the same path.
+        let mut files = BTreeMap::new();
+        if let Some(pack) = ProjectTemplate::framework_pack(&self.config.framework) {
+            for file in pack.render(&vars)? {
+                files.insert(file.path, file.contents);
+            }
+        }
+        for file in template.render(&vars)? {
+            files.insert(file.path, file.contents);
+        }
+
+        // Dependencies and scripts the template declares are merged into the
+        // pack's package.json, or a new one when the pack has none.
+        let mut package = match files.get(PACKAGE_JSON) {
+            Some(contents) => PackageJson::parse(
+                std::str::from_utf8(contents).with_context(|| format!("{} is not UTF-8 text", PACKAGE_JSON))?,
+            )?,
+            None => PackageJson::new(&self.config.name),
+        };
+        let manifest = &template.manifest;
+        if !package
+            .declare(&self.config.framework, &manifest.dependencies, &manifest.scripts)?
+            .is_empty()
+        {
+            files.insert(PACKAGE_JSON.to_string(), package.render()?.into_bytes());
+        }
+
+        for (path, contents) in &files {
+            self.writer.write_bytes(path, contents)?;
+        }
         Ok(())
     }
 
     pub fn write_file(&self, relative_path: &str, contents: &str) -> Result<()> {
-        let path = self.root.join(relative_path);
-        if let Some(parent) = path.parent() {
-            std::fs::create_dir_all(parent)?;
-        }
-        std::fs::write(path, contents)?;
+        self.writer.write(relative_path, contents)?;
         Ok(())
     }
 
+    /// Writes a component and its companion files, adds it to the folder's
+    /// barrel when asked to, and registers pages and layouts with the router.
+    /// An existing component with the same name is only replaced with
+    /// `--on-conflict overwrite` or `merge-markers`. Router problems are
+    /// reported before anything is written.
     pub fn generate_component(
         &self,
-        template: &str,
-        name: &str,
+        kind: &ComponentType,
+        name: &ComponentName,
         framework: &str,
+        options: &ComponentOptions,
     ) -> Result<GeneratedComponent> {
-        let extension = match framework {
-            "react" | "nextjs" => "tsx",
-            "vue" => "vue",
-            "svelte" => "svelte",
-            "angular" => "component.ts",
-            _ => "jsx",
+        let routable = matches!(kind, ComponentType::Page | ComponentType::Layout);
+        let router = if routable && !options.no_route {
+            Router::find(&self.root, framework)?
+        } else {
+            None
         };
-        let filename = format!("{}.{}", name, extension);
-        let path = self.root.join("src/components").join(filename);
-        std::fs::create_dir_all(path.parent().unwrap())?;
-        let contents = format!(
-            "// Generated {} component\n\nexport default function {}() {{\n  return <div>{}</div>;\n}}\n",
-            template, name, name
+        let mut skipped = Vec::new();
+        if !matches!(kind, ComponentType::Page) {
+            if options.route.is_some() {
+                skipped.push("--route");
+            }
+            if options.layout.is_some() {
+                skipped.push("--layout");
+            }
+        }
+
+        let routed = router.as_ref().is_some_and(Router::uses_outlet);
+        let component = components::render_component(kind, name, framework, options, routed)?;
+        let replacing = matches!(
+            self.writer.conflict(),
+            ConflictPolicy::Overwrite | ConflictPolicy::MergeMarkers
+        );
+        if let (Some(main), false) = (component.files.first(), replacing) {
+            if let Some(existing) = self.find_case_insensitive(&main.path) {
+                anyhow::bail!(
+                    "{} already exists. Choose another name, or pass --on-conflict overwrite to regenerate it",
+                    existing.display()
+                );
+            }
+        }
+
+        let route = match (&router, component.files.first()) {
+            (Some(router), Some(main)) => Some(router.plan(
+                &self.root,
+                kind,
+                name,
+                &main.path,
+                options.route.as_deref(),
+                options.layout.as_deref(),
+            )?),
+            _ => None,
+        };
+
+        let api = match (kind, component.files.as_slice()) {
+            (ComponentType::Api, [client, schema, ..]) => Some(api::plan(
+                &self.root,
+                &self.config,
+                name,
+                &client.path,
+                &schema.path,
+            )?),
+            _ => None,
+        };
+
+        let mut files = Vec::new();
+        for file in &component.files {
+            self.write_file(&file.path, &file.contents)?;
+            files.push(self.root.join(&file.path));
+        }
+
+        if let Some((index, line)) = &component.barrel {
+            let mut contents = std::fs::read_to_string(self.root.join(index)).unwrap_or_default();
+            if !contents.lines().any(|existing| existing.trim() == line) {
+                if !contents.is_empty() && !contents.ends_with('\n') {
+                    contents.push('\n');
+                }
+                contents.push_str(line);
+                contents.push('\n');
+                // The barrel is edited in place; an existing one is expected.
+                self.writer
+                    .with_conflict(ConflictPolicy::Overwrite)
+                    .write(index, &contents)?;
+                files.push(self.root.join(index));
+            }
+        }
+
+        if let Some(route) = &route {
+            for file in &route.files {
+                self.write_file(&file.path, &file.contents)?;
+                files.push(self.root.join(&file.path));
+            }
+            if let Some(edit) = &route.edit {
+                self.writer
+                    .with_conflict(ConflictPolicy::Overwrite)
+                    .write(&edit.path, &edit.contents)?;
+                files.push(self.root.join(&edit.path));
+            }
+        }
+
+        if let Some(api) = &api {
+            for file in &api.files {
+                self.write_file(&file.path, &file.contents)?;
+                files.push(self.root.join(&file.path));
+            }
+            for edit in &api.edits {
+                self.writer
+                    .with_conflict(ConflictPolicy::Overwrite)
+                    .write(&edit.path, &edit.contents)?;
+                files.push(self.root.join(&edit.path));
+            }
+        }
+
+        skipped.extend(component.skipped);
+        Ok(GeneratedComponent {
+            files,
+            skipped,
+            route: route.map(|route| route.description),
+            endpoint: api.map(|api| (format!("{} ({})", api.base_path, api.server.name()), api.hint)),
+        })
+    }
+
+    /// The file at `relative`, ignoring case, so `button.tsx` finds `Button.tsx`
+    /// on case-sensitive filesystems too.
+    fn find_case_insensitive(&self, relative: &str) -> Option<PathBuf> {
+        let path = self.root.join(relative);
+        let file_name = path.file_name()?.to_string_lossy().to_lowercase();
+        std::fs::read_dir(path.parent()?)
+            .ok()?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .find(|candidate| {
+                candidate
+                    .file_name()
+                    .is_some_and(|name| name.to_string_lossy().to_lowercase() == file_name)
+            })
+    }
+}
+
+/// Walks up from `start` to the nearest directory containing `nexus.yaml`,
+/// the way cargo looks for `Cargo.toml`.
+pub fn find_root(start: &Path) -> Option<PathBuf> {
+    let start = start.canonicalize().ok()?;
+    let start = if start.is_file() { start.parent()?.to_path_buf() } else { start };
+    start
+        .ancestors()
+        .find(|dir| dir.join(CONFIG_FILE).is_file())
+        .map(Path::to_path_buf)
+}
+
+/// Project names become directory names and package names, so only a
+/// conservative character set is accepted, and no capitals, which npm does
+/// not allow in package names.
+pub fn validate_name(name: &str) -> Result<()> {
+    if name.trim().is_empty() {
+        anyhow::bail!("Project name cannot be empty");
+    }
+    if name.starts_with('.') || name.starts_with('-') {
+        anyhow::bail!("Project name cannot start with '.' or '-'");
+    }
+    if name.contains(|c: char| c.is_ascii_uppercase()) {
+        let suggestion = ComponentName::parse(name)
+            .map(|parsed| parsed.kebab())
+            .unwrap_or_else(|_| name.to_lowercase());
+        anyhow::bail!(
+            "Project name '{}' has capital letters, which npm does not allow in package names. Try '{}'",
+            name,
+            suggestion
+        );
+    }
+    if let Some(c) = name
+        .chars()
+        .find(|c| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')))
+    {
+        anyhow::bail!(
+            "Invalid character '{}' in project name: use letters, digits, '-', '_' and '.'",
+            c
         );
-        std::fs::write(&path, contents)?;
-        Ok(GeneratedComponent { path })
     }
+    Ok(())
 }
 
 impl ComponentType {
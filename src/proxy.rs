@@ -0,0 +1,283 @@
+use crate::schema::ProxyRule;
+use anyhow::{Context, Result};
+use bytes::Bytes;
+use futures::{SinkExt, StreamExt};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use tokio_tungstenite::tungstenite;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+use warp::filters::BoxedFilter;
+use warp::http::{HeaderMap, HeaderName, HeaderValue, Method, StatusCode};
+use warp::path::FullPath;
+use warp::reply::Response;
+use warp::ws::{WebSocket, Ws};
+use warp::{Filter, Rejection, Reply};
+
+/// Headers about one connection, which are not passed on.
+const HOP_BY_HOP: &[&str] = &[
+    "connection",
+    "keep-alive",
+    "proxy-authenticate",
+    "proxy-authorization",
+    "te",
+    "trailer",
+    "transfer-encoding",
+    "upgrade",
+    "host",
+];
+
+/// Set again by the WebSocket handshake with the upstream.
+const WEBSOCKET_HANDSHAKE: &[&str] = &[
+    "sec-websocket-key",
+    "sec-websocket-version",
+    "sec-websocket-extensions",
+    "sec-websocket-accept",
+];
+
+/// Paths the dev server answers itself, such as live reload.
+const RESERVED_PREFIX: &str = "/__nexus/";
+
+struct Route {
+    /// Without a trailing `/`; empty for a rule on `/`.
+    prefix: String,
+    rule: ProxyRule,
+    headers: HeaderMap,
+}
+
+#[derive(Clone)]
+struct Proxy {
+    /// Longest prefix first, so `/api/v2` wins over `/api`.
+    routes: Arc<Vec<Route>>,
+    client: reqwest::Client,
+}
+
+/// Forwards requests matching the `serve.proxy` rules, WebSocket upgrades
+/// included, and rejects the rest so the site is served. `None` without
+/// rules.
+pub fn routes(rules: &BTreeMap<String, ProxyRule>) -> Result<Option<BoxedFilter<(Response,)>>> {
+    if rules.is_empty() {
+        return Ok(None);
+    }
+    let proxy = Proxy::new(rules)?;
+    let with_proxy = warp::any().map(move || proxy.clone());
+    let query = warp::query::raw().or(warp::any().map(String::new)).unify();
+
+    // The route is matched before `warp::ws()`, whose rejection for plain
+    // requests would otherwise turn the site's 404s into 400s.
+    let sockets = warp::path::full()
+        .and(query)
+        .and(warp::header::headers_cloned())
+        .and(with_proxy.clone())
+        .and_then(|path: FullPath, query: String, headers: HeaderMap, proxy: Proxy| async move {
+            let Some(route) = proxy.find(path.as_str()).filter(|route| route.rule.ws) else {
+                return Err(warp::reject::not_found());
+            };
+            // http:// becomes ws:// and https:// becomes wss://.
+            let url = route.url(path.as_str(), &query).replacen("http", "ws", 1);
+            Ok::<_, Rejection>((url, route.forwarded_headers(&headers, WEBSOCKET_HANDSHAKE)))
+        })
+        .and(warp::ws())
+        .map(|(url, headers): (String, HeaderMap), ws: Ws| {
+            ws.on_upgrade(move |socket| bridge(socket, url, headers)).into_response()
+        });
+
+    let requests = warp::method()
+        .and(warp::path::full())
+        .and(query)
+        .and(warp::header::headers_cloned())
+        .and(warp::body::bytes())
+        .and(with_proxy)
+        .and_then(
+            |method: Method, path: FullPath, query: String, headers: HeaderMap, body: Bytes, proxy: Proxy| async move {
+                let Some(route) = proxy.find(path.as_str()) else {
+                    return Err(warp::reject::not_found());
+                };
+                let url = route.url(path.as_str(), &query);
+                let headers = route.forwarded_headers(&headers, &[]);
+                Ok::<_, Rejection>(proxy.forward(method, url, headers, body).await)
+            },
+        );
+
+    Ok(Some(sockets.or(requests).unify().boxed()))
+}
+
+impl Proxy {
+    fn new(rules: &BTreeMap<String, ProxyRule>) -> Result<Self> {
+        let mut routes = Vec::new();
+        for (prefix, rule) in rules {
+            if !prefix.starts_with('/') {
+                anyhow::bail!("Proxy prefix '{}' in nexus.yaml must start with /", prefix);
+            }
+            let mut headers = HeaderMap::new();
+            for (name, value) in &rule.headers {
+                let name = HeaderName::from_bytes(name.as_bytes())
+                    .with_context(|| format!("Invalid header name '{}' for proxy {}", name, prefix))?;
+                let value = HeaderValue::from_str(value)
+                    .with_context(|| format!("Invalid value for header {} of proxy {}", name, prefix))?;
+                headers.insert(name, value);
+            }
+            routes.push(Route {
+                prefix: prefix.trim_end_matches('/').to_string(),
+                rule: rule.clone(),
+                headers,
+            });
+        }
+        routes.sort_by_key(|route| std::cmp::Reverse(route.prefix.len()));
+        // Redirects go back to the browser, as they would without the proxy.
+        let client = reqwest::Client::builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .context("Failed to create the proxy client")?;
+        Ok(Self {
+            routes: Arc::new(routes),
+            client,
+        })
+    }
+
+    /// The rule for `path`. `/api` matches `/api` and `/api/users`, not `/apis`.
+    fn find(&self, path: &str) -> Option<&Route> {
+        if path.starts_with(RESERVED_PREFIX) {
+            return None;
+        }
+        self.routes.iter().find(|route| {
+            path.strip_prefix(&route.prefix)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+        })
+    }
+
+    async fn forward(&self, method: Method, url: String, headers: HeaderMap, body: Bytes) -> Response {
+        let mut upstream = match self.client.request(method, &url).headers(headers).body(body).send().await {
+            Ok(upstream) => upstream,
+            Err(e) => {
+                let e = e.without_url();
+                println!("⚠️  Proxy could not reach {}: {}", url, e);
+                let mut response = Response::new(format!("nexus serve could not reach {}: {}", url, e).into());
+                *response.status_mut() = StatusCode::BAD_GATEWAY;
+                return response;
+            }
+        };
+
+        // Streamed, so server-sent events arrive as they are sent.
+        let (mut sender, body) = warp::hyper::Body::channel();
+        let mut response = Response::new(body);
+        *response.status_mut() = upstream.status();
+        for (name, value) in upstream.headers() {
+            if !HOP_BY_HOP.contains(&name.as_str()) {
+                response.headers_mut().append(name, value.clone());
+            }
+        }
+        tokio::spawn(async move {
+            while let Ok(Some(chunk)) = upstream.chunk().await {
+                if sender.send_data(chunk).await.is_err() {
+                    break;
+                }
+            }
+        });
+        response
+    }
+}
+
+impl Route {
+    /// The upstream URL for a request, with the prefix rewritten.
+    fn url(&self, path: &str, query: &str) -> String {
+        let path = match &self.rule.rewrite {
+            Some(rewrite) => format!("{}{}", rewrite, &path[self.prefix.len()..]),
+            None => path.to_string(),
+        };
+        let mut url = format!("{}/{}", self.rule.target, path.trim_start_matches('/'));
+        if !query.is_empty() {
+            url.push('?');
+            url.push_str(query);
+        }
+        url
+    }
+
+    /// The request's headers without connection headers and `skipped`, plus
+    /// `X-Forwarded-*` and the rule's own headers.
+    fn forwarded_headers(&self, incoming: &HeaderMap, skipped: &[&str]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in incoming {
+            if !HOP_BY_HOP.contains(&name.as_str()) && !skipped.contains(&name.as_str()) {
+                headers.append(name, value.clone());
+            }
+        }
+        if let Some(host) = incoming.get("host") {
+            headers.insert("x-forwarded-host", host.clone());
+        }
+        headers.insert("x-forwarded-proto", HeaderValue::from_static("http"));
+        for (name, value) in &self.headers {
+            headers.insert(name, value.clone());
+        }
+        headers
+    }
+}
+
+/// Connects to the upstream WebSocket and relays messages both ways until
+/// either side closes.
+async fn bridge(socket: WebSocket, url: String, headers: HeaderMap) {
+    let mut request = match url.as_str().into_client_request() {
+        Ok(request) => request,
+        Err(e) => {
+            println!("⚠️  Proxy could not connect to {}: {}", url, e);
+            return;
+        }
+    };
+    // tungstenite uses a newer `http` than warp, so headers are copied over
+    // by name.
+    for (name, value) in &headers {
+        if let (Ok(name), Ok(value)) = (
+            tungstenite::http::HeaderName::from_bytes(name.as_str().as_bytes()),
+            tungstenite::http::HeaderValue::from_bytes(value.as_bytes()),
+        ) {
+            request.headers_mut().insert(name, value);
+        }
+    }
+    let upstream = match tokio_tungstenite::connect_async(request).await {
+        Ok((upstream, _)) => upstream,
+        Err(e) => {
+            println!("⚠️  Proxy could not connect to {}: {}", url, e);
+            let _ = socket.close().await;
+            return;
+        }
+    };
+
+    let (mut client_tx, mut client_rx) = socket.split();
+    let (mut upstream_tx, mut upstream_rx) = upstream.split();
+    // Pings are answered by each side's own connection, so only data and
+    // close messages are relayed.
+    let to_upstream = async {
+        while let Some(Ok(message)) = client_rx.next().await {
+            let message = if message.is_text() {
+                tungstenite::Message::Text(message.to_str().unwrap_or_default().to_string())
+            } else if message.is_binary() {
+                tungstenite::Message::Binary(message.as_bytes().to_vec())
+            } else if message.is_close() {
+                break;
+            } else {
+                continue;
+            };
+            if upstream_tx.send(message).await.is_err() {
+                break;
+            }
+        }
+        let _ = upstream_tx.close().await;
+    };
+    let to_client = async {
+        while let Some(Ok(message)) = upstream_rx.next().await {
+            let message = match message {
+                tungstenite::Message::Text(text) => warp::ws::Message::text(text),
+                tungstenite::Message::Binary(bytes) => warp::ws::Message::binary(bytes),
+                tungstenite::Message::Close(_) => break,
+                _ => continue,
+            };
+            if client_tx.send(message).await.is_err() {
+                break;
+            }
+        }
+        let _ = client_tx.close().await;
+    };
+    tokio::select! {
+        _ = to_upstream => {}
+        _ = to_client => {}
+    }
+}
@@ -0,0 +1,185 @@
+use crate::cache::content_hash;
+use crate::manifest::{route_of, MANIFEST_FILE};
+use anyhow::{Context, Result};
+use serde_json::json;
+use std::path::Path;
+use walkdir::WalkDir;
+
+pub const SERVICE_WORKER: &str = "sw.js";
+pub const WEB_MANIFEST: &str = "manifest.webmanifest";
+
+/// Service workers and manifests a project may already ship, for example from
+/// `nexus add pwa`. These are kept as they are.
+const EXISTING_WORKERS: &[&str] = &["sw.js", "service-worker.js"];
+const EXISTING_MANIFESTS: &[&str] = &["manifest.webmanifest", "manifest.json"];
+
+/// Default `theme_color`, the same as the `pwa` recipe's.
+const THEME_COLOR: &str = "#4f46e5";
+
+/// Turns the web build in `output` into a PWA: writes a web app manifest and a
+/// service worker that precaches every emitted file, unless the project ships
+/// its own, and links both from each HTML page. Returns warnings.
+pub fn generate(output: &Path, name: &str) -> Result<Vec<String>> {
+    let mut warnings = Vec::new();
+    let mut files = Vec::new();
+    for entry in WalkDir::new(output) {
+        let entry = entry?;
+        if entry.file_type().is_file() {
+            files.push(entry.path().strip_prefix(output)?.to_string_lossy().replace('\\', "/"));
+        }
+    }
+    files.sort();
+    if !files.iter().any(|file| file == "index.html") {
+        anyhow::bail!(
+            "The pwa target needs an index.html in {}, but the build produced none",
+            output.display()
+        );
+    }
+
+    let manifest = match EXISTING_MANIFESTS.iter().find(|file| files.iter().any(|f| f == *file)) {
+        Some(existing) => {
+            warnings.push(format!("Kept the project's {}", existing));
+            existing.to_string()
+        }
+        None => {
+            let icons: Vec<_> = files
+                .iter()
+                .filter(|file| {
+                    let lower = file.to_lowercase();
+                    (lower.contains("icon") || lower.contains("logo"))
+                        && (lower.ends_with(".png") || lower.ends_with(".svg"))
+                })
+                .map(|file| {
+                    let kind = if file.ends_with(".svg") { "image/svg+xml" } else { "image/png" };
+                    json!({ "src": format!("/{}", file), "type": kind, "sizes": "any" })
+                })
+                .collect();
+            if icons.is_empty() {
+                warnings.push("No icon files (icon*.png, logo*.svg) found; the app cannot be installed without one".to_string());
+            }
+            let web_manifest = json!({
+                "name": name,
+                "short_name": name,
+                "start_url": "/",
+                "display": "standalone",
+                "background_color": "#ffffff",
+                "theme_color": THEME_COLOR,
+                "icons": icons,
+            });
+            write(output, WEB_MANIFEST, &serde_json::to_string_pretty(&web_manifest)?)?;
+            files.push(WEB_MANIFEST.to_string());
+            WEB_MANIFEST.to_string()
+        }
+    };
+
+    // A project that ships its worker usually registers it from a script,
+    // as the `pwa` recipe does; pages then need no registration of their own.
+    let mut registered = false;
+    let worker = match EXISTING_WORKERS.iter().find(|file| files.iter().any(|f| f == *file)) {
+        Some(existing) => {
+            warnings.push(format!("Kept the project's {}", existing));
+            let scripts = files
+                .iter()
+                .filter(|file| file.ends_with(".js") && !EXISTING_WORKERS.contains(&file.as_str()));
+            for script in scripts {
+                let contents = std::fs::read_to_string(output.join(script)).unwrap_or_default();
+                registered |= contents.contains("serviceWorker");
+            }
+            existing.to_string()
+        }
+        None => {
+            let precache: Vec<String> = files
+                .iter()
+                .filter(|file| file.as_str() != MANIFEST_FILE)
+                .map(|file| route_of(file))
+                .collect();
+            // The cache name changes with any file, so a new build replaces
+            // the old cache.
+            let mut fingerprint = Vec::new();
+            for file in &files {
+                fingerprint.extend(content_hash(&std::fs::read(output.join(file))?).into_bytes());
+            }
+            let script = SERVICE_WORKER_SCRIPT
+                .replace("__CACHE__", &format!("nexus-{}", &content_hash(&fingerprint)[..8]))
+                .replace("__PRECACHE__", &serde_json::to_string_pretty(&precache)?);
+            write(output, SERVICE_WORKER, &script)?;
+            SERVICE_WORKER.to_string()
+        }
+    };
+
+    for page in files.iter().filter(|file| file.ends_with(".html")) {
+        let path = output.join(page);
+        let contents = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        let linked = link(&contents, &manifest, (!registered).then_some(worker.as_str()));
+        if linked != contents {
+            std::fs::write(&path, linked).with_context(|| format!("Failed to write {}", path.display()))?;
+        }
+    }
+    Ok(warnings)
+}
+
+/// Adds the manifest link and, given a `worker`, its registration to a page
+/// that does not have them.
+fn link(html: &str, manifest: &str, worker: Option<&str>) -> String {
+    let mut html = html.to_string();
+    if !html.contains("rel=\"manifest\"") && !html.contains("rel=manifest") {
+        let tags = format!(
+            "<link rel=\"manifest\" href=\"/{}\"><meta name=\"theme-color\" content=\"{}\">",
+            manifest, THEME_COLOR
+        );
+        insert_before(&mut html, "</head>", &tags);
+    }
+    if let Some(worker) = worker.filter(|_| !html.contains("serviceWorker")) {
+        let script = format!(
+            "<script>if(\"serviceWorker\"in navigator)addEventListener(\"load\",()=>navigator.serviceWorker.register(\"/{}\"))</script>",
+            worker
+        );
+        insert_before(&mut html, "</body>", &script);
+    }
+    html
+}
+
+/// Inserts `text` before the last `tag`, or at the end without one.
+fn insert_before(html: &mut String, tag: &str, text: &str) {
+    let index = html.rfind(tag).unwrap_or(html.len());
+    html.insert_str(index, text);
+}
+
+fn write(output: &Path, file: &str, contents: &str) -> Result<()> {
+    std::fs::write(output.join(file), contents)
+        .with_context(|| format!("Failed to write {}", output.join(file).display()))
+}
+
+const SERVICE_WORKER_SCRIPT: &str = r#"// Generated by nexus build --target pwa.
+const CACHE = "__CACHE__";
+const PRECACHE = __PRECACHE__;
+
+self.addEventListener("install", (event) => {
+  event.waitUntil(caches.open(CACHE).then((cache) => cache.addAll(PRECACHE)));
+  self.skipWaiting();
+});
+
+self.addEventListener("activate", (event) => {
+  event.waitUntil(
+    caches
+      .keys()
+      .then((keys) => Promise.all(keys.filter((key) => key !== CACHE).map((key) => caches.delete(key))))
+      .then(() => self.clients.claim())
+  );
+});
+
+self.addEventListener("fetch", (event) => {
+  const request = event.request;
+  if (request.method !== "GET" || new URL(request.url).origin !== location.origin) return;
+  event.respondWith(
+    caches.match(request).then(
+      (cached) =>
+        cached ||
+        fetch(request).catch(() =>
+          request.mode === "navigate" ? caches.match("/") : Response.error()
+        )
+    )
+  );
+});
+"#;
@@ -0,0 +1,313 @@
+use crate::package_json::{Declared, PackageJson, PACKAGE_JSON};
+use crate::project::{Project, CONFIG_FILE};
+use crate::templates::{render_text, BuiltinFile, BuiltinTemplate, ProjectTemplate, TemplateVars};
+use crate::writer::ConflictPolicy;
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+// Recipes use the template directory format: a `template.yaml` manifest plus
+// files, with `frameworks/<framework>/` overlays. Files are created like
+// template files; `patches`, `dependencies` and `scripts` edit the project.
+
+const TAILWIND_FILES: &[BuiltinFile] = &[
+    ("postcss.config.cjs", include_str!("../templates/recipes/tailwind/postcss.config.cjs")),
+    ("tailwind.config.cjs", include_str!("../templates/recipes/tailwind/tailwind.config.cjs")),
+    ("frameworks/nextjs/app/tailwind.css", include_str!("../templates/recipes/tailwind/frameworks/nextjs/app/tailwind.css")),
+    ("frameworks/react-ts/src/tailwind.css", include_str!("../templates/recipes/tailwind/frameworks/react-ts/src/tailwind.css")),
+    ("frameworks/react/src/tailwind.css", include_str!("../templates/recipes/tailwind/frameworks/react/src/tailwind.css")),
+    ("frameworks/svelte/src/routes/+layout.svelte", include_str!("../templates/recipes/tailwind/frameworks/svelte/src/routes/+layout.svelte")),
+    ("frameworks/svelte/src/tailwind.css", include_str!("../templates/recipes/tailwind/frameworks/svelte/src/tailwind.css")),
+    ("frameworks/vue/src/tailwind.css", include_str!("../templates/recipes/tailwind/frameworks/vue/src/tailwind.css")),
+];
+
+const ROUTER_FILES: &[BuiltinFile] = &[
+    ("frameworks/react-ts/src/router.tsx", include_str!("../templates/recipes/router/frameworks/react-ts/src/router.tsx")),
+    ("frameworks/react/src/router.jsx", include_str!("../templates/recipes/router/frameworks/react/src/router.jsx")),
+    ("frameworks/vue/src/router.ts", include_str!("../templates/recipes/router/frameworks/vue/src/router.ts")),
+    ("frameworks/vue/src/views/HomeView.vue", include_str!("../templates/recipes/router/frameworks/vue/src/views/HomeView.vue")),
+];
+
+const STATE_FILES: &[BuiltinFile] = &[
+    ("frameworks/nextjs/store/useAppStore.ts", include_str!("../templates/recipes/state/frameworks/nextjs/store/useAppStore.ts")),
+    ("frameworks/react-ts/src/store/useAppStore.ts", include_str!("../templates/recipes/state/frameworks/react-ts/src/store/useAppStore.ts")),
+    ("frameworks/react/src/store/useAppStore.js", include_str!("../templates/recipes/state/frameworks/react/src/store/useAppStore.js")),
+    ("frameworks/vue/src/stores/app.ts", include_str!("../templates/recipes/state/frameworks/vue/src/stores/app.ts")),
+];
+
+const TESTING_FILES: &[BuiltinFile] = &[
+    ("vitest.config.js", include_str!("../templates/recipes/testing/vitest.config.js")),
+    ("frameworks/react-ts/src/App.test.tsx", include_str!("../templates/recipes/testing/frameworks/react-ts/src/App.test.tsx")),
+    ("frameworks/react/src/App.test.jsx", include_str!("../templates/recipes/testing/frameworks/react/src/App.test.jsx")),
+    ("frameworks/svelte/src/lib/example.test.js", include_str!("../templates/recipes/testing/frameworks/svelte/src/lib/example.test.js")),
+    ("frameworks/vue/src/App.test.ts", include_str!("../templates/recipes/testing/frameworks/vue/src/App.test.ts")),
+];
+
+const LINT_FILES: &[BuiltinFile] = &[
+    (".prettierignore", include_str!("../templates/recipes/lint/.prettierignore")),
+    (".prettierrc.json", include_str!("../templates/recipes/lint/.prettierrc.json")),
+    ("eslint.config.mjs.tera", include_str!("../templates/recipes/lint/eslint.config.mjs.tera")),
+];
+
+const AUTH_FILES: &[BuiltinFile] = &[
+    ("frameworks/nextjs/app/auth-provider.tsx.tera", include_str!("../templates/recipes/auth/frameworks/nextjs/app/auth-provider.tsx.tera")),
+    ("frameworks/nextjs/app/login/page.tsx", include_str!("../templates/recipes/auth/frameworks/nextjs/app/login/page.tsx")),
+    ("frameworks/react-ts/src/auth/AuthContext.tsx.tera", include_str!("../templates/recipes/auth/frameworks/react-ts/src/auth/AuthContext.tsx.tera")),
+    ("frameworks/react-ts/src/auth/LoginForm.tsx", include_str!("../templates/recipes/auth/frameworks/react-ts/src/auth/LoginForm.tsx")),
+    ("frameworks/react/src/auth/AuthContext.jsx.tera", include_str!("../templates/recipes/auth/frameworks/react/src/auth/AuthContext.jsx.tera")),
+    ("frameworks/react/src/auth/LoginForm.jsx", include_str!("../templates/recipes/auth/frameworks/react/src/auth/LoginForm.jsx")),
+    ("frameworks/svelte/src/lib/auth.js.tera", include_str!("../templates/recipes/auth/frameworks/svelte/src/lib/auth.js.tera")),
+    ("frameworks/svelte/src/routes/login/+page.svelte", include_str!("../templates/recipes/auth/frameworks/svelte/src/routes/login/+page.svelte")),
+    ("frameworks/vue/src/components/LoginForm.vue", include_str!("../templates/recipes/auth/frameworks/vue/src/components/LoginForm.vue")),
+    ("frameworks/vue/src/composables/useAuth.ts.tera", include_str!("../templates/recipes/auth/frameworks/vue/src/composables/useAuth.ts.tera")),
+];
+
+const PWA_FILES: &[BuiltinFile] = &[
+    ("frameworks/html/src/manifest.webmanifest.tera", include_str!("../templates/recipes/pwa/frameworks/html/src/manifest.webmanifest.tera")),
+    ("frameworks/html/src/sw.js.tera", include_str!("../templates/recipes/pwa/frameworks/html/src/sw.js.tera")),
+    ("frameworks/react-ts/public/manifest.webmanifest.tera", include_str!("../templates/recipes/pwa/frameworks/react-ts/public/manifest.webmanifest.tera")),
+    ("frameworks/react-ts/public/sw.js.tera", include_str!("../templates/recipes/pwa/frameworks/react-ts/public/sw.js.tera")),
+    ("frameworks/react-ts/src/registerSW.ts", include_str!("../templates/recipes/pwa/frameworks/react-ts/src/registerSW.ts")),
+    ("frameworks/react/public/manifest.webmanifest.tera", include_str!("../templates/recipes/pwa/frameworks/react/public/manifest.webmanifest.tera")),
+    ("frameworks/react/public/sw.js.tera", include_str!("../templates/recipes/pwa/frameworks/react/public/sw.js.tera")),
+    ("frameworks/react/src/registerSW.js", include_str!("../templates/recipes/pwa/frameworks/react/src/registerSW.js")),
+    ("frameworks/svelte/src/service-worker.js.tera", include_str!("../templates/recipes/pwa/frameworks/svelte/src/service-worker.js.tera")),
+    ("frameworks/svelte/static/manifest.webmanifest.tera", include_str!("../templates/recipes/pwa/frameworks/svelte/static/manifest.webmanifest.tera")),
+    ("frameworks/vue/public/manifest.webmanifest.tera", include_str!("../templates/recipes/pwa/frameworks/vue/public/manifest.webmanifest.tera")),
+    ("frameworks/vue/public/sw.js.tera", include_str!("../templates/recipes/pwa/frameworks/vue/public/sw.js.tera")),
+    ("frameworks/vue/src/registerSW.ts", include_str!("../templates/recipes/pwa/frameworks/vue/src/registerSW.ts")),
+];
+
+pub const BUILTIN_RECIPES: &[BuiltinTemplate] = &[
+    BuiltinTemplate {
+        name: "tailwind",
+        manifest: include_str!("../templates/recipes/tailwind/template.yaml"),
+        files: TAILWIND_FILES,
+    },
+    BuiltinTemplate {
+        name: "router",
+        manifest: include_str!("../templates/recipes/router/template.yaml"),
+        files: ROUTER_FILES,
+    },
+    BuiltinTemplate {
+        name: "state",
+        manifest: include_str!("../templates/recipes/state/template.yaml"),
+        files: STATE_FILES,
+    },
+    BuiltinTemplate {
+        name: "testing",
+        manifest: include_str!("../templates/recipes/testing/template.yaml"),
+        files: TESTING_FILES,
+    },
+    BuiltinTemplate {
+        name: "lint",
+        manifest: include_str!("../templates/recipes/lint/template.yaml"),
+        files: LINT_FILES,
+    },
+    BuiltinTemplate {
+        name: "auth",
+        manifest: include_str!("../templates/recipes/auth/template.yaml"),
+        files: AUTH_FILES,
+    },
+    BuiltinTemplate {
+        name: "pwa",
+        manifest: include_str!("../templates/recipes/pwa/template.yaml"),
+        files: PWA_FILES,
+    },
+];
+
+/// Where a patch without an anchor inserts its text.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PatchPosition {
+    Start,
+    #[default]
+    End,
+}
+
+/// An idempotent edit to an existing project file. `insert` goes on its own
+/// line after or before the first line containing the anchor, replaces the
+/// `replace` text, or is added at `position`. A patch whose text is already in
+/// the file is skipped. `file` and `insert` are rendered with Tera.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FilePatch {
+    pub file: String,
+    /// Frameworks the patch applies to. Empty means all of them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub frameworks: Vec<String>,
+    pub insert: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub after: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub before: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub replace: Option<String>,
+    #[serde(default)]
+    pub position: PatchPosition,
+}
+
+impl FilePatch {
+    pub fn applies_to(&self, framework: &str) -> bool {
+        self.frameworks.is_empty() || self.frameworks.iter().any(|f| f == framework)
+    }
+
+    /// Returns the patched contents, or `None` when the patch is already applied.
+    fn apply(&self, contents: &str, insert: &str) -> Result<Option<String>> {
+        let text = insert.trim_end_matches('\n');
+        if contents.contains(text.trim()) {
+            return Ok(None);
+        }
+
+        if let Some(target) = &self.replace {
+            if !contents.contains(target.as_str()) {
+                anyhow::bail!("'{}' not found", target);
+            }
+            return Ok(Some(contents.replacen(target.as_str(), text, 1)));
+        }
+
+        let anchor = match (&self.after, &self.before) {
+            (Some(anchor), _) => Some((anchor.as_str(), true)),
+            (None, Some(anchor)) => Some((anchor.as_str(), false)),
+            (None, None) => None,
+        };
+
+        let Some((anchor, after)) = anchor else {
+            return Ok(Some(match self.position {
+                PatchPosition::Start => format!("{}\n{}", text, contents),
+                PatchPosition::End => {
+                    let mut patched = contents.to_string();
+                    if !patched.is_empty() && !patched.ends_with('\n') {
+                        patched.push('\n');
+                    }
+                    patched.push_str(text);
+                    patched.push('\n');
+                    patched
+                }
+            }));
+        };
+
+        let mut offset = 0;
+        for line in contents.split_inclusive('\n') {
+            if line.contains(anchor) {
+                let at = if after { offset + line.len() } else { offset };
+                let mut patched = String::with_capacity(contents.len() + text.len() + 2);
+                patched.push_str(&contents[..at]);
+                if after && !line.ends_with('\n') {
+                    patched.push('\n');
+                }
+                patched.push_str(text);
+                patched.push('\n');
+                patched.push_str(&contents[at..]);
+                return Ok(Some(patched));
+            }
+            offset += line.len();
+        }
+        anyhow::bail!("'{}' not found", anchor)
+    }
+}
+
+/// What applying a recipe changed besides creating its files.
+#[derive(Debug, Default)]
+pub struct RecipeChanges {
+    pub patched_files: Vec<String>,
+    pub package: Declared,
+    /// False when nexus.yaml already listed the recipe.
+    pub newly_recorded: bool,
+}
+
+/// Resolves a recipe: built-in recipes first, then anything `nexus new -t`
+/// accepts (a template directory path or an installed template).
+pub fn find(name: &str) -> Result<ProjectTemplate> {
+    if let Some(builtin) = BUILTIN_RECIPES.iter().find(|recipe| recipe.name == name) {
+        return ProjectTemplate::from_builtin(builtin);
+    }
+    ProjectTemplate::find(name).with_context(|| {
+        let names: Vec<&str> = BUILTIN_RECIPES.iter().map(|recipe| recipe.name).collect();
+        format!("Unknown recipe '{}'. Built-in recipes: {}", name, names.join(", "))
+    })
+}
+
+pub fn builtin() -> Result<Vec<ProjectTemplate>> {
+    BUILTIN_RECIPES.iter().map(ProjectTemplate::from_builtin).collect()
+}
+
+/// Applies a recipe to the project through its writer. Patches are checked
+/// before anything is written, so a patch that cannot be applied leaves the
+/// project untouched.
+pub fn apply(
+    project: &mut Project,
+    recipe: &ProjectTemplate,
+    supplied: &BTreeMap<String, String>,
+) -> Result<RecipeChanges> {
+    let manifest = &recipe.manifest;
+    let framework = project.config.framework.clone();
+    if !manifest.supports(&framework) {
+        anyhow::bail!(
+            "Recipe '{}' does not support framework '{}'. Supported: {}",
+            manifest.name,
+            framework,
+            manifest.frameworks.join(", ")
+        );
+    }
+    let variables = recipe.resolve_variables(&framework, supplied)?;
+    let mut vars = TemplateVars::from_config(&project.config);
+    vars.extra.extend(variables);
+
+    let mut changes = RecipeChanges::default();
+
+    let mut patched: BTreeMap<String, String> = BTreeMap::new();
+    for patch in manifest.patches.iter().filter(|patch| patch.applies_to(&framework)) {
+        let file = render_text(&patch.file, &vars)?;
+        let insert = render_text(&patch.insert, &vars)?;
+        let contents = match patched.get(&file) {
+            Some(contents) => contents.clone(),
+            None => {
+                let path = project.root.join(&file);
+                std::fs::read_to_string(&path).with_context(|| {
+                    format!("Recipe '{}' patches {}, which does not exist", manifest.name, file)
+                })?
+            }
+        };
+        let result = patch.apply(&contents, &insert).with_context(|| {
+            format!(
+                "Cannot patch {}. It may have been edited by hand; apply the '{}' recipe manually",
+                file, manifest.name
+            )
+        })?;
+        if let Some(contents) = result {
+            patched.insert(file, contents);
+        }
+    }
+
+    let mut package = PackageJson::read(&project.root)?
+        .unwrap_or_else(|| PackageJson::new(&project.config.name));
+    changes.package = package.declare(&framework, &manifest.dependencies, &manifest.scripts)?;
+    let package_json = if changes.package.is_empty() {
+        None
+    } else {
+        Some(package.render()?)
+    };
+
+    for file in recipe.render(&vars)? {
+        project.writer.write_bytes(&file.path, &file.contents)?;
+    }
+
+    // Patches and config edits were asked for explicitly; they never prompt.
+    let editor = project.writer.with_conflict(ConflictPolicy::Overwrite);
+    for (file, contents) in &patched {
+        editor.write(file, contents)?;
+        changes.patched_files.push(file.clone());
+    }
+    if let Some(contents) = package_json {
+        editor.write(PACKAGE_JSON, &contents)?;
+    }
+
+    if !project.config.recipes.contains(&manifest.name) {
+        project.config.recipes.push(manifest.name.clone());
+        editor.write(CONFIG_FILE, &serde_yaml::to_string(&project.config)?)?;
+        changes.newly_recorded = true;
+    }
+
+    Ok(changes)
+}
@@ -0,0 +1,256 @@
+use crate::config::data_dir;
+use crate::templates::{ProjectTemplate, TemplateManifest, BUILTIN_TEMPLATES, MANIFEST_FILE};
+use anyhow::{Context, Result};
+use flate2::read::GzDecoder;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use walkdir::WalkDir;
+
+/// Where a template is installed from.
+#[derive(Debug, Clone)]
+pub enum InstallSource {
+    /// A plain directory containing a `template.yaml`.
+    Directory(PathBuf),
+    /// A local git checkout; only tracked and non-ignored files are installed.
+    Git(PathBuf),
+    /// A `.tar.gz`, `.tgz` or `.zip` archive.
+    Archive(PathBuf),
+}
+
+impl InstallSource {
+    pub fn detect(source: &str) -> Result<Self> {
+        let path = PathBuf::from(source);
+        if !path.exists() {
+            anyhow::bail!("Template source not found: {}", path.display());
+        }
+
+        let lower = source.to_lowercase();
+        if path.is_file() {
+            if lower.ends_with(".tar.gz") || lower.ends_with(".tgz") || lower.ends_with(".zip") {
+                return Ok(Self::Archive(path));
+            }
+            anyhow::bail!(
+                "Unsupported template archive {} (expected .tar.gz, .tgz or .zip)",
+                path.display()
+            );
+        }
+
+        if path.join(".git").exists() {
+            Ok(Self::Git(path))
+        } else {
+            Ok(Self::Directory(path))
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self {
+            Self::Directory(path) => format!("directory {}", path.display()),
+            Self::Git(path) => format!("git checkout {}", path.display()),
+            Self::Archive(path) => format!("archive {}", path.display()),
+        }
+    }
+}
+
+/// Templates installed under `<data dir>/templates/<name>`.
+pub struct TemplateRegistry {
+    root: PathBuf,
+}
+
+impl TemplateRegistry {
+    pub fn open() -> Result<Self> {
+        Ok(Self { root: data_dir()?.join("templates") })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn list(&self) -> Result<Vec<ProjectTemplate>> {
+        if !self.root.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut entries: Vec<_> = std::fs::read_dir(&self.root)?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.join(MANIFEST_FILE).is_file())
+            .collect();
+        entries.sort();
+
+        let mut templates = Vec::new();
+        for path in entries {
+            match ProjectTemplate::from_dir(&path) {
+                Ok(template) => templates.push(template),
+                Err(e) => log::warn!("Skipping template at {}: {:#}", path.display(), e),
+            }
+        }
+        Ok(templates)
+    }
+
+    pub fn get(&self, name: &str) -> Result<Option<ProjectTemplate>> {
+        if !is_valid_name(name) {
+            return Ok(None);
+        }
+        let dir = self.root.join(name);
+        if !dir.join(MANIFEST_FILE).is_file() {
+            return Ok(None);
+        }
+        ProjectTemplate::from_dir(&dir).map(Some)
+    }
+
+    /// Installs a template, returning its manifest. The template is named
+    /// after its manifest unless `name` overrides it.
+    pub fn add(&self, source: &InstallSource, name: Option<&str>, force: bool) -> Result<TemplateManifest> {
+        let staging = tempfile::tempdir().context("Failed to create staging directory")?;
+        match source {
+            InstallSource::Directory(path) => copy_dir(path, staging.path())?,
+            InstallSource::Git(path) => copy_git_checkout(path, staging.path())?,
+            InstallSource::Archive(path) => extract_archive(path, staging.path())?,
+        }
+
+        let template_root = find_template_root(staging.path())
+            .with_context(|| format!("No {} found in {}", MANIFEST_FILE, source.describe()))?;
+        let mut manifest = TemplateManifest::load(&template_root)?;
+        if let Some(name) = name {
+            manifest.name = name.to_string();
+        }
+
+        if !is_valid_name(&manifest.name) {
+            anyhow::bail!(
+                "Invalid template name '{}': use letters, digits, '-' and '_'",
+                manifest.name
+            );
+        }
+        if BUILTIN_TEMPLATES.iter().any(|builtin| builtin.name == manifest.name) {
+            anyhow::bail!("'{}' is a built-in template name; pass --name to rename it", manifest.name);
+        }
+
+        let dest = self.root.join(&manifest.name);
+        if dest.exists() {
+            if !force {
+                anyhow::bail!(
+                    "Template '{}' is already installed. Use --force to replace it",
+                    manifest.name
+                );
+            }
+            std::fs::remove_dir_all(&dest)
+                .with_context(|| format!("Failed to remove {}", dest.display()))?;
+        }
+
+        copy_dir(&template_root, &dest)?;
+        let contents = serde_yaml::to_string(&manifest)?;
+        std::fs::write(dest.join(MANIFEST_FILE), contents)?;
+        Ok(manifest)
+    }
+
+    pub fn remove(&self, name: &str) -> Result<()> {
+        if BUILTIN_TEMPLATES.iter().any(|builtin| builtin.name == name) {
+            anyhow::bail!("'{}' is a built-in template and cannot be removed", name);
+        }
+        let dir = self.root.join(name);
+        if !is_valid_name(name) || !dir.join(MANIFEST_FILE).is_file() {
+            anyhow::bail!("Template '{}' is not installed", name);
+        }
+        std::fs::remove_dir_all(&dir)
+            .with_context(|| format!("Failed to remove {}", dir.display()))?;
+        Ok(())
+    }
+}
+
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Archives often wrap everything in a single top-level folder. Metadata
+/// next to it, such as macOS's `__MACOSX/` and `.DS_Store` or the
+/// `pax_global_header` file of GitHub tarballs, is ignored.
+fn find_template_root(dir: &Path) -> Option<PathBuf> {
+    if dir.join(MANIFEST_FILE).is_file() {
+        return Some(dir.to_path_buf());
+    }
+    let entries: Vec<PathBuf> = std::fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            entry.path().is_dir() && !name.starts_with('.') && name != "__MACOSX"
+        })
+        .map(|entry| entry.path())
+        .collect();
+    match entries.as_slice() {
+        [only] if only.join(MANIFEST_FILE).is_file() => Some(only.clone()),
+        _ => None,
+    }
+}
+
+fn copy_dir(source: &Path, dest: &Path) -> Result<()> {
+    for entry in WalkDir::new(source) {
+        let entry = entry?;
+        let relative = entry.path().strip_prefix(source)?;
+        if relative.components().any(|c| c.as_os_str() == ".git") {
+            continue;
+        }
+        let target = dest.join(relative);
+        if entry.file_type().is_dir() {
+            std::fs::create_dir_all(&target)?;
+        } else if entry.file_type().is_file() {
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::copy(entry.path(), &target)
+                .with_context(|| format!("Failed to copy {}", entry.path().display()))?;
+        }
+    }
+    Ok(())
+}
+
+fn copy_git_checkout(source: &Path, dest: &Path) -> Result<()> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(source)
+        .args(["ls-files", "-z", "--cached", "--others", "--exclude-standard"])
+        .output()
+        .context("git not found. Install git to add templates from a checkout")?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "git ls-files failed in {}: {}",
+            source.display(),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    for relative in output.stdout.split(|b| *b == 0).filter(|p| !p.is_empty()) {
+        let relative = PathBuf::from(String::from_utf8_lossy(relative).to_string());
+        let from = source.join(&relative);
+        if !from.is_file() {
+            continue;
+        }
+        let target = dest.join(&relative);
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::copy(&from, &target)
+            .with_context(|| format!("Failed to copy {}", from.display()))?;
+    }
+    Ok(())
+}
+
+fn extract_archive(archive: &Path, dest: &Path) -> Result<()> {
+    let file = File::open(archive)
+        .with_context(|| format!("Failed to open archive {}", archive.display()))?;
+    if archive.to_string_lossy().to_lowercase().ends_with(".zip") {
+        zip::ZipArchive::new(file)
+            .and_then(|mut zip| zip.extract(dest))
+            .with_context(|| format!("Failed to extract {}", archive.display()))?;
+    } else {
+        tar::Archive::new(GzDecoder::new(file))
+            .unpack(dest)
+            .with_context(|| format!("Failed to extract {}", archive.display()))?;
+    }
+    Ok(())
+}
@@ -0,0 +1,396 @@
+use crate::naming::ComponentName;
+use crate::templates::RenderedFile;
+use crate::types::ComponentType;
+use anyhow::{Context, Result};
+use std::path::Path;
+
+/// Router files carry these markers; new imports and routes are inserted on
+/// the line above them. Layouts get their own `// nexus:routes:<layout>` marker
+/// for child routes.
+pub const IMPORTS_MARKER: &str = "// nexus:route-imports";
+pub const ROUTES_MARKER: &str = "// nexus:routes";
+
+/// How a framework maps URLs to pages.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Router {
+    /// `src/router.jsx`/`.tsx` from `nexus add router`.
+    React { file: String },
+    /// `src/router.ts` from `nexus add router`.
+    Vue { file: String },
+    /// `src/app/app.routes.ts`, with lazily loaded components.
+    Angular { file: String },
+    /// Route directories under `src/routes`.
+    SvelteKit,
+    /// Route directories under `app`.
+    NextApp,
+}
+
+/// Files to write and the router edit that register one page or layout.
+#[derive(Debug, Default)]
+pub struct RoutePlan {
+    pub files: Vec<RenderedFile>,
+    pub edit: Option<RenderedFile>,
+    /// Where the route was registered, for the summary.
+    pub description: String,
+}
+
+impl Router {
+    /// Finds the project's router. Frameworks without a router (plain HTML)
+    /// return `None`; a missing router file is an error.
+    pub fn find(root: &Path, framework: &str) -> Result<Option<Self>> {
+        let (router, setup) = match framework {
+            "react" => (Router::React { file: "src/router.jsx".into() }, "Run 'nexus add router' to set one up"),
+            "react-ts" => (Router::React { file: "src/router.tsx".into() }, "Run 'nexus add router' to set one up"),
+            "vue" => (Router::Vue { file: "src/router.ts".into() }, "Run 'nexus add router' to set one up"),
+            "angular" => (Router::Angular { file: "src/app/app.routes.ts".into() }, "Restore it from a new Angular project"),
+            "svelte" => (Router::SvelteKit, "SvelteKit routes live there"),
+            "nextjs" => (Router::NextApp, "The Next.js app router lives there"),
+            _ => return Ok(None),
+        };
+        let required = router.table_file().unwrap_or(match router {
+            Router::SvelteKit => "src/routes",
+            _ => "app",
+        });
+        if !root.join(required).exists() {
+            anyhow::bail!(
+                "Cannot register the route: {} not found. {}, or pass --no-route to skip registration",
+                required,
+                setup
+            );
+        }
+        Ok(Some(router))
+    }
+
+    fn table_file(&self) -> Option<&str> {
+        match self {
+            Router::React { file } | Router::Vue { file } | Router::Angular { file } => {
+                Some(file)
+            }
+            Router::SvelteKit | Router::NextApp => None,
+        }
+    }
+
+    /// Route-table routers render child routes through an outlet, so layouts
+    /// generated for them use one instead of children or slots.
+    pub fn uses_outlet(&self) -> bool {
+        self.table_file().is_some()
+    }
+
+    /// The fixed URL paths in a route table, skipping parameters (`:id`) and
+    /// wildcards. Child paths are taken as relative to `/`, which is where
+    /// generated layouts mount them. Directory routers return none: their
+    /// frameworks prerender routes themselves.
+    pub fn paths(&self, root: &Path) -> Result<Vec<String>> {
+        let Some(file) = self.table_file() else {
+            return Ok(Vec::new());
+        };
+        let contents = std::fs::read_to_string(root.join(file))
+            .with_context(|| format!("Failed to read {}", file))?;
+        let mut paths = Vec::new();
+        for (at, _) in contents.match_indices("path:") {
+            let rest = contents[at + "path:".len()..].trim_start();
+            let Some(quote) = rest.chars().next().filter(|c| matches!(c, '"' | '\'' | '`')) else {
+                continue;
+            };
+            let Some(end) = rest[1..].find(quote) else { continue };
+            let path = &rest[1..end + 1];
+            if path.contains(':') || path.contains('*') {
+                continue;
+            }
+            let path = format!("/{}", path.trim_start_matches('/'));
+            if !paths.contains(&path) {
+                paths.push(path);
+            }
+        }
+        Ok(paths)
+    }
+
+    /// Plans the registration of `component` (the generated main file). Nothing
+    /// is written; errors leave the project untouched.
+    pub fn plan(
+        &self,
+        root: &Path,
+        kind: &ComponentType,
+        name: &ComponentName,
+        component: &str,
+        route: Option<&str>,
+        layout: Option<&str>,
+    ) -> Result<RoutePlan> {
+        let path = match route {
+            Some(route) => format!("/{}", route.trim().trim_matches('/')),
+            None => name.route_path(),
+        };
+        let layout = layout.map(ComponentName::parse).transpose()?.map(|layout| layout.kebab());
+        match self {
+            Router::SvelteKit | Router::NextApp => self.plan_directory(root, kind, name, component, &path, layout.as_deref()),
+            _ => self.plan_table(root, kind, name, component, &path, layout.as_deref()),
+        }
+    }
+
+    fn plan_table(
+        &self,
+        root: &Path,
+        kind: &ComponentType,
+        name: &ComponentName,
+        component: &str,
+        path: &str,
+        layout: Option<&str>,
+    ) -> Result<RoutePlan> {
+        let file = self.table_file().unwrap_or_default();
+        let mut contents = std::fs::read_to_string(root.join(file))
+            .with_context(|| format!("Failed to read {}", file))?;
+        let router_dir = file.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("");
+        let identifier = name.pascal();
+
+        let (module, import) = match self {
+            Router::React { .. } => {
+                let module = relative_import(router_dir, strip_extension(component));
+                let import = format!("import {} from \"{}\"", identifier, module);
+                (module, Some(import))
+            }
+            Router::Vue { .. } => {
+                let module = relative_import(router_dir, component);
+                let import = format!("import {} from \"{}\"", identifier, module);
+                (module, Some(import))
+            }
+            _ => (relative_import(router_dir, strip_extension(component)), None),
+        };
+
+        let description = match kind {
+            ComponentType::Layout => format!("layout route in {}", file),
+            _ => format!("{} in {}", path, file),
+        };
+        if contents.contains(&format!("\"{}\"", module)) {
+            return Ok(RoutePlan { description: format!("{} (already registered)", description), ..RoutePlan::default() });
+        }
+
+        // Angular creates child routes relative to their parent, and so does
+        // Vue Router; React Router accepts absolute child paths.
+        let nested = layout.is_some() && !matches!(self, Router::React { .. });
+        let route_path = match self {
+            Router::Angular { .. } => path.trim_start_matches('/').to_string(),
+            _ if nested => path.trim_start_matches('/').to_string(),
+            _ => path.to_string(),
+        };
+        if matches!(kind, ComponentType::Page)
+            && (contents.contains(&format!("path: \"{}\"", route_path))
+                || contents.contains(&format!("path: '{}'", route_path)))
+        {
+            anyhow::bail!(
+                "Route {} is already registered in {}. Pass --route to choose another path",
+                path,
+                file
+            );
+        }
+
+        if matches!(self, Router::Angular { .. }) && !contents.contains(ROUTES_MARKER) {
+            // New Angular projects start with an empty, single-line array.
+            contents = contents.replacen(
+                "routes: Routes = []",
+                &format!("routes: Routes = [\n  {}\n]", ROUTES_MARKER),
+                1,
+            );
+        }
+
+        let marker = match layout {
+            Some(layout) => format!("{}:{}", ROUTES_MARKER, layout),
+            None => ROUTES_MARKER.to_string(),
+        };
+        if !contents.lines().any(|line| line.trim() == marker) {
+            match layout {
+                Some(layout) => anyhow::bail!(
+                    "Layout '{}' is not registered in {}. Generate it with 'nexus component layout {}' first",
+                    layout,
+                    file,
+                    layout
+                ),
+                None => anyhow::bail!(
+                    "Cannot register the route: no '{}' marker in {}. Add it inside the routes array",
+                    marker,
+                    file
+                ),
+            }
+        }
+
+        let component_ref = match self {
+            Router::React { .. } => format!("element: <{} />", identifier),
+            Router::Vue { .. } => format!("component: {}", identifier),
+            _ => format!(
+                "loadComponent: () => import(\"{}\").then((m) => m.{}Component)",
+                module, identifier
+            ),
+        };
+        let entry = match kind {
+            ComponentType::Layout => {
+                let parent = match self {
+                    Router::React { .. } => String::new(),
+                    Router::Vue { .. } => "\n  path: \"/\",".to_string(),
+                    _ => "\n  path: \"\",".to_string(),
+                };
+                format!(
+                    "{{{}\n  {},\n  children: [\n    {}:{}\n  ],\n}},",
+                    parent,
+                    component_ref,
+                    ROUTES_MARKER,
+                    name.kebab()
+                )
+            }
+            _ => match self {
+                Router::Vue { .. } => format!(
+                    "{{ path: \"{}\", name: \"{}\", {} }},",
+                    route_path,
+                    path.trim_matches('/').replace('/', "-"),
+                    component_ref
+                ),
+                _ => format!("{{ path: \"{}\", {} }},", route_path, component_ref),
+            },
+        };
+
+        let mut contents = insert_before_marker(&contents, &marker, &entry);
+        if let Some(import) = import {
+            if !contents.lines().any(|line| line.trim() == IMPORTS_MARKER) {
+                anyhow::bail!(
+                    "Cannot register the route: no '{}' marker in {}. Add it after the imports",
+                    IMPORTS_MARKER,
+                    file
+                );
+            }
+            contents = insert_before_marker(&contents, IMPORTS_MARKER, &import);
+        }
+
+        Ok(RoutePlan {
+            files: Vec::new(),
+            edit: Some(RenderedFile { path: file.to_string(), contents }),
+            description,
+        })
+    }
+
+    fn plan_directory(
+        &self,
+        root: &Path,
+        kind: &ComponentType,
+        name: &ComponentName,
+        component: &str,
+        path: &str,
+        layout: Option<&str>,
+    ) -> Result<RoutePlan> {
+        let routes_dir = match self {
+            Router::SvelteKit => "src/routes",
+            _ => "app",
+        };
+        let group = layout.map(|layout| format!("({})", layout));
+        if let Some(group) = &group {
+            if !root.join(routes_dir).join(group).is_dir() {
+                anyhow::bail!(
+                    "Layout '{}' has no route group {}/{}. Generate it with 'nexus component layout {}' first",
+                    group.trim_matches(|c| c == '(' || c == ')'),
+                    routes_dir,
+                    group,
+                    group.trim_matches(|c| c == '(' || c == ')')
+                );
+            }
+        }
+
+        let dir = match kind {
+            ComponentType::Layout => format!("{}/({})", routes_dir, name.kebab()),
+            _ => std::iter::once(routes_dir)
+                .chain(group.as_deref())
+                .chain(path.split('/').filter(|segment| !segment.is_empty()))
+                .collect::<Vec<_>>()
+                .join("/"),
+        };
+        let (file, contents) = match (self, kind) {
+            (Router::SvelteKit, _) => {
+                let module = format!("$lib/{}", component.trim_start_matches("src/lib/"));
+                let body = match kind {
+                    ComponentType::Layout => format!("<{0}>\n  <slot />\n</{0}>", name.pascal()),
+                    _ => format!("<{} />", name.pascal()),
+                };
+                let file = match kind {
+                    ComponentType::Layout => "+layout.svelte",
+                    _ => "+page.svelte",
+                };
+                (
+                    file,
+                    format!(
+                        "<script>\n  import {} from \"{}\"\n</script>\n\n{}\n",
+                        name.pascal(),
+                        module,
+                        body
+                    ),
+                )
+            }
+            (_, ComponentType::Layout) => (
+                "layout.tsx",
+                format!("export {{ default }} from \"{}\"\n", relative_import(&dir, strip_extension(component))),
+            ),
+            _ => (
+                "page.tsx",
+                format!("export {{ default }} from \"{}\"\n", relative_import(&dir, strip_extension(component))),
+            ),
+        };
+
+        let route_file = format!("{}/{}", dir, file);
+        let description = match kind {
+            ComponentType::Layout => format!("route group {}", dir),
+            _ => format!("{} at {}", path, route_file),
+        };
+        match std::fs::read_to_string(root.join(&route_file)) {
+            Ok(existing) if existing == contents => Ok(RoutePlan {
+                description: format!("{} (already registered)", description),
+                ..RoutePlan::default()
+            }),
+            Ok(_) => anyhow::bail!(
+                "{} already exists. Pass --route to choose another path",
+                route_file
+            ),
+            Err(_) => Ok(RoutePlan {
+                files: vec![RenderedFile { path: route_file, contents }],
+                edit: None,
+                description,
+            }),
+        }
+    }
+}
+
+/// Inserts `text` on its own lines above `marker`, at the marker's indentation.
+pub fn insert_before_marker(contents: &str, marker: &str, text: &str) -> String {
+    let mut output = String::with_capacity(contents.len() + text.len() + 16);
+    let mut inserted = false;
+    for line in contents.split_inclusive('\n') {
+        if !inserted && line.trim() == marker {
+            let indent: String = line.chars().take_while(|c| c.is_whitespace()).collect();
+            for text_line in text.lines() {
+                output.push_str(&indent);
+                output.push_str(text_line);
+                output.push('\n');
+            }
+            inserted = true;
+        }
+        output.push_str(line);
+    }
+    output
+}
+
+pub fn strip_extension(path: &str) -> &str {
+    match path.rsplit_once('.') {
+        Some((stem, _)) if !stem.ends_with('/') && !stem.is_empty() => stem,
+        _ => path,
+    }
+}
+
+/// An import specifier for `target` from a module in `from_dir`, both
+/// relative to the project root.
+pub fn relative_import(from_dir: &str, target: &str) -> String {
+    let from: Vec<&str> = from_dir.split('/').filter(|s| !s.is_empty()).collect();
+    let to: Vec<&str> = target.split('/').filter(|s| !s.is_empty()).collect();
+    let common = from.iter().zip(&to).take_while(|(a, b)| a == b).count();
+    let mut parts: Vec<&str> = vec![".."; from.len() - common];
+    parts.extend(&to[common..]);
+    let joined = parts.join("/");
+    if joined.starts_with("..") {
+        joined
+    } else {
+        format!("./{}", joined)
+    }
+}
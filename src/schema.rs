@@ -0,0 +1,681 @@
+use crate::types::BuildTarget;
+use serde::{Deserialize, Serialize};
+use serde_yaml::{Mapping, Value};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+/// Version written to new `nexus.yaml` files. Bump it together with a new
+/// entry in `MIGRATIONS`, also for new optional fields: sections reject
+/// unknown fields, so without a bump an older CLI reports the new field
+/// instead of asking to be upgraded.
+pub const CURRENT_SCHEMA_VERSION: u32 = 4;
+
+/// Files without a `schema_version` predate versioning.
+const LEGACY_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProjectConfig {
+    pub schema_version: u32,
+    pub name: String,
+    pub template: String,
+    pub framework: String,
+    /// Template variables the project was generated with.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub variables: BTreeMap<String, String>,
+    #[serde(default)]
+    pub build: BuildConfig,
+    #[serde(default)]
+    pub serve: ServeConfig,
+    #[serde(default)]
+    pub deploy: DeployConfig,
+    #[serde(default)]
+    pub ai: AiConfig,
+    #[serde(default)]
+    pub db: DbConfig,
+    #[serde(default, skip_serializing_if = "ApiConfig::is_empty")]
+    pub api: ApiConfig,
+    #[serde(default, skip_serializing_if = "EnvConfig::is_empty")]
+    pub env: EnvConfig,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub plugins: Vec<PluginConfig>,
+    /// Recipes applied with `nexus add`, in the order they were applied.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub recipes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BuildConfig {
+    pub out_dir: PathBuf,
+    pub mode: String,
+    pub target: BuildTarget,
+    #[serde(skip_serializing_if = "BudgetConfig::is_empty")]
+    pub budgets: BudgetConfig,
+    #[serde(skip_serializing_if = "SsgConfig::is_empty")]
+    pub ssg: SsgConfig,
+}
+
+impl Default for BuildConfig {
+    fn default() -> Self {
+        Self {
+            out_dir: PathBuf::from("dist"),
+            mode: "production".to_string(),
+            target: BuildTarget::Web,
+            budgets: BudgetConfig::default(),
+            ssg: SsgConfig::default(),
+        }
+    }
+}
+
+/// Options for `--target ssg`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SsgConfig {
+    /// Where the site is published, e.g. `https://example.com`. The sitemap
+    /// needs absolute URLs, so it is only written when this is set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub site_url: Option<String>,
+    /// Routes to prerender besides the ones found in the project, such as
+    /// `/blog/hello-world/` for a `blog/[slug].html` page.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub routes: Vec<String>,
+}
+
+impl SsgConfig {
+    pub fn is_empty(&self) -> bool {
+        self.site_url.is_none() && self.routes.is_empty()
+    }
+}
+
+/// Size limits checked against `nexus-manifest.json` after each build.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BudgetConfig {
+    /// Limit for all emitted files together.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<ByteSize>,
+    /// Limit for any single file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub per_file: Option<ByteSize>,
+    /// Limits for files matching a glob, e.g. `assets/*.js: 150KB`.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub files: BTreeMap<String, ByteSize>,
+    /// Which size is compared with the limits.
+    pub measure: SizeMeasure,
+    pub on_exceed: BudgetAction,
+}
+
+impl BudgetConfig {
+    pub fn is_empty(&self) -> bool {
+        self.total.is_none() && self.per_file.is_none() && self.files.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SizeMeasure {
+    Raw,
+    #[default]
+    Gzip,
+    Brotli,
+}
+
+impl SizeMeasure {
+    pub fn name(&self) -> &'static str {
+        match self {
+            SizeMeasure::Raw => "raw",
+            SizeMeasure::Gzip => "gzip",
+            SizeMeasure::Brotli => "brotli",
+        }
+    }
+}
+
+/// What an exceeded budget does: print a warning, or fail the build.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BudgetAction {
+    #[default]
+    Warn,
+    Error,
+}
+
+/// A size written as bytes (`1500`) or with a unit (`150KB`, `1.5 MB`).
+/// Units are binary: 1KB is 1024 bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "RawByteSize", into = "String")]
+pub struct ByteSize(pub u64);
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawByteSize {
+    Bytes(u64),
+    Text(String),
+}
+
+impl TryFrom<RawByteSize> for ByteSize {
+    type Error = String;
+
+    fn try_from(raw: RawByteSize) -> Result<Self, Self::Error> {
+        let text = match raw {
+            RawByteSize::Bytes(bytes) => return Ok(ByteSize(bytes)),
+            RawByteSize::Text(text) => text,
+        };
+        let trimmed = text.trim();
+        let split = trimmed
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(trimmed.len());
+        let (number, unit) = trimmed.split_at(split);
+        let multiplier = match unit.trim().to_ascii_lowercase().as_str() {
+            "" | "b" => 1,
+            "k" | "kb" | "kib" => 1024,
+            "m" | "mb" | "mib" => 1024 * 1024,
+            "g" | "gb" | "gib" => 1024 * 1024 * 1024,
+            _ => return Err(format!("invalid size '{}': use a unit of B, KB, MB or GB", text)),
+        };
+        let number: f64 = number
+            .parse()
+            .map_err(|_| format!("invalid size '{}': expected a number such as 150KB", text))?;
+        Ok(ByteSize((number * multiplier as f64).round() as u64))
+    }
+}
+
+impl From<ByteSize> for String {
+    fn from(size: ByteSize) -> Self {
+        size.to_string()
+    }
+}
+
+impl std::fmt::Display for ByteSize {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            bytes if bytes >= 1024 * 1024 && bytes % (1024 * 1024) == 0 => {
+                write!(f, "{}MB", bytes / (1024 * 1024))
+            }
+            bytes if bytes >= 1024 && bytes % 1024 == 0 => write!(f, "{}KB", bytes / 1024),
+            bytes => write!(f, "{}B", bytes),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServeConfig {
+    pub port: u16,
+    pub host: String,
+    pub open: bool,
+    /// Rebuild on changes and reload open pages.
+    pub live_reload: bool,
+    /// Serve `index.html` for page paths with no file, so client-side routes
+    /// can be opened directly.
+    pub spa_fallback: bool,
+    /// Print each request with its status and duration.
+    pub log_requests: bool,
+    /// URL pattern (`/assets/*`) to headers added to matching responses.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, BTreeMap<String, String>>,
+    /// Path prefix (`/api`) to the server its requests are forwarded to.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub proxy: BTreeMap<String, ProxyRule>,
+}
+
+impl Default for ServeConfig {
+    fn default() -> Self {
+        Self {
+            port: 3000,
+            host: "127.0.0.1".to_string(),
+            open: false,
+            live_reload: true,
+            spa_fallback: true,
+            log_requests: true,
+            headers: BTreeMap::new(),
+            proxy: BTreeMap::new(),
+        }
+    }
+}
+
+/// Where `nexus serve` forwards requests under a path prefix. Written as the
+/// upstream URL alone, or with options.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "RawProxyRule")]
+pub struct ProxyRule {
+    /// Upstream base URL, e.g. `http://localhost:8080`.
+    pub target: String,
+    /// Replaces the matched prefix: with `""`, `/api/users` is forwarded as
+    /// `/users`. The path is kept as it is by default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rewrite: Option<String>,
+    /// Headers set on every forwarded request.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+    /// Forward WebSocket connections as well.
+    pub ws: bool,
+}
+
+/// A rule as written: a bare target URL or the full options. Deserialized
+/// by hand rather than as an untagged enum, so a mistake in the options
+/// keeps serde's own message, such as the name of an unknown field.
+struct RawProxyRule(ProxyOptions);
+
+impl<'de> Deserialize<'de> for RawProxyRule {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct RuleVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for RuleVisitor {
+            type Value = RawProxyRule;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("a target URL or a map with `target`")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, target: &str) -> Result<Self::Value, E> {
+                Ok(RawProxyRule(ProxyOptions {
+                    target: target.to_string(),
+                    rewrite: None,
+                    headers: BTreeMap::new(),
+                    ws: true,
+                }))
+            }
+
+            fn visit_map<A: serde::de::MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+                ProxyOptions::deserialize(serde::de::value::MapAccessDeserializer::new(map)).map(RawProxyRule)
+            }
+        }
+
+        deserializer.deserialize_any(RuleVisitor)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ProxyOptions {
+    target: String,
+    #[serde(default)]
+    rewrite: Option<String>,
+    #[serde(default)]
+    headers: BTreeMap<String, String>,
+    #[serde(default = "default_true")]
+    ws: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl TryFrom<RawProxyRule> for ProxyRule {
+    type Error = String;
+
+    fn try_from(RawProxyRule(options): RawProxyRule) -> Result<Self, Self::Error> {
+        if !(options.target.starts_with("http://") || options.target.starts_with("https://")) {
+            return Err(format!(
+                "invalid proxy target '{}': expected an http:// or https:// URL",
+                options.target
+            ));
+        }
+        Ok(ProxyRule {
+            target: options.target.trim_end_matches('/').to_string(),
+            rewrite: options.rewrite,
+            headers: options.headers,
+            ws: options.ws,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DeployConfig {
+    /// Default target for `nexus deploy` (vercel, netlify, docker, static, github).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    pub env: String,
+    /// Where `nexus deploy static` publishes the `ssg` build, such as
+    /// a web server's document root. Relative to the project root.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dir: Option<PathBuf>,
+}
+
+impl Default for DeployConfig {
+    fn default() -> Self {
+        Self {
+            target: None,
+            env: "production".to_string(),
+            dir: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AiConfig {
+    pub enabled: bool,
+    pub model: String,
+}
+
+impl Default for AiConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            model: "phi-3-mini".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DbConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    pub migrations_dir: PathBuf,
+}
+
+impl Default for DbConfig {
+    fn default() -> Self {
+        Self {
+            url: None,
+            migrations_dir: PathBuf::from("migrations"),
+        }
+    }
+}
+
+/// Backend used for `nexus component api`. Both settings are detected from
+/// the project when unset.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ApiConfig {
+    /// express, nextjs, axum or warp.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub server: Option<String>,
+    /// Directory for server handlers, relative to the project root.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dir: Option<PathBuf>,
+}
+
+impl ApiConfig {
+    pub fn is_empty(&self) -> bool {
+        self.server.is_none() && self.dir.is_none()
+    }
+}
+
+/// Environment variables for builds, read from `.env` files and the shell.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EnvConfig {
+    /// Prefixes of the variables injected into the built output
+    /// [default: NEXUS_PUBLIC_].
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub public_prefixes: Vec<String>,
+    /// Variables that must be set, and not empty, for a build to start.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub required: Vec<String>,
+}
+
+impl EnvConfig {
+    pub fn is_empty(&self) -> bool {
+        self.public_prefixes.is_empty() && self.required.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PluginConfig {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub options: BTreeMap<String, Value>,
+}
+
+impl ProjectConfig {
+    pub fn new(
+        name: &str,
+        template: &str,
+        framework: &str,
+        variables: BTreeMap<String, String>,
+    ) -> Self {
+        Self {
+            schema_version: CURRENT_SCHEMA_VERSION,
+            name: name.to_string(),
+            template: template.to_string(),
+            framework: framework.to_string(),
+            variables,
+            build: BuildConfig::default(),
+            serve: ServeConfig::default(),
+            deploy: DeployConfig::default(),
+            ai: AiConfig::default(),
+            db: DbConfig::default(),
+            api: ApiConfig::default(),
+            env: EnvConfig::default(),
+            plugins: Vec::new(),
+            recipes: Vec::new(),
+        }
+    }
+
+    /// Parses `nexus.yaml` contents, migrating older schema versions in memory.
+    pub fn parse(contents: &str, path: &Path) -> Result<Self, ConfigError> {
+        let value: Value = serde_yaml::from_str(contents)
+            .map_err(|e| ConfigError::from_yaml(path, contents, e))?;
+        let version = schema_version(&value).map_err(|message| ConfigError::Invalid {
+            path: path.to_path_buf(),
+            message,
+        })?;
+
+        if version > CURRENT_SCHEMA_VERSION {
+            return Err(ConfigError::TooNew {
+                path: path.to_path_buf(),
+                version,
+            });
+        }
+
+        if version == CURRENT_SCHEMA_VERSION {
+            // Parse the text directly so errors keep their line and column.
+            return serde_yaml::from_str(contents)
+                .map_err(|e| ConfigError::from_yaml(path, contents, e));
+        }
+
+        let (migrated, _) = migrate(value.clone(), version).map_err(|message| ConfigError::Invalid {
+            path: path.to_path_buf(),
+            message,
+        })?;
+        if without_version(&migrated) == without_version(&value) {
+            // Only the version changed, so the text is parsed directly and
+            // errors keep their line and column.
+            let mut config: Self = serde_yaml::from_str(contents)
+                .map_err(|e| ConfigError::from_yaml(path, contents, e))?;
+            config.schema_version = CURRENT_SCHEMA_VERSION;
+            return Ok(config);
+        }
+        serde_yaml::from_value(migrated).map_err(|e| ConfigError::Invalid {
+            path: path.to_path_buf(),
+            message: format!("{} (after migrating from schema version {})", e, version),
+        })
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("{}:{line}:{column}: {message}\n{snippet}", path.display())]
+    Syntax {
+        path: PathBuf,
+        line: usize,
+        column: usize,
+        message: String,
+        snippet: String,
+    },
+
+    #[error("{}: {message}", path.display())]
+    Invalid { path: PathBuf, message: String },
+
+    #[error(
+        "{}: schema version {version} is newer than this nexus supports ({}). Upgrade the nexus CLI.",
+        path.display(),
+        CURRENT_SCHEMA_VERSION
+    )]
+    TooNew { path: PathBuf, version: u32 },
+}
+
+impl ConfigError {
+    fn from_yaml(path: &Path, contents: &str, error: serde_yaml::Error) -> Self {
+        match error.location() {
+            Some(location) => {
+                let message = error.to_string();
+                // serde_yaml appends the location to the message; it is shown
+                // in the prefix instead.
+                let message = message
+                    .split(" at line ")
+                    .next()
+                    .unwrap_or(&message)
+                    .to_string();
+                Self::Syntax {
+                    path: path.to_path_buf(),
+                    line: location.line(),
+                    column: location.column(),
+                    message,
+                    snippet: snippet(contents, location.line(), location.column()),
+                }
+            }
+            None => Self::Invalid {
+                path: path.to_path_buf(),
+                message: error.to_string(),
+            },
+        }
+    }
+}
+
+fn snippet(contents: &str, line: usize, column: usize) -> String {
+    let Some(source_line) = contents.lines().nth(line.saturating_sub(1)) else {
+        return String::new();
+    };
+    let gutter = format!("{:>4} | ", line);
+    format!(
+        "{}{}\n{}^",
+        gutter,
+        source_line,
+        " ".repeat(gutter.len() + column.saturating_sub(1))
+    )
+}
+
+pub fn schema_version(value: &Value) -> Result<u32, String> {
+    match value.get("schema_version") {
+        None => Ok(LEGACY_SCHEMA_VERSION),
+        Some(Value::Number(number)) => number
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .filter(|v| *v >= LEGACY_SCHEMA_VERSION)
+            .ok_or_else(|| format!("invalid schema_version {}", number)),
+        Some(other) => Err(format!(
+            "schema_version must be a number, found {}",
+            serde_yaml::to_string(other).unwrap_or_default().trim()
+        )),
+    }
+}
+
+type Migration = fn(&mut Mapping) -> Result<(), String>;
+
+/// `MIGRATIONS[i]` upgrades a file from schema version `i + 1` to `i + 2`.
+const MIGRATIONS: &[(&str, Migration)] = &[
+    (
+        "add schema_version and build, serve, deploy, ai and db sections",
+        migrate_v1_to_v2,
+    ),
+    ("limit build.target to web, ssg, pwa or lib", migrate_v2_to_v3),
+    (
+        "allow build.ssg, serve.proxy, serve.headers and other optional settings",
+        migrate_v3_to_v4,
+    ),
+];
+
+/// The document without its `schema_version`, to compare versions by content.
+fn without_version(value: &Value) -> Value {
+    let mut value = value.clone();
+    if let Some(mapping) = value.as_mapping_mut() {
+        mapping.remove("schema_version");
+    }
+    value
+}
+
+/// Applies each migration from `from` up to the current version, returning
+/// the migrated document and a description of every step taken.
+pub fn migrate(mut value: Value, from: u32) -> Result<(Value, Vec<String>), String> {
+    let mut steps = Vec::new();
+    let mut version = from;
+
+    while version < CURRENT_SCHEMA_VERSION {
+        let (description, migration) = MIGRATIONS
+            .get((version - LEGACY_SCHEMA_VERSION) as usize)
+            .ok_or_else(|| format!("no migration from schema version {}", version))?;
+        let mapping = value
+            .as_mapping_mut()
+            .ok_or_else(|| "nexus.yaml must be a mapping".to_string())?;
+        migration(mapping)?;
+
+        // Keep schema_version as the first key.
+        let mut upgraded = Mapping::new();
+        upgraded.insert("schema_version".into(), Value::from(version + 1));
+        for (key, entry) in mapping.iter() {
+            if key.as_str() != Some("schema_version") {
+                upgraded.insert(key.clone(), entry.clone());
+            }
+        }
+        *mapping = upgraded;
+
+        steps.push(format!("v{} → v{}: {}", version, version + 1, description));
+        version += 1;
+    }
+
+    Ok((value, steps))
+}
+
+/// The sections schema version 2 added, with the values it defined. Spelled
+/// out so that later changes to the defaults do not change what a version 1
+/// file migrates to.
+const V2_SECTIONS: &str = "\
+build:
+  out_dir: dist
+  mode: production
+  target: web
+serve:
+  port: 3000
+  host: '127.0.0.1'
+  open: false
+deploy:
+  env: production
+ai:
+  enabled: false
+  model: phi-3-mini
+db:
+  migrations_dir: migrations
+";
+
+fn migrate_v1_to_v2(config: &mut Mapping) -> Result<(), String> {
+    let sections: Mapping = serde_yaml::from_str(V2_SECTIONS).map_err(|e| e.to_string())?;
+    for (key, section) in sections {
+        if !config.contains_key(&key) {
+            config.insert(key, section);
+        }
+    }
+    Ok(())
+}
+
+/// `build.target` used to be free text that only Vite read, as its esbuild
+/// target. Values that are not a build target become `web`.
+fn migrate_v2_to_v3(config: &mut Mapping) -> Result<(), String> {
+    let Some(build) = config.get_mut("build").and_then(Value::as_mapping_mut) else {
+        return Ok(());
+    };
+    let valid = build
+        .get("target")
+        .and_then(|target| serde_yaml::from_value::<BuildTarget>(target.clone()).ok())
+        .is_some();
+    if build.contains_key("target") && !valid {
+        build.insert("target".into(), "web".into());
+    }
+    Ok(())
+}
+
+/// Version 4 only adds optional settings: `build.ssg`, `serve.proxy`,
+/// `serve.headers`, `serve.live_reload`, `serve.spa_fallback`,
+/// `serve.log_requests` and `deploy.dir`. Version 3 files are valid as they are.
+fn migrate_v3_to_v4(_config: &mut Mapping) -> Result<(), String> {
+    Ok(())
+}
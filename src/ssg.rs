@@ -0,0 +1,229 @@
+use crate::manifest::route_of;
+use crate::schema::SsgConfig;
+use anyhow::{Context, Result};
+use std::collections::BTreeMap;
+use std::path::Path;
+use walkdir::WalkDir;
+
+pub const SITEMAP: &str = "sitemap.xml";
+pub const ROBOTS: &str = "robots.txt";
+
+/// Pages that are served on errors rather than at a route of their own.
+const ERROR_PAGES: &[&str] = &["404.html", "500.html", "200.html"];
+
+/// Whether a page path such as `blog/[slug].html` has parameter segments,
+/// making it a template for the routes that match it.
+pub fn is_route_template(path: &str) -> bool {
+    path.split('/').any(|segment| segment.starts_with('[') && segment.contains(']'))
+}
+
+/// The parameters of `route` if it matches the page `template`: `/blog/hello/`
+/// matches `blog/[slug].html` and `blog/[slug]/index.html` with `slug = hello`.
+pub fn match_route(template: &str, route: &str) -> Option<BTreeMap<String, String>> {
+    let pattern = segments(template.strip_suffix(".html").unwrap_or(template));
+    let route = segments(route.strip_suffix(".html").unwrap_or(route));
+    if pattern.len() != route.len() {
+        return None;
+    }
+    let mut params = BTreeMap::new();
+    for (expected, actual) in pattern.iter().zip(&route) {
+        match expected.strip_prefix('[').and_then(|name| name.strip_suffix(']')) {
+            Some(name) => {
+                params.insert(name.to_string(), actual.to_string());
+            }
+            None if expected == actual => {}
+            None => return None,
+        }
+    }
+    Some(params)
+}
+
+fn segments(path: &str) -> Vec<&str> {
+    let path = match path.trim_matches('/') {
+        "index" => "",
+        path => path.strip_suffix("/index").unwrap_or(path),
+    };
+    path.split('/').filter(|segment| !segment.is_empty()).collect()
+}
+
+/// The file a route is written to: `/` is `index.html`, `/blog/hello/` is
+/// `blog/hello/index.html` and `/about.html` stays `about.html`, so any
+/// static host serves it without rewrites.
+pub fn route_file(route: &str) -> String {
+    let path = route.trim().trim_matches('/');
+    if path.ends_with(".html") {
+        path.to_string()
+    } else if path.is_empty() {
+        "index.html".to_string()
+    } else {
+        format!("{}/index.html", path)
+    }
+}
+
+/// Gives each route of a client-rendered app its own copy of the built
+/// `index.html`, so deep links load the app on hosts without rewrites.
+/// Returns the number of files written.
+pub fn write_app_shells(output: &Path, routes: &[String]) -> Result<usize> {
+    let index = output.join("index.html");
+    let shell = std::fs::read_to_string(&index)
+        .with_context(|| format!("The ssg target needs an index.html in {}", output.display()))?;
+    let mut written = 0;
+    for route in routes {
+        let file = route_file(route);
+        let path = output.join(&file);
+        if path.exists() {
+            continue;
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let shell = relocate(&shell, file.matches('/').count());
+        std::fs::write(&path, shell).with_context(|| format!("Failed to write {}", path.display()))?;
+        written += 1;
+    }
+    Ok(written)
+}
+
+/// The shell for a page `depth` directories below the root: relative `src`
+/// and `href` attributes, such as Vite's `./assets/...` with `base: './'`,
+/// get a `../` per level. Comments and `<script>` and `<style>` bodies are
+/// not markup and stay as they are. A shell with a `<base>` tag, as
+/// Angular's has, resolves relative values against it and is kept whole.
+fn relocate(shell: &str, depth: usize) -> String {
+    if depth == 0 || shell.contains("<base ") {
+        return shell.to_string();
+    }
+    let up = "../".repeat(depth);
+    // ASCII lowercasing keeps byte offsets, so they apply to `shell`.
+    let lower = shell.to_ascii_lowercase();
+    let mut output = String::with_capacity(shell.len());
+    let mut last = 0;
+    let mut i = 0;
+    while let Some(open) = lower[i..].find('<').map(|offset| i + offset) {
+        if lower[open..].starts_with("<!--") {
+            i = lower[open..].find("-->").map_or(shell.len(), |end| open + end + 3);
+            continue;
+        }
+        let Some(close) = lower[open..].find('>').map(|offset| open + offset) else {
+            break;
+        };
+        for (start, end) in url_attributes(&shell[open..close]) {
+            let value = &shell[open + start..open + end];
+            // Absolute paths, fragments, and URLs with a scheme such as `data:`.
+            if value.is_empty() || value.starts_with(['/', '#', '?']) || value.contains(':') {
+                continue;
+            }
+            output.push_str(&shell[last..open + start]);
+            output.push_str(&up);
+            output.push_str(value.strip_prefix("./").unwrap_or(value));
+            last = open + end;
+        }
+        i = close + 1;
+        for element in ["script", "style"] {
+            if lower[open + 1..].starts_with(element) {
+                let end_tag = format!("</{}", element);
+                i = lower[i..].find(&end_tag).map_or(shell.len(), |end| i + end);
+            }
+        }
+    }
+    output.push_str(&shell[last..]);
+    output
+}
+
+/// Byte ranges of the quoted `src` and `href` values in the opening tag
+/// `tag`. The name must follow whitespace, so `data-src` is not one.
+fn url_attributes(tag: &str) -> Vec<(usize, usize)> {
+    let bytes = tag.as_bytes();
+    let mut values = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            quote @ (b'"' | b'\'') => {
+                i = tag[i + 1..].find(quote as char).map_or(bytes.len(), |end| i + end + 2);
+            }
+            b'=' => {
+                let name_start = tag[..i]
+                    .rfind(|c: char| !c.is_ascii_alphanumeric())
+                    .map_or(0, |position| position + 1);
+                let name = &tag[name_start..i];
+                let is_url = (name.eq_ignore_ascii_case("src") || name.eq_ignore_ascii_case("href"))
+                    && tag[..name_start].ends_with(|c: char| c.is_ascii_whitespace());
+                i += 1;
+                if let Some(&quote @ (b'"' | b'\'')) = bytes.get(i) {
+                    let start = i + 1;
+                    let Some(end) = tag[start..].find(quote as char).map(|end| start + end) else {
+                        break;
+                    };
+                    if is_url {
+                        values.push((start, end));
+                    }
+                    i = end + 1;
+                }
+            }
+            _ => i += 1,
+        }
+    }
+    values
+}
+
+/// Writes `sitemap.xml` and `robots.txt` for the pages in `output`, unless
+/// the project ships its own, and checks that every configured route was
+/// rendered. Returns warnings.
+pub fn finish(output: &Path, config: &SsgConfig) -> Result<Vec<String>> {
+    let mut warnings = Vec::new();
+    let mut pages = Vec::new();
+    for entry in WalkDir::new(output) {
+        let entry = entry?;
+        let relative = entry.path().strip_prefix(output)?.to_string_lossy().replace('\\', "/");
+        if entry.file_type().is_file() && relative.ends_with(".html") && !ERROR_PAGES.contains(&relative.as_str()) {
+            pages.push(relative);
+        }
+    }
+    pages.sort();
+
+    for route in &config.routes {
+        // Next.js exports `/about` as `about.html`.
+        let flat = format!("{}.html", route.trim().trim_matches('/'));
+        if !pages.contains(&route_file(route)) && !pages.contains(&flat) {
+            warnings.push(format!("Route {} was not rendered: no page matches it", route));
+        }
+    }
+
+    let site_url = config.site_url.as_deref().map(|url| url.trim_end_matches('/'));
+    if output.join(SITEMAP).exists() {
+        warnings.push(format!("Kept the project's {}", SITEMAP));
+    } else if let Some(site_url) = site_url {
+        let mut sitemap = String::from(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<urlset xmlns=\"http://www.sitemaps.org/schemas/sitemap/0.9\">\n",
+        );
+        for page in &pages {
+            sitemap.push_str(&format!("  <url><loc>{}{}</loc></url>\n", site_url, escape_xml(&route_of(page))));
+        }
+        sitemap.push_str("</urlset>\n");
+        write(output, SITEMAP, &sitemap)?;
+    } else {
+        warnings.push("Set build.ssg.site_url in nexus.yaml to generate sitemap.xml".to_string());
+    }
+
+    if !output.join(ROBOTS).exists() {
+        let mut robots = String::from("User-agent: *\nAllow: /\n");
+        if let Some(site_url) = site_url.filter(|_| output.join(SITEMAP).exists()) {
+            robots.push_str(&format!("\nSitemap: {}/{}\n", site_url, SITEMAP));
+        }
+        write(output, ROBOTS, &robots)?;
+    }
+    Ok(warnings)
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+fn write(output: &Path, file: &str, contents: &str) -> Result<()> {
+    std::fs::write(output.join(file), contents)
+        .with_context(|| format!("Failed to write {}", output.join(file).display()))
+}
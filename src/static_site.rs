@@ -0,0 +1,874 @@
+use crate::cache::{content_hash, key_of, BuildCache};
+use crate::env::ProjectEnv;
+use crate::manifest::route_of;
+use crate::ssg;
+use anyhow::{Context, Result};
+use handlebars::Handlebars;
+use rayon::prelude::*;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Project files left out of a static build copied from the project root.
+const STATIC_EXCLUDES: &[&str] = &[
+    "node_modules",
+    "public",
+    "nexus.yaml",
+    "nexus.api.json",
+    "nexus-workspace.yaml",
+    "package.json",
+    "package-lock.json",
+    "pnpm-lock.yaml",
+    "yarn.lock",
+    "bun.lockb",
+];
+
+/// Files fetched at a fixed URL by browsers, crawlers or a script's string
+/// literal, such as `register("sw.js")`, which keep their name when assets
+/// are fingerprinted. Files under `.well-known/` keep theirs as well.
+const FIXED_NAMES: &[&str] = &[
+    "sw.js",
+    "service-worker.js",
+    "manifest.webmanifest",
+    "manifest.json",
+    "robots.txt",
+    "sitemap.xml",
+    "favicon.ico",
+    "browserconfig.xml",
+    "humans.txt",
+    "ads.txt",
+    "security.txt",
+];
+
+/// Directory whose files are served at URLs other sites expect; the only
+/// dot directory a static build includes.
+const WELL_KNOWN: &str = ".well-known";
+
+/// Files whose references to other files are rewritten when fingerprinting.
+const TEXT_ASSETS: &[&str] = &["css", "js", "mjs"];
+
+/// Options for [`build`].
+#[derive(Debug, Clone, Copy)]
+pub struct StaticOptions<'a> {
+    /// Site name, available to templates as `site.name`.
+    pub name: &'a str,
+    pub mode: &'a str,
+    /// Minify HTML, CSS and JS.
+    pub minify: bool,
+    /// Rename assets to `name.<hash>.ext` and rewrite references to them.
+    pub fingerprint: bool,
+    /// Public variables, available to templates as `env` and inlined in scripts.
+    pub env: &'a ProjectEnv,
+    /// Routes rendered from pages with `[param]` segments, such as
+    /// `/blog/hello/` from `blog/[slug].html`.
+    pub routes: &'a [String],
+}
+
+impl<'a> StaticOptions<'a> {
+    /// Production builds are minified and fingerprinted; other modes are not.
+    pub fn for_mode(name: &'a str, mode: &'a str, env: &'a ProjectEnv) -> Self {
+        let production = mode == "production";
+        Self {
+            name,
+            mode,
+            minify: production,
+            fingerprint: production,
+            env,
+            routes: &[],
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct PageContext<'a> {
+    site: SiteContext<'a>,
+    page: PageInfo,
+    env: &'a BTreeMap<String, String>,
+}
+
+#[derive(Serialize)]
+struct SiteContext<'a> {
+    name: &'a str,
+    mode: &'a str,
+}
+
+#[derive(Serialize)]
+struct PageInfo {
+    /// Output path relative to the site root, e.g. `about/index.html`.
+    path: String,
+    /// URL path the page is served at, e.g. `/about/`.
+    route: String,
+    /// Values of the `[param]` segments of a route template.
+    params: BTreeMap<String, String>,
+}
+
+/// Builds a site without Node: renders the pages in `src/` (or the project
+/// root), processes its assets and copies `public/` verbatim into `output`.
+/// Files and folders starting with `_` are partials and layouts, available to
+/// Tera's `include`/`extends` by path and to Handlebars as partials, but not
+/// written. Pages with `[param]` segments are rendered once for each matching
+/// route in `options.routes`. Returns warnings.
+/// With a `cache`, minified files are reused from earlier builds.
+pub fn build(
+    root: &Path,
+    output: &Path,
+    options: StaticOptions,
+    cache: Option<&BuildCache>,
+) -> Result<Vec<String>> {
+    let source = match root.join("src") {
+        src if src.is_dir() => src,
+        _ => root.to_path_buf(),
+    };
+    let mut warnings = Vec::new();
+
+    let sources: BTreeMap<String, Vec<u8>> = collect(&source, output)?
+        .into_par_iter()
+        .map(|(relative, path)| {
+            let contents = std::fs::read(&path)
+                .with_context(|| format!("Failed to read {}", path.display()))?;
+            Ok((relative, contents))
+        })
+        .collect::<Result<_>>()?;
+
+    let is_partial = |path: &str| path.split('/').any(|segment| segment.starts_with('_'));
+    let is_page = |path: &str| path.ends_with(".html") || path.ends_with(".hbs");
+    let templates = Templates::new(&sources, is_partial)?;
+
+    // Each page with the output path and parameters it is rendered with.
+    let mut renders = Vec::new();
+    for (path, contents) in sources.iter().filter(|(path, _)| is_page(path) && !is_partial(path)) {
+        let output_path = match path.strip_suffix(".hbs") {
+            Some(stem) => format!("{}.html", stem),
+            None => path.clone(),
+        };
+        if !ssg::is_route_template(path) {
+            renders.push((path, contents, output_path, BTreeMap::new()));
+            continue;
+        }
+        for route in options.routes {
+            if let Some(params) = ssg::match_route(&output_path, route) {
+                renders.push((path, contents, ssg::route_file(route), params));
+            }
+        }
+    }
+
+    // Pages are rendered first; assets keep their bytes.
+    let mut pages: BTreeMap<String, String> = renders
+        .into_par_iter()
+        .map(|(path, contents, output_path, params)| {
+            let context = PageContext {
+                site: SiteContext { name: options.name, mode: options.mode },
+                page: PageInfo { route: route_of(&output_path), path: output_path.clone(), params },
+                env: &options.env.public,
+            };
+            let html = options.env.inline_scripts(&templates.render(path, contents, &context)?);
+            Ok((output_path, html))
+        })
+        .collect::<Result<_>>()?;
+    let mut assets: BTreeMap<String, Vec<u8>> = sources
+        .into_iter()
+        .filter(|(path, _)| !is_page(path) && !is_partial(path))
+        .collect();
+    // Before fingerprinting, so a changed value changes the file name.
+    assets.par_iter_mut().for_each(|(path, contents)| {
+        if let ("js" | "mjs", Ok(js)) = (extension(path), std::str::from_utf8(contents)) {
+            *contents = options.env.inline(js).into_bytes();
+        }
+    });
+
+    if options.minify {
+        pages
+            .par_iter_mut()
+            .for_each(|(_, html)| *html = cached(cache, "html", html, minify_html));
+        assets.par_iter_mut().for_each(|(path, contents)| {
+            let minified = match (extension(path), std::str::from_utf8(contents)) {
+                ("css", Ok(css)) => cached(cache, "css", css, minify_css),
+                ("js" | "mjs", Ok(js)) => cached(cache, "js", js, minify_js),
+                _ => return,
+            };
+            *contents = minified.into_bytes();
+        });
+    }
+
+    let names = if options.fingerprint {
+        let mut fingerprinter = Fingerprinter::new(&mut assets);
+        fingerprinter.run();
+        let names = fingerprinter.names;
+        pages
+            .par_iter_mut()
+            .for_each(|(path, html)| *html = rewrite_references(html, path, &names));
+        names
+    } else {
+        HashMap::new()
+    };
+
+    if output.exists() {
+        std::fs::remove_dir_all(output)
+            .with_context(|| format!("Failed to clear {}", output.display()))?;
+    }
+    let mut files: Vec<(String, Vec<u8>)> = pages
+        .into_iter()
+        .map(|(path, html)| (path, html.into_bytes()))
+        .collect();
+    files.extend(assets.into_iter().map(|(path, contents)| {
+        let name = names.get(&path).cloned().unwrap_or(path);
+        (name, contents)
+    }));
+
+    let public = root.join("public");
+    let mut public_files = Vec::new();
+    if public.is_dir() {
+        let written: HashSet<&str> = files.iter().map(|(path, _)| path.as_str()).collect();
+        for (relative, path) in collect(&public, output)? {
+            if written.contains(relative.as_str()) {
+                warnings.push(format!("public/{} replaces the built {}", relative, relative));
+            }
+            public_files.push((relative, path));
+        }
+    }
+
+    files.par_iter().try_for_each(|(path, contents)| {
+        write(&output.join(path), contents)
+    })?;
+    public_files.par_iter().try_for_each(|(relative, path)| {
+        let destination = output.join(relative);
+        if let Some(parent) = destination.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::copy(path, &destination)
+            .with_context(|| format!("Failed to copy {}", path.display()))?;
+        Ok::<_, anyhow::Error>(())
+    })?;
+
+    // Only minified files are cached, so other modes leave them for the next
+    // production build.
+    if let Some(cache) = cache.filter(|_| options.minify) {
+        if let Err(e) = cache.prune_files() {
+            warnings.push(format!("Could not prune the build cache: {:#}", e));
+        }
+    }
+    Ok(warnings)
+}
+
+/// `transform(input)`, from the cache when an earlier build stored it.
+fn cached(cache: Option<&BuildCache>, kind: &str, input: &str, transform: fn(&str) -> String) -> String {
+    let Some(cache) = cache else {
+        return transform(input);
+    };
+    let key = key_of(&[env!("CARGO_PKG_VERSION").as_bytes(), kind.as_bytes(), input.as_bytes()]);
+    if let Some(output) = cache.get(&key).and_then(|bytes| String::from_utf8(bytes).ok()) {
+        return output;
+    }
+    let output = transform(input);
+    // The cache only saves time; a failed write is not an error.
+    let _ = cache.put(&key, output.as_bytes());
+    output
+}
+
+/// Files under `dir` keyed by their `/`-separated relative path, skipping
+/// dotfiles other than `.well-known`, dependencies, project config and the output directory.
+fn collect(dir: &Path, output: &Path) -> Result<Vec<(String, PathBuf)>> {
+    let mut files = Vec::new();
+    let entries = WalkDir::new(dir).into_iter().filter_entry(|entry| {
+        let name = entry.file_name().to_string_lossy();
+        entry.depth() == 0
+            || !((name.starts_with('.') && name != WELL_KNOWN)
+                || STATIC_EXCLUDES.contains(&name.as_ref())
+                || entry.path() == output)
+    });
+    for entry in entries {
+        let entry = entry?;
+        if entry.file_type().is_file() {
+            let relative = entry.path().strip_prefix(dir)?;
+            let relative = relative
+                .components()
+                .map(|part| part.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            files.push((relative, entry.into_path()));
+        }
+    }
+    Ok(files)
+}
+
+fn write(path: &Path, contents: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, contents).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+fn extension(path: &str) -> &str {
+    path.rsplit_once('.').map(|(_, extension)| extension).unwrap_or("")
+}
+
+/// Tera templates (`.html`) and Handlebars partials (`.hbs` and `_` files)
+/// for the whole site, so pages can include and extend each other.
+struct Templates {
+    tera: tera::Tera,
+    handlebars: Handlebars<'static>,
+}
+
+impl Templates {
+    fn new(sources: &BTreeMap<String, Vec<u8>>, is_partial: impl Fn(&str) -> bool) -> Result<Self> {
+        let mut tera = tera::Tera::default();
+        tera.autoescape_on(Vec::new());
+        let mut handlebars = Handlebars::new();
+        handlebars.register_escape_fn(handlebars::no_escape);
+
+        let mut raw = Vec::new();
+        for (path, contents) in sources {
+            let Ok(text) = std::str::from_utf8(contents) else { continue };
+            if path.ends_with(".html") && (is_partial(path) || has_tera_syntax(text)) {
+                raw.push((path.clone(), text.to_string()));
+            }
+            if is_partial(path) && (path.ends_with(".hbs") || path.ends_with(".html")) {
+                // `{{> _partials/header}}` and `{{> header}}` both work.
+                let without_extension = &path[..path.rfind('.').unwrap_or(path.len())];
+                let stem = without_extension.rsplit('/').next().unwrap_or(without_extension);
+                for name in [without_extension, stem.trim_start_matches('_')] {
+                    handlebars
+                        .register_partial(name, text)
+                        .with_context(|| format!("Failed to parse partial {}", path))?;
+                }
+            }
+        }
+        tera.add_raw_templates(raw)
+            .context("Failed to parse the site's Tera templates")?;
+        Ok(Self { tera, handlebars })
+    }
+
+    fn render(&self, path: &str, contents: &[u8], context: &PageContext) -> Result<String> {
+        let text = std::str::from_utf8(contents).with_context(|| format!("{} is not UTF-8", path))?;
+        if path.ends_with(".hbs") {
+            return self
+                .handlebars
+                .render_template(text, context)
+                .with_context(|| format!("Failed to render {}", path));
+        }
+        if !has_tera_syntax(text) {
+            return Ok(text.to_string());
+        }
+        let context = tera::Context::from_serialize(context)?;
+        self.tera
+            .render(path, &context)
+            .with_context(|| format!("Failed to render {}", path))
+    }
+}
+
+fn has_tera_syntax(text: &str) -> bool {
+    text.contains("{{") || text.contains("{%")
+}
+
+/// Renames assets after their content hash, in dependency order, so a
+/// stylesheet's hash covers the hashed names of the images it uses. Files in
+/// an import cycle keep the hash of their original content.
+struct Fingerprinter<'a> {
+    assets: &'a mut BTreeMap<String, Vec<u8>>,
+    /// Original path to fingerprinted path.
+    names: HashMap<String, String>,
+    done: HashSet<String>,
+    in_progress: HashSet<String>,
+    pinned: HashSet<String>,
+}
+
+impl<'a> Fingerprinter<'a> {
+    fn new(assets: &'a mut BTreeMap<String, Vec<u8>>) -> Self {
+        Self {
+            assets,
+            names: HashMap::new(),
+            done: HashSet::new(),
+            in_progress: HashSet::new(),
+            pinned: HashSet::new(),
+        }
+    }
+
+    fn run(&mut self) {
+        let paths: Vec<String> = self.assets.keys().cloned().collect();
+        for path in paths {
+            self.visit(&path);
+        }
+    }
+
+    fn visit(&mut self, path: &str) {
+        if self.done.contains(path) || self.in_progress.contains(path) {
+            return;
+        }
+        self.in_progress.insert(path.to_string());
+
+        let text = TEXT_ASSETS.contains(&extension(path))
+            .then(|| String::from_utf8(self.assets[path].clone()).ok())
+            .flatten();
+        if let Some(text) = &text {
+            for dependency in references(text, path) {
+                if !self.assets.contains_key(&dependency) || dependency == path {
+                    continue;
+                }
+                if self.in_progress.contains(&dependency) {
+                    if has_fixed_name(&dependency) {
+                        continue;
+                    }
+                    // A cycle: the dependency is named before its own
+                    // references are rewritten.
+                    let name = hashed_name(&dependency, &self.assets[&dependency]);
+                    self.names.insert(dependency.clone(), name);
+                    self.pinned.insert(dependency);
+                } else {
+                    self.visit(&dependency);
+                }
+            }
+            let rewritten = rewrite_references(text, path, &self.names);
+            self.assets.insert(path.to_string(), rewritten.into_bytes());
+        }
+
+        if !self.pinned.contains(path) && !has_fixed_name(path) {
+            let name = hashed_name(path, &self.assets[path]);
+            self.names.insert(path.to_string(), name);
+        }
+        self.in_progress.remove(path);
+        self.done.insert(path.to_string());
+    }
+}
+
+/// Whether `path` must keep its name; its references are still rewritten.
+fn has_fixed_name(path: &str) -> bool {
+    let file = path.rsplit('/').next().unwrap_or(path);
+    path.split('/').next() == Some(WELL_KNOWN) || FIXED_NAMES.contains(&file)
+}
+
+/// `css/app.css` becomes `css/app.1a2b3c4d.css`.
+fn hashed_name(path: &str, contents: &[u8]) -> String {
+    let short = content_hash(contents);
+    match path.rsplit_once('.') {
+        Some((stem, extension)) if !stem.ends_with('/') => {
+            format!("{}.{}.{}", stem, &short[..8], extension)
+        }
+        _ => format!("{}.{}", path, &short[..8]),
+    }
+}
+
+/// Site paths referenced from `file`: quoted strings and CSS `url()` values
+/// that resolve to a file, relative to `file` or to the site root.
+fn references(text: &str, file: &str) -> Vec<String> {
+    reference_spans(text)
+        .into_iter()
+        .filter_map(|(start, end)| resolve(file, &text[start..end]))
+        .collect()
+}
+
+/// Replaces references to fingerprinted files in `text` with their new names.
+fn rewrite_references(text: &str, file: &str, names: &HashMap<String, String>) -> String {
+    if names.is_empty() {
+        return text.to_string();
+    }
+    let mut output = String::with_capacity(text.len());
+    let mut last = 0;
+    for (start, end) in reference_spans(text) {
+        let reference = &text[start..end];
+        let Some(renamed) = resolve(file, reference).and_then(|path| names.get(&path)) else {
+            continue;
+        };
+        // Keep the reference's own directory part and any query or fragment.
+        let path_end = reference.find(['?', '#']).unwrap_or(reference.len());
+        let directory_end = reference[..path_end].rfind('/').map(|i| i + 1).unwrap_or(0);
+        let file_name = renamed.rsplit('/').next().unwrap_or(renamed);
+        output.push_str(&text[last..start]);
+        output.push_str(&reference[..directory_end]);
+        output.push_str(file_name);
+        output.push_str(&reference[path_end..]);
+        last = end;
+    }
+    output.push_str(&text[last..]);
+    output
+}
+
+/// Byte ranges of single-line quoted strings and unquoted `url(...)` values.
+fn reference_spans(text: &str) -> Vec<(usize, usize)> {
+    let bytes = text.as_bytes();
+    let mut spans = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            quote @ (b'"' | b'\'' | b'`') => {
+                let start = i + 1;
+                let end = bytes[start..]
+                    .iter()
+                    .position(|&b| b == quote || b == b'\n')
+                    .map(|offset| start + offset);
+                match end {
+                    Some(end) if bytes[end] == quote => {
+                        spans.push((start, end));
+                        i = end + 1;
+                    }
+                    _ => i += 1,
+                }
+            }
+            b'u' if text[i..].starts_with("url(") => {
+                let start = i + 4;
+                match bytes.get(start) {
+                    Some(b'"' | b'\'') | None => i = start,
+                    Some(_) => {
+                        let end = text[start..].find(')').map(|offset| start + offset);
+                        match end {
+                            Some(end) => {
+                                spans.push((start, end));
+                                i = end + 1;
+                            }
+                            None => i = start,
+                        }
+                    }
+                }
+            }
+            _ => i += 1,
+        }
+    }
+    spans
+}
+
+/// The site path `reference` points to from `file`, if it is a local path.
+fn resolve(file: &str, reference: &str) -> Option<String> {
+    let reference = reference.trim();
+    let path = &reference[..reference.find(['?', '#']).unwrap_or(reference.len())];
+    if path.is_empty()
+        || path.contains("://")
+        || path.starts_with("//")
+        || path.starts_with("data:")
+        || path.contains(char::is_whitespace)
+        || !path.contains('.')
+    {
+        return None;
+    }
+
+    let mut segments: Vec<&str> = match path.strip_prefix('/') {
+        Some(_) => Vec::new(),
+        None => file.split('/').collect::<Vec<_>>().split_last().map(|(_, dir)| dir.to_vec())?,
+    };
+    for segment in path.trim_start_matches('/').split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop()?;
+            }
+            segment => segments.push(segment),
+        }
+    }
+    Some(segments.join("/"))
+}
+
+/// Removes comments and collapses whitespace outside `pre`, `textarea`,
+/// `script` and `style`; inline scripts and styles are minified as JS and CSS.
+fn minify_html(html: &str) -> String {
+    let lower = html.to_ascii_lowercase();
+    let mut output = String::with_capacity(html.len());
+    let mut i = 0;
+    while i < html.len() {
+        let next_raw = ["<pre", "<textarea", "<script", "<style"]
+            .iter()
+            .filter_map(|tag| lower[i..].find(tag).map(|offset| (i + offset, *tag)))
+            .min_by_key(|(position, _)| *position);
+        let text_end = next_raw.map(|(position, _)| position).unwrap_or(html.len());
+        output.push_str(&collapse_html(&html[i..text_end]));
+        let Some((start, tag)) = next_raw else { break };
+
+        let name = &tag[1..];
+        let open_end = lower[start..].find('>').map(|offset| start + offset + 1).unwrap_or(html.len());
+        let close = format!("</{}", name);
+        let close_start = lower[open_end..].find(&close).map(|offset| open_end + offset).unwrap_or(html.len());
+        let opening = &html[start..open_end];
+        let body = &html[open_end..close_start];
+        output.push_str(opening);
+        let opening_lower = &lower[start..open_end];
+        match name {
+            "style" => output.push_str(&minify_css(body)),
+            "script" if is_javascript(opening_lower) => output.push_str(&minify_js(body)),
+            _ => output.push_str(body),
+        }
+        i = close_start;
+        if i < html.len() {
+            let close_end = lower[i..].find('>').map(|offset| i + offset + 1).unwrap_or(html.len());
+            output.push_str(&html[i..close_end]);
+            i = close_end;
+        }
+    }
+    output.trim().to_string()
+}
+
+fn is_javascript(opening_tag: &str) -> bool {
+    match opening_tag.find("type=") {
+        None => true,
+        Some(position) => {
+            let value = &opening_tag[position + 5..];
+            ["\"module\"", "'module'", "\"text/javascript\"", "module"]
+                .iter()
+                .any(|kind| value.starts_with(kind))
+        }
+    }
+}
+
+fn collapse_html(text: &str) -> String {
+    let mut without_comments = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("<!--") {
+        without_comments.push_str(&rest[..start]);
+        let comment = &rest[start..];
+        let end = comment.find("-->").map(|end| end + 3).unwrap_or(comment.len());
+        // Conditional comments still mean something to old browsers.
+        if comment.starts_with("<!--[if") {
+            without_comments.push_str(&comment[..end]);
+        }
+        rest = &comment[end..];
+    }
+    without_comments.push_str(rest);
+    collapse_whitespace(&without_comments)
+}
+
+/// Whitespace runs become one newline if they contain one, else one space.
+fn collapse_whitespace(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut run: Option<bool> = None;
+    for c in text.chars() {
+        if c.is_whitespace() {
+            run = Some(run.unwrap_or(false) || c == '\n');
+            continue;
+        }
+        if let Some(newline) = run.take() {
+            output.push(if newline { '\n' } else { ' ' });
+        }
+        output.push(c);
+    }
+    if let Some(newline) = run {
+        output.push(if newline { '\n' } else { ' ' });
+    }
+    output
+}
+
+/// Removes comments (except `/*! ... */`) and the whitespace CSS does not need.
+fn minify_css(css: &str) -> String {
+    const TIGHT: &str = "{};,>";
+    let chars: Vec<char> = css.chars().collect();
+    let mut output = String::with_capacity(css.len());
+    let mut pending_space = false;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '/' && chars.get(i + 1) == Some(&'*') {
+            let end = find_sequence(&chars, i + 2, &['*', '/']).map(|end| end + 2).unwrap_or(chars.len());
+            if chars.get(i + 2) == Some(&'!') {
+                output.extend(&chars[i..end]);
+            } else {
+                pending_space = true;
+            }
+            i = end;
+            continue;
+        }
+        if c.is_whitespace() {
+            pending_space = true;
+            i += 1;
+            continue;
+        }
+        if pending_space {
+            let previous = output.chars().last();
+            if previous.is_some_and(|p| !TIGHT.contains(p)) && !TIGHT.contains(c) {
+                output.push(' ');
+            }
+            pending_space = false;
+        }
+        if c == '"' || c == '\'' {
+            let end = skip_string(&chars, i);
+            output.extend(&chars[i..end]);
+            i = end;
+            continue;
+        }
+        if c == '}' && output.ends_with(';') {
+            output.pop();
+        }
+        output.push(c);
+        i += 1;
+    }
+    output
+}
+
+/// Removes comments (except `/*! ... */`) and indentation from JavaScript.
+/// Line breaks are kept where automatic semicolon insertion might need them.
+fn minify_js(js: &str) -> String {
+    let chars: Vec<char> = js.chars().collect();
+    let mut output = String::with_capacity(js.len());
+    let mut pending: Option<bool> = None;
+    // Brace depth inside each `${` of the enclosing template literals.
+    let mut templates: Vec<usize> = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+
+        if c == '/' && next == Some('/') {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+            continue;
+        }
+        if c == '/' && next == Some('*') {
+            let end = find_sequence(&chars, i + 2, &['*', '/']).map(|end| end + 2).unwrap_or(chars.len());
+            if chars.get(i + 2) == Some(&'!') {
+                flush_whitespace(&mut output, &mut pending, c);
+                output.extend(&chars[i..end]);
+                output.push('\n');
+            } else {
+                let newline = chars[i..end].contains(&'\n');
+                pending = Some(pending.unwrap_or(false) || newline);
+            }
+            i = end;
+            continue;
+        }
+        if c.is_whitespace() {
+            pending = Some(pending.unwrap_or(false) || c == '\n');
+            i += 1;
+            continue;
+        }
+
+        flush_whitespace(&mut output, &mut pending, c);
+        match c {
+            '"' | '\'' => {
+                let end = skip_string(&chars, i);
+                output.extend(&chars[i..end]);
+                i = end;
+            }
+            '`' => {
+                output.push(c);
+                i = copy_template(&chars, i + 1, &mut output, &mut templates);
+            }
+            '/' if starts_regex(&output) => {
+                let end = skip_regex(&chars, i);
+                output.extend(&chars[i..end]);
+                i = end;
+            }
+            '{' => {
+                if let Some(depth) = templates.last_mut() {
+                    *depth += 1;
+                }
+                output.push(c);
+                i += 1;
+            }
+            '}' => {
+                match templates.last_mut() {
+                    Some(0) => {
+                        // The end of a `${...}`: back inside the template.
+                        templates.pop();
+                        output.push(c);
+                        i = copy_template(&chars, i + 1, &mut output, &mut templates);
+                        continue;
+                    }
+                    Some(depth) => *depth -= 1,
+                    None => {}
+                }
+                output.push(c);
+                i += 1;
+            }
+            _ => {
+                output.push(c);
+                i += 1;
+            }
+        }
+    }
+    output.trim().to_string()
+}
+
+/// Writes the whitespace before `next`, if it is needed at all.
+fn flush_whitespace(output: &mut String, pending: &mut Option<bool>, next: char) {
+    const TIGHT: &str = "{}()[];,=:<>?!&|*%^~";
+    let Some(newline) = pending.take() else { return };
+    let Some(previous) = output.chars().last() else { return };
+    if newline {
+        // A newline after these can never end a statement.
+        if !"{;,([".contains(previous) && !"})],;".contains(next) {
+            output.push('\n');
+        }
+    } else if !TIGHT.contains(previous) && !TIGHT.contains(next) {
+        output.push(' ');
+    }
+}
+
+/// Copies a template literal from just after its opening backtick (or the
+/// `}` of a substitution) up to and including the closing backtick or the
+/// next `${`. Returns the index to continue from.
+fn copy_template(chars: &[char], mut i: usize, output: &mut String, templates: &mut Vec<usize>) -> usize {
+    while i < chars.len() {
+        let c = chars[i];
+        output.push(c);
+        match c {
+            '\\' => {
+                if let Some(escaped) = chars.get(i + 1) {
+                    output.push(*escaped);
+                }
+                i += 2;
+            }
+            '`' => return i + 1,
+            '$' if chars.get(i + 1) == Some(&'{') => {
+                output.push('{');
+                templates.push(0);
+                return i + 2;
+            }
+            _ => i += 1,
+        }
+    }
+    i
+}
+
+/// A `/` starts a regular expression where a value is expected.
+fn starts_regex(output: &str) -> bool {
+    let trimmed = output.trim_end();
+    match trimmed.chars().last() {
+        None => true,
+        Some(previous) if "(,=:[!&|?{};+-*%<>~^".contains(previous) => true,
+        Some(_) => ["return", "typeof", "case", "do", "else", "in", "of", "void", "yield"]
+            .iter()
+            .any(|keyword| {
+                trimmed.ends_with(keyword)
+                    && !trimmed[..trimmed.len() - keyword.len()]
+                        .ends_with(|c: char| c.is_alphanumeric() || c == '_' || c == '$')
+            }),
+    }
+}
+
+fn skip_regex(chars: &[char], start: usize) -> usize {
+    let mut i = start + 1;
+    let mut in_class = false;
+    while i < chars.len() && chars[i] != '\n' {
+        match chars[i] {
+            '\\' => i += 1,
+            '[' => in_class = true,
+            ']' => in_class = false,
+            '/' if !in_class => {
+                i += 1;
+                while i < chars.len() && chars[i].is_ascii_alphabetic() {
+                    i += 1;
+                }
+                return i;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    i.min(chars.len())
+}
+
+/// Index just past the string starting at `start`.
+fn skip_string(chars: &[char], start: usize) -> usize {
+    let quote = chars[start];
+    let mut i = start + 1;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' => i += 2,
+            c if c == quote => return i + 1,
+            '\n' => return i,
+            _ => i += 1,
+        }
+    }
+    chars.len()
+}
+
+fn find_sequence(chars: &[char], from: usize, sequence: &[char]) -> Option<usize> {
+    (from..chars.len()).find(|&i| chars[i..].starts_with(sequence))
+}
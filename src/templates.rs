@@ -0,0 +1,629 @@
+use crate::schema::ProjectConfig;
+use crate::recipes::FilePatch;
+use crate::registry::TemplateRegistry;
+use anyhow::{Context, Result};
+use handlebars::Handlebars;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use walkdir::WalkDir;
+
+/// Files ending in `.tera` are rendered with Tera, files ending in `.hbs` with
+/// Handlebars. Everything else is copied verbatim.
+const TERA_SUFFIX: &str = ".tera";
+const HANDLEBARS_SUFFIX: &str = ".hbs";
+
+/// Every template directory carries a manifest describing it.
+pub const MANIFEST_FILE: &str = "template.yaml";
+
+/// Files under `frameworks/<framework>/` in a template are only generated for
+/// that framework, with the prefix stripped.
+const FRAMEWORK_OVERLAY_DIR: &str = "frameworks/";
+
+pub type BuiltinFile = (&'static str, &'static str);
+
+const DEFAULT_FILES: &[BuiltinFile] = &[
+    ("README.md.tera", include_str!("../templates/projects/default/README.md.tera")),
+];
+
+const FULLSTACK_FILES: &[BuiltinFile] = &[
+    ("README.md.tera", include_str!("../templates/projects/fullstack/README.md.tera")),
+    (".env.example.tera", include_str!("../templates/projects/fullstack/.env.example.tera")),
+    ("server/index.js.tera", include_str!("../templates/projects/fullstack/server/index.js.tera")),
+    ("server/package.json", include_str!("../templates/projects/fullstack/server/package.json")),
+    ("server/routes/health.js", include_str!("../templates/projects/fullstack/server/routes/health.js")),
+    ("frameworks/react-ts/src/App.tsx.tera", include_str!("../templates/projects/fullstack/frameworks/react-ts/src/App.tsx.tera")),
+    ("frameworks/react/src/App.jsx.tera", include_str!("../templates/projects/fullstack/frameworks/react/src/App.jsx.tera")),
+];
+
+const DASHBOARD_FILES: &[BuiltinFile] = &[
+    ("README.md.tera", include_str!("../templates/projects/dashboard/README.md.tera")),
+    ("src/styles/dashboard.css", include_str!("../templates/projects/dashboard/src/styles/dashboard.css")),
+    ("frameworks/react-ts/src/App.tsx.tera", include_str!("../templates/projects/dashboard/frameworks/react-ts/src/App.tsx.tera")),
+    ("frameworks/react/src/App.jsx.tera", include_str!("../templates/projects/dashboard/frameworks/react/src/App.jsx.tera")),
+    ("frameworks/react-ts/src/components/Sidebar.tsx", include_str!("../templates/projects/dashboard/frameworks/react-ts/src/components/Sidebar.tsx")),
+    ("frameworks/react-ts/src/components/StatCard.tsx", include_str!("../templates/projects/dashboard/frameworks/react-ts/src/components/StatCard.tsx")),
+    ("frameworks/react/src/components/Sidebar.jsx", include_str!("../templates/projects/dashboard/frameworks/react/src/components/Sidebar.jsx")),
+    ("frameworks/react/src/components/StatCard.jsx", include_str!("../templates/projects/dashboard/frameworks/react/src/components/StatCard.jsx")),
+];
+
+pub struct BuiltinTemplate {
+    pub name: &'static str,
+    pub manifest: &'static str,
+    pub files: &'static [BuiltinFile],
+}
+
+pub const BUILTIN_TEMPLATES: &[BuiltinTemplate] = &[
+    BuiltinTemplate {
+        name: "default",
+        manifest: include_str!("../templates/projects/default/template.yaml"),
+        files: DEFAULT_FILES,
+    },
+    BuiltinTemplate {
+        name: "fullstack",
+        manifest: include_str!("../templates/projects/fullstack/template.yaml"),
+        files: FULLSTACK_FILES,
+    },
+    BuiltinTemplate {
+        name: "dashboard",
+        manifest: include_str!("../templates/projects/dashboard/template.yaml"),
+        files: DASHBOARD_FILES,
+    },
+];
+
+const REACT_PACK: &[BuiltinFile] = &[
+    (".gitignore", include_str!("../templates/frameworks/react/gitignore")),
+    ("index.html.tera", include_str!("../templates/frameworks/react/index.html.tera")),
+    ("package.json.tera", include_str!("../templates/frameworks/react/package.json.tera")),
+    ("vite.config.js", include_str!("../templates/frameworks/react/vite.config.js")),
+    ("src/App.jsx.tera", include_str!("../templates/frameworks/react/src/App.jsx.tera")),
+    ("src/index.css", include_str!("../templates/frameworks/react/src/index.css")),
+    ("src/main.jsx", include_str!("../templates/frameworks/react/src/main.jsx")),
+];
+
+const REACT_TS_PACK: &[BuiltinFile] = &[
+    (".gitignore", include_str!("../templates/frameworks/react-ts/gitignore")),
+    ("index.html.tera", include_str!("../templates/frameworks/react-ts/index.html.tera")),
+    ("package.json.tera", include_str!("../templates/frameworks/react-ts/package.json.tera")),
+    ("tsconfig.json", include_str!("../templates/frameworks/react-ts/tsconfig.json")),
+    ("vite.config.ts", include_str!("../templates/frameworks/react-ts/vite.config.ts")),
+    ("src/App.tsx.tera", include_str!("../templates/frameworks/react-ts/src/App.tsx.tera")),
+    ("src/index.css", include_str!("../templates/frameworks/react-ts/src/index.css")),
+    ("src/main.tsx", include_str!("../templates/frameworks/react-ts/src/main.tsx")),
+    ("src/vite-env.d.ts", include_str!("../templates/frameworks/react-ts/src/vite-env.d.ts")),
+];
+
+const VUE_PACK: &[BuiltinFile] = &[
+    (".gitignore", include_str!("../templates/frameworks/vue/gitignore")),
+    ("index.html.tera", include_str!("../templates/frameworks/vue/index.html.tera")),
+    ("package.json.tera", include_str!("../templates/frameworks/vue/package.json.tera")),
+    ("tsconfig.json", include_str!("../templates/frameworks/vue/tsconfig.json")),
+    ("vite.config.ts", include_str!("../templates/frameworks/vue/vite.config.ts")),
+    ("src/App.vue.tera", include_str!("../templates/frameworks/vue/src/App.vue.tera")),
+    ("src/env.d.ts", include_str!("../templates/frameworks/vue/src/env.d.ts")),
+    ("src/main.ts", include_str!("../templates/frameworks/vue/src/main.ts")),
+    ("src/style.css", include_str!("../templates/frameworks/vue/src/style.css")),
+];
+
+const SVELTE_PACK: &[BuiltinFile] = &[
+    (".gitignore", include_str!("../templates/frameworks/svelte/gitignore")),
+    ("package.json.tera", include_str!("../templates/frameworks/svelte/package.json.tera")),
+    ("svelte.config.js", include_str!("../templates/frameworks/svelte/svelte.config.js")),
+    ("vite.config.js", include_str!("../templates/frameworks/svelte/vite.config.js")),
+    ("src/app.html", include_str!("../templates/frameworks/svelte/src/app.html")),
+    ("src/routes/+layout.js", include_str!("../templates/frameworks/svelte/src/routes/+layout.js")),
+    ("src/routes/+page.svelte.tera", include_str!("../templates/frameworks/svelte/src/routes/+page.svelte.tera")),
+];
+
+const ANGULAR_PACK: &[BuiltinFile] = &[
+    (".gitignore", include_str!("../templates/frameworks/angular/gitignore")),
+    ("angular.json.tera", include_str!("../templates/frameworks/angular/angular.json.tera")),
+    ("package.json.tera", include_str!("../templates/frameworks/angular/package.json.tera")),
+    ("tsconfig.app.json", include_str!("../templates/frameworks/angular/tsconfig.app.json")),
+    ("tsconfig.json", include_str!("../templates/frameworks/angular/tsconfig.json")),
+    ("src/index.html.tera", include_str!("../templates/frameworks/angular/src/index.html.tera")),
+    ("src/main.ts", include_str!("../templates/frameworks/angular/src/main.ts")),
+    ("src/styles.css", include_str!("../templates/frameworks/angular/src/styles.css")),
+    ("src/app/app.component.ts.tera", include_str!("../templates/frameworks/angular/src/app/app.component.ts.tera")),
+    ("src/app/app.config.ts", include_str!("../templates/frameworks/angular/src/app/app.config.ts")),
+    ("src/app/app.routes.ts", include_str!("../templates/frameworks/angular/src/app/app.routes.ts")),
+];
+
+const NEXTJS_PACK: &[BuiltinFile] = &[
+    (".gitignore", include_str!("../templates/frameworks/nextjs/gitignore")),
+    ("next-env.d.ts", include_str!("../templates/frameworks/nextjs/next-env.d.ts")),
+    ("next.config.mjs", include_str!("../templates/frameworks/nextjs/next.config.mjs")),
+    ("package.json.tera", include_str!("../templates/frameworks/nextjs/package.json.tera")),
+    ("tsconfig.json", include_str!("../templates/frameworks/nextjs/tsconfig.json")),
+    ("app/globals.css", include_str!("../templates/frameworks/nextjs/app/globals.css")),
+    ("app/layout.tsx.tera", include_str!("../templates/frameworks/nextjs/app/layout.tsx.tera")),
+    ("app/page.tsx.tera", include_str!("../templates/frameworks/nextjs/app/page.tsx.tera")),
+];
+
+const HTML_PACK: &[BuiltinFile] = &[
+    (".gitignore", include_str!("../templates/frameworks/html/gitignore")),
+    ("src/index.html.tera", include_str!("../templates/frameworks/html/src/index.html.tera")),
+    ("src/main.js.tera", include_str!("../templates/frameworks/html/src/main.js.tera")),
+    ("src/styles.css", include_str!("../templates/frameworks/html/src/styles.css")),
+];
+
+/// Starter files for each supported framework, generated before the project
+/// template so templates only need to add what is specific to them.
+const FRAMEWORK_PACKS: &[(&str, &[BuiltinFile])] = &[
+    ("react", REACT_PACK),
+    ("react-ts", REACT_TS_PACK),
+    ("vue", VUE_PACK),
+    ("svelte", SVELTE_PACK),
+    ("angular", ANGULAR_PACK),
+    ("nextjs", NEXTJS_PACK),
+    ("html", HTML_PACK),
+];
+
+/// Canonical framework names accepted by `nexus new --framework`.
+pub const FRAMEWORKS: &[&str] = &["react", "react-ts", "vue", "svelte", "angular", "nextjs", "html"];
+
+/// Maps aliases used by the PowerShell TUI to canonical framework names.
+pub fn canonical_framework(name: &str) -> Result<&'static str> {
+    let lower = name.to_lowercase();
+    let canonical = match lower.as_str() {
+        "vue3" => "vue",
+        "sveltekit" => "svelte",
+        "next" => "nextjs",
+        "vanilla" | "static" => "html",
+        other => other,
+    };
+    FRAMEWORKS
+        .iter()
+        .copied()
+        .find(|framework| *framework == canonical)
+        .with_context(|| {
+            format!(
+                "Unknown framework '{}'. Available frameworks: {}",
+                name,
+                FRAMEWORKS.join(", ")
+            )
+        })
+}
+
+/// The component a framework starter renders first, relative to the project root.
+pub fn app_entry_file(framework: &str) -> &'static str {
+    match framework {
+        "react-ts" => "src/App.tsx",
+        "vue" => "src/App.vue",
+        "svelte" => "src/routes/+page.svelte",
+        "angular" => "src/app/app.component.ts",
+        "nextjs" => "app/page.tsx",
+        "html" => "src/main.js",
+        _ => "src/App.jsx",
+    }
+}
+
+/// Contents of a template's `template.yaml`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateManifest {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Frameworks the template works with. Empty means any framework.
+    #[serde(default)]
+    pub frameworks: Vec<String>,
+    #[serde(default)]
+    pub variables: Vec<TemplateVariable>,
+    /// npm packages added to package.json.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dependencies: Vec<DependencySpec>,
+    /// package.json scripts, added unless a script with the same name exists.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub scripts: BTreeMap<String, String>,
+    /// Edits to existing project files, applied by `nexus add`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub patches: Vec<FilePatch>,
+}
+
+/// An npm package a template or recipe needs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DependencySpec {
+    pub name: String,
+    pub version: String,
+    /// Goes to `devDependencies` instead of `dependencies`.
+    #[serde(default)]
+    pub dev: bool,
+    /// Frameworks that need the package. Empty means all of them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub frameworks: Vec<String>,
+}
+
+impl DependencySpec {
+    pub fn applies_to(&self, framework: &str) -> bool {
+        self.frameworks.is_empty() || self.frameworks.iter().any(|f| f == framework)
+    }
+}
+
+/// A variable the template expects in addition to the project variables.
+/// Variables without a default must be supplied with `--var key=value`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateVariable {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub default: Option<String>,
+}
+
+impl TemplateVariable {
+    pub fn is_required(&self) -> bool {
+        self.default.is_none()
+    }
+}
+
+impl TemplateManifest {
+    pub fn parse(contents: &str, origin: &str) -> Result<Self> {
+        let manifest: Self = serde_yaml::from_str(contents)
+            .with_context(|| format!("Invalid template manifest {}", origin))?;
+        if manifest.name.trim().is_empty() {
+            anyhow::bail!("Template manifest {} has an empty name", origin);
+        }
+        Ok(manifest)
+    }
+
+    pub fn load(dir: &Path) -> Result<Self> {
+        let path = dir.join(MANIFEST_FILE);
+        let contents = std::fs::read_to_string(&path)
+            .with_context(|| format!("Missing template manifest at {}", path.display()))?;
+        Self::parse(&contents, &path.display().to_string())
+    }
+
+    pub fn supports(&self, framework: &str) -> bool {
+        self.frameworks.is_empty() || self.frameworks.iter().any(|f| f == framework)
+    }
+}
+
+/// Variables available to every template file and file path.
+#[derive(Debug, Clone, Serialize)]
+pub struct TemplateVars {
+    pub name: String,
+    pub framework: String,
+    pub template: String,
+    pub author: String,
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, String>,
+}
+
+impl TemplateVars {
+    pub fn from_config(config: &ProjectConfig) -> Self {
+        Self {
+            name: config.name.clone(),
+            framework: config.framework.clone(),
+            template: config.template.clone(),
+            author: detect_author(),
+            extra: config.variables.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum TemplateSource {
+    Builtin(&'static [BuiltinFile]),
+    Directory(PathBuf),
+}
+
+#[derive(Debug, Clone)]
+pub struct RenderedFile {
+    pub path: String,
+    pub contents: String,
+}
+
+/// A file a project template produces. Only `.tera` and `.hbs` files are
+/// rendered; the rest keep their bytes, so images and fonts are copied as
+/// they are.
+#[derive(Debug, Clone)]
+pub struct TemplateFile {
+    pub path: String,
+    pub contents: Vec<u8>,
+}
+
+impl From<RenderedFile> for TemplateFile {
+    fn from(file: RenderedFile) -> Self {
+        Self {
+            path: file.path,
+            contents: file.contents.into_bytes(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ProjectTemplate {
+    pub manifest: TemplateManifest,
+    pub source: TemplateSource,
+}
+
+impl ProjectTemplate {
+    /// Resolves a template by name. A name with a path separator or a leading
+    /// `.` or `~` is a template directory; any other name is looked up among
+    /// installed and built-in templates, so a folder of the same name in the
+    /// current directory does not shadow them.
+    pub fn find(name: &str) -> Result<Self> {
+        if is_template_path(name) {
+            let path = expand_home(name);
+            if !path.is_dir() {
+                anyhow::bail!("Template directory {} not found", path.display());
+            }
+            return Self::from_dir(&path);
+        }
+
+        if let Some(template) = TemplateRegistry::open()?.get(name)? {
+            return Ok(template);
+        }
+
+        match Self::builtin(name) {
+            Some(template) => template,
+            None => {
+                let available: Vec<String> = Self::all()?
+                    .into_iter()
+                    .map(|template| template.manifest.name)
+                    .collect();
+                anyhow::bail!(
+                    "Unknown template '{}'. Available templates: {}",
+                    name,
+                    available.join(", ")
+                )
+            }
+        }
+    }
+
+    pub fn from_dir(dir: &Path) -> Result<Self> {
+        Ok(Self {
+            manifest: TemplateManifest::load(dir)?,
+            source: TemplateSource::Directory(dir.to_path_buf()),
+        })
+    }
+
+    pub fn builtin(name: &str) -> Option<Result<Self>> {
+        BUILTIN_TEMPLATES
+            .iter()
+            .find(|builtin| builtin.name == name)
+            .map(Self::from_builtin)
+    }
+
+    /// The starter pack for a canonical framework name.
+    pub fn framework_pack(framework: &str) -> Option<Self> {
+        FRAMEWORK_PACKS
+            .iter()
+            .find(|(name, _)| *name == framework)
+            .map(|(name, files)| Self {
+                manifest: TemplateManifest {
+                    name: name.to_string(),
+                    description: format!("{} starter files", name),
+                    frameworks: vec![name.to_string()],
+                    variables: Vec::new(),
+                    dependencies: Vec::new(),
+                    scripts: BTreeMap::new(),
+                    patches: Vec::new(),
+                },
+                source: TemplateSource::Builtin(files),
+            })
+    }
+
+    pub fn from_builtin(builtin: &BuiltinTemplate) -> Result<Self> {
+        Ok(Self {
+            manifest: TemplateManifest::parse(builtin.manifest, builtin.name)?,
+            source: TemplateSource::Builtin(builtin.files),
+        })
+    }
+
+    /// Built-in templates followed by the ones installed in the registry.
+    pub fn all() -> Result<Vec<Self>> {
+        let mut templates = BUILTIN_TEMPLATES
+            .iter()
+            .map(Self::from_builtin)
+            .collect::<Result<Vec<_>>>()?;
+        templates.extend(TemplateRegistry::open()?.list()?);
+        Ok(templates)
+    }
+
+    pub fn is_builtin(&self) -> bool {
+        matches!(self.source, TemplateSource::Builtin(_))
+    }
+
+    /// Relative paths of the files the template produces, before rendering.
+    pub fn file_names(&self) -> Result<Vec<String>> {
+        match &self.source {
+            TemplateSource::Builtin(files) => {
+                Ok(files.iter().map(|(path, _)| path.to_string()).collect())
+            }
+            TemplateSource::Directory(dir) => Ok(template_files(dir)?
+                .into_iter()
+                .map(|(relative, _)| relative)
+                .collect()),
+        }
+    }
+
+    /// The output paths the template produces for a framework, with overlay
+    /// files for other frameworks left out.
+    pub fn files_for(&self, framework: &str) -> Result<Vec<String>> {
+        Ok(self
+            .file_names()?
+            .iter()
+            .filter_map(|path| select_for_framework(path, framework))
+            .map(|path| {
+                path.strip_suffix(TERA_SUFFIX)
+                    .or_else(|| path.strip_suffix(HANDLEBARS_SUFFIX))
+                    .unwrap_or(path)
+            })
+            .map(str::to_string)
+            .collect())
+    }
+
+    /// Checks the framework is supported and fills in variable defaults,
+    /// failing if a required variable was not supplied.
+    pub fn resolve_variables(
+        &self,
+        framework: &str,
+        supplied: &BTreeMap<String, String>,
+    ) -> Result<BTreeMap<String, String>> {
+        if !self.manifest.supports(framework) {
+            anyhow::bail!(
+                "Template '{}' does not support framework '{}'. Supported: {}",
+                self.manifest.name,
+                framework,
+                self.manifest.frameworks.join(", ")
+            );
+        }
+
+        let mut resolved = supplied.clone();
+        let mut missing = Vec::new();
+        for variable in &self.manifest.variables {
+            if resolved.contains_key(&variable.name) {
+                continue;
+            }
+            match &variable.default {
+                Some(default) => {
+                    resolved.insert(variable.name.clone(), default.clone());
+                }
+                None => missing.push(variable.name.as_str()),
+            }
+        }
+
+        if !missing.is_empty() {
+            anyhow::bail!(
+                "Template '{}' requires variables: {}. Pass them with --var key=value",
+                self.manifest.name,
+                missing.join(", ")
+            );
+        }
+        Ok(resolved)
+    }
+
+    pub fn render(&self, vars: &TemplateVars) -> Result<Vec<TemplateFile>> {
+        let mut rendered = Vec::new();
+        match &self.source {
+            TemplateSource::Builtin(files) => {
+                for (path, contents) in files.iter() {
+                    if let Some(path) = select_for_framework(path, &vars.framework) {
+                        rendered.push(render_file(path, contents, vars)?.into());
+                    }
+                }
+            }
+            TemplateSource::Directory(dir) => {
+                for (relative, path) in template_files(dir)? {
+                    let Some(relative) = select_for_framework(&relative, &vars.framework) else {
+                        continue;
+                    };
+                    let contents = std::fs::read(&path).with_context(|| {
+                        format!("Failed to read template file {}", path.display())
+                    })?;
+                    if !is_template(relative) {
+                        rendered.push(TemplateFile {
+                            path: render_path(relative, vars)?,
+                            contents,
+                        });
+                        continue;
+                    }
+                    let contents = String::from_utf8(contents)
+                        .with_context(|| format!("Template file {} is not UTF-8 text", path.display()))?;
+                    rendered.push(render_file(relative, &contents, vars)?.into());
+                }
+            }
+        }
+        Ok(rendered)
+    }
+}
+
+fn is_template_path(name: &str) -> bool {
+    name.contains(['/', '\\']) || name.starts_with(['.', '~'])
+}
+
+/// `~` and `~/...` relative to the home directory; other paths as they are.
+fn expand_home(path: &str) -> PathBuf {
+    let home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"));
+    match (path.strip_prefix('~'), home) {
+        (Some(rest), Some(home)) if rest.is_empty() || rest.starts_with(['/', '\\']) => {
+            PathBuf::from(home).join(rest.trim_start_matches(['/', '\\']))
+        }
+        _ => PathBuf::from(path),
+    }
+}
+
+fn select_for_framework<'p>(path: &'p str, framework: &str) -> Option<&'p str> {
+    match path.strip_prefix(FRAMEWORK_OVERLAY_DIR) {
+        Some(overlay) => overlay
+            .strip_prefix(framework)
+            .and_then(|rest| rest.strip_prefix('/')),
+        None => Some(path),
+    }
+}
+
+/// Files of a template directory, excluding the manifest and any `.git` folder.
+fn template_files(dir: &Path) -> Result<Vec<(String, PathBuf)>> {
+    let mut files = Vec::new();
+    for entry in WalkDir::new(dir).sort_by_file_name() {
+        let entry = entry?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let relative = entry.path().strip_prefix(dir)?;
+        if relative.components().any(|c| c.as_os_str() == ".git")
+            || relative == Path::new(MANIFEST_FILE)
+        {
+            continue;
+        }
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        files.push((relative, entry.path().to_path_buf()));
+    }
+    Ok(files)
+}
+
+fn is_template(path: &str) -> bool {
+    path.ends_with(TERA_SUFFIX) || path.ends_with(HANDLEBARS_SUFFIX)
+}
+
+fn render_file(path: &str, contents: &str, vars: &TemplateVars) -> Result<RenderedFile> {
+    let path = render_path(path, vars)?;
+
+    if let Some(stripped) = path.strip_suffix(TERA_SUFFIX) {
+        let context = tera::Context::from_serialize(vars)?;
+        let contents = tera::Tera::one_off(contents, &context, false)
+            .with_context(|| format!("Failed to render template {}", path))?;
+        return Ok(RenderedFile { path: stripped.to_string(), contents });
+    }
+
+    if let Some(stripped) = path.strip_suffix(HANDLEBARS_SUFFIX) {
+        let mut handlebars = Handlebars::new();
+        handlebars.register_escape_fn(handlebars::no_escape);
+        let contents = handlebars
+            .render_template(contents, vars)
+            .with_context(|| format!("Failed to render template {}", path))?;
+        return Ok(RenderedFile { path: stripped.to_string(), contents });
+    }
+
+    Ok(RenderedFile { path, contents: contents.to_string() })
+}
+
+/// File paths may reference template variables, e.g. `src/{{ name }}.js`.
+fn render_path(path: &str, vars: &TemplateVars) -> Result<String> {
+    render_text(path, vars).with_context(|| format!("Failed to render template path {}", path))
+}
+
+/// Renders a short Tera snippet, such as a patch, with the template variables.
+pub fn render_text(text: &str, vars: &TemplateVars) -> Result<String> {
+    if !text.contains("{{") && !text.contains("{%") {
+        return Ok(text.to_string());
+    }
+    let context = tera::Context::from_serialize(vars)?;
+    Ok(tera::Tera::one_off(text, &context, false)?)
+}
+
+fn detect_author() -> String {
+    Command::new("git")
+        .args(["config", "user.name"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .filter(|name| !name.is_empty())
+        .or_else(|| std::env::var("USER").ok())
+        .or_else(|| std::env::var("USERNAME").ok())
+        .unwrap_or_else(|| "Nexus Studio Team".to_string())
+}
@@ -1,4 +1,5 @@
 use clap::Subcommand;
+use serde::{Deserialize, Serialize};
 
 #[derive(clap::ValueEnum, Clone, Debug)]
 pub enum ComponentType {
@@ -9,6 +10,32 @@ pub enum ComponentType {
     Util,
 }
 
+/// What `nexus build` produces.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BuildTarget {
+    /// Single-page app
+    #[default]
+    Web,
+    /// A static HTML file for each route
+    Ssg,
+    /// The web app with a service worker and web app manifest
+    Pwa,
+    /// An ES module bundle with type declarations
+    Lib,
+}
+
+impl BuildTarget {
+    pub fn name(&self) -> &'static str {
+        match self {
+            BuildTarget::Web => "web",
+            BuildTarget::Ssg => "ssg",
+            BuildTarget::Pwa => "pwa",
+            BuildTarget::Lib => "lib",
+        }
+    }
+}
+
 #[derive(clap::ValueEnum, Clone, Debug)]
 pub enum DeployTarget {
     Vercel,
@@ -18,6 +45,94 @@ pub enum DeployTarget {
     Github,
 }
 
+/// Companion files generated next to a component, and how pages and layouts
+/// are registered with the router.
+#[derive(clap::Args, Clone, Debug, Default)]
+pub struct ComponentOptions {
+    /// Also generate a test file
+    #[arg(long)]
+    pub test: bool,
+
+    /// Also generate a Storybook story
+    #[arg(long)]
+    pub story: bool,
+
+    /// Also generate a CSS module and use it in the component
+    #[arg(long)]
+    pub css_module: bool,
+
+    /// Export the component from the folder's index barrel
+    #[arg(long)]
+    pub index: bool,
+
+    /// URL path for a page (default: from its name, e.g. /admin/user-list)
+    #[arg(long, value_name = "PATH", conflicts_with = "no_route")]
+    pub route: Option<String>,
+
+    /// Register a page as a child route of this layout
+    #[arg(long, value_name = "NAME", conflicts_with = "no_route")]
+    pub layout: Option<String>,
+
+    /// Do not register a page or layout with the router
+    #[arg(long)]
+    pub no_route: bool,
+}
+
+/// Which workspace members a build, serve or deploy runs on. Without either
+/// flag the command runs on the current project, or on every member when run
+/// from a workspace root.
+#[derive(clap::Args, Clone, Debug, Default)]
+pub struct MemberSelection {
+    /// Workspace member to run on (repeatable)
+    #[arg(long = "member", value_name = "NAME")]
+    pub members: Vec<String>,
+
+    /// Run on every member of the enclosing workspace
+    #[arg(long)]
+    pub workspace: bool,
+}
+
+#[derive(Subcommand)]
+pub enum DepsCommands {
+    /// List the project's dependencies
+    List,
+
+    /// Add dependencies to package.json
+    Add {
+        /// Packages as name or name@version. Without a version the latest is used
+        #[arg(required = true)]
+        packages: Vec<String>,
+
+        /// Add to devDependencies
+        #[arg(short = 'D', long)]
+        dev: bool,
+
+        /// Run the package manager's install afterwards
+        #[arg(long)]
+        install: bool,
+    },
+
+    /// Remove dependencies from package.json
+    Remove {
+        #[arg(required = true)]
+        packages: Vec<String>,
+
+        /// Run the package manager's install afterwards
+        #[arg(long)]
+        install: bool,
+    },
+
+    /// Upgrade dependencies to their latest versions, keeping ^ and ~ ranges
+    Upgrade {
+        /// Packages to upgrade, optionally as name@version. Omit to upgrade all
+        packages: Vec<String>,
+
+        /// Run the package manager's install afterwards
+        #[arg(long)]
+        install: bool,
+    },
+}
+
 #[derive(Subcommand)]
 pub enum DbCommands {
     /// Initialize database
@@ -170,3 +285,35 @@ pub enum ConfigCommands {
     /// Reset configuration to defaults
     Reset,
 }
+
+#[derive(Subcommand)]
+pub enum TemplateCommands {
+    /// List built-in and installed templates
+    List,
+
+    /// Show details for a template
+    Show {
+        /// Template name
+        name: String,
+    },
+
+    /// Install a template from a directory, git checkout or .tar.gz/.zip archive
+    Add {
+        /// Path to the template source
+        source: String,
+
+        /// Install under a different name than the manifest's
+        #[arg(short, long)]
+        name: Option<String>,
+
+        /// Replace an installed template with the same name
+        #[arg(short, long)]
+        force: bool,
+    },
+
+    /// Remove an installed template
+    Remove {
+        /// Template name
+        name: String,
+    },
+}
@@ -0,0 +1,105 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+/// How often the watched directories are scanned.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+/// A burst of changes is over once nothing changed for this long.
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Directories never watched, whatever `.gitignore` says.
+const ALWAYS_IGNORED: &[&str] = &["node_modules", ".git", ".nexus"];
+
+/// Watches project directories by polling them, which needs no platform
+/// file-notification support. Files ignored by `.gitignore` (or `.ignore`),
+/// hidden directories and the `ignored` paths such as build outputs are
+/// skipped.
+pub struct Watcher {
+    roots: Vec<PathBuf>,
+    ignored: Vec<PathBuf>,
+    snapshot: HashMap<PathBuf, (SystemTime, u64)>,
+}
+
+impl Watcher {
+    pub fn new(roots: Vec<PathBuf>, ignored: Vec<PathBuf>) -> Self {
+        let mut watcher = Self {
+            roots,
+            ignored,
+            snapshot: HashMap::new(),
+        };
+        watcher.snapshot = watcher.scan();
+        watcher
+    }
+
+    /// Waits for files to be added, changed or removed, then for the burst
+    /// to settle, and returns every path that changed.
+    pub async fn changes(&mut self) -> Vec<PathBuf> {
+        let mut changed = Vec::new();
+        loop {
+            tokio::time::sleep(if changed.is_empty() { POLL_INTERVAL } else { DEBOUNCE }).await;
+            let snapshot = self.scan();
+            let burst = diff(&self.snapshot, &snapshot);
+            self.snapshot = snapshot;
+            if burst.is_empty() && !changed.is_empty() {
+                changed.sort();
+                changed.dedup();
+                return changed;
+            }
+            changed.extend(burst);
+        }
+    }
+
+    fn scan(&self) -> HashMap<PathBuf, (SystemTime, u64)> {
+        let mut files = HashMap::new();
+        for root in &self.roots {
+            let ignored = self.ignored.clone();
+            let walker = ignore::WalkBuilder::new(root)
+                .hidden(false)
+                .require_git(false)
+                .filter_entry(move |entry| !is_ignored(entry, &ignored))
+                .build();
+            for entry in walker.filter_map(|entry| entry.ok()) {
+                if !entry.file_type().is_some_and(|file_type| file_type.is_file()) {
+                    continue;
+                }
+                if let Ok(metadata) = entry.metadata() {
+                    let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+                    files.insert(entry.into_path(), (modified, metadata.len()));
+                }
+            }
+        }
+        files
+    }
+}
+
+fn is_ignored(entry: &ignore::DirEntry, ignored: &[PathBuf]) -> bool {
+    if entry.depth() == 0 {
+        return false;
+    }
+    let is_dir = entry.file_type().is_some_and(|file_type| file_type.is_dir());
+    let name = entry.file_name().to_string_lossy();
+    ALWAYS_IGNORED.contains(&name.as_ref())
+        || (is_dir && name.starts_with('.'))
+        || ignored.iter().any(|path| entry.path() == path)
+}
+
+fn diff(
+    before: &HashMap<PathBuf, (SystemTime, u64)>,
+    after: &HashMap<PathBuf, (SystemTime, u64)>,
+) -> Vec<PathBuf> {
+    let mut changed: Vec<PathBuf> = after
+        .iter()
+        .filter(|(path, stamp)| before.get(*path) != Some(*stamp))
+        .map(|(path, _)| path.clone())
+        .collect();
+    changed.extend(before.keys().filter(|path| !after.contains_key(*path)).cloned());
+    changed
+}
+
+/// `path` relative to `root` for display, or as given when outside it.
+pub fn display_path(path: &Path, root: &Path) -> String {
+    path.strip_prefix(root)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+}
@@ -0,0 +1,631 @@
+use crate::project::validate_name;
+use crate::templates::{canonical_framework, ProjectTemplate, TemplateVariable};
+use anyhow::Result;
+use crossterm::{
+    event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+use ratatui::{
+    backend::CrosstermBackend,
+    buffer::Buffer,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, List, ListItem, ListState, Paragraph, StatefulWidget, Widget, Wrap},
+    Terminal,
+};
+use std::collections::BTreeMap;
+use std::io;
+use std::path::Path;
+
+/// Frameworks offered by the wizard, in the order of the PowerShell wizard.
+const FRAMEWORK_CHOICES: &[(&str, &str)] = &[
+    ("react-ts", "React + TypeScript"),
+    ("react", "React (JavaScript)"),
+    ("vue", "Vue 3 + Composition API"),
+    ("nextjs", "Next.js (Full-stack)"),
+    ("svelte", "SvelteKit"),
+    ("angular", "Angular"),
+    ("html", "HTML/CSS/JS"),
+];
+
+const AI_MODELS: &[&str] = &["phi-3-mini", "phi-3-small", "phi-3-medium"];
+
+/// Initial selections, taken from the `nexus new` flags.
+pub struct WizardDefaults<'a> {
+    pub template: &'a str,
+    pub framework: &'a str,
+    pub ai: bool,
+    pub ai_model: &'a str,
+    pub variables: &'a BTreeMap<String, String>,
+}
+
+/// Everything `nexus new` needs, as chosen in the wizard.
+#[derive(Debug, Clone)]
+pub struct WizardAnswers {
+    pub name: String,
+    pub framework: String,
+    pub template: String,
+    pub variables: BTreeMap<String, String>,
+    pub ai: bool,
+    pub ai_model: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Step {
+    Name,
+    Framework,
+    Template,
+    Variables,
+    Ai,
+    Confirm,
+}
+
+impl Step {
+    fn title(&self) -> &'static str {
+        match self {
+            Step::Name => "Step 1: Project Details",
+            Step::Framework => "Step 2: Select Framework",
+            Step::Template => "Step 3: Select Template",
+            Step::Variables => "Step 4: Template Variables",
+            Step::Ai => "Step 5: AI Configuration",
+            Step::Confirm => "Confirmation",
+        }
+    }
+
+    fn help(&self) -> &'static str {
+        match self {
+            Step::Name => "Type a name · Enter next · Esc cancel",
+            Step::Framework | Step::Template => "↑/↓ select · Enter next · Esc back",
+            Step::Variables => "Type a value · ↑/↓ switch variable · Enter next · Esc back",
+            Step::Ai => "Space toggle AI · ↑/↓ model · Enter next · Esc back",
+            Step::Confirm => "Enter/y create project · n/Esc back",
+        }
+    }
+}
+
+enum Action {
+    Continue,
+    Cancel,
+    Finish,
+}
+
+struct Wizard {
+    step: Step,
+    error: Option<String>,
+    name: String,
+    framework: ListState,
+    templates: Vec<ProjectTemplate>,
+    template: ListState,
+    /// Template to preselect whenever the framework changes.
+    template_name: String,
+    variables: Vec<(TemplateVariable, String)>,
+    variable_index: usize,
+    ai: bool,
+    model: ListState,
+}
+
+impl Wizard {
+    fn new(defaults: &WizardDefaults) -> Result<Self> {
+        let framework = canonical_framework(defaults.framework)?;
+        let mut framework_state = ListState::default();
+        framework_state.select(Some(
+            FRAMEWORK_CHOICES
+                .iter()
+                .position(|(value, _)| *value == framework)
+                .unwrap_or(0),
+        ));
+
+        let mut model_state = ListState::default();
+        model_state.select(Some(
+            AI_MODELS
+                .iter()
+                .position(|model| *model == defaults.ai_model)
+                .unwrap_or(0),
+        ));
+
+        let mut wizard = Self {
+            step: Step::Name,
+            error: None,
+            name: String::new(),
+            framework: framework_state,
+            templates: ProjectTemplate::all()?,
+            template: ListState::default(),
+            template_name: defaults.template.to_string(),
+            variables: Vec::new(),
+            variable_index: 0,
+            ai: defaults.ai,
+            model: model_state,
+        };
+        wizard.select_template();
+        Ok(wizard)
+    }
+
+    fn selected_framework(&self) -> &'static str {
+        FRAMEWORK_CHOICES[self.framework.selected().unwrap_or(0)].0
+    }
+
+    /// Templates usable with the selected framework.
+    fn available_templates(&self) -> Vec<&ProjectTemplate> {
+        let framework = self.selected_framework();
+        self.templates
+            .iter()
+            .filter(|template| template.manifest.supports(framework))
+            .collect()
+    }
+
+    fn selected_template(&self) -> Option<&ProjectTemplate> {
+        self.available_templates()
+            .get(self.template.selected().unwrap_or(0))
+            .copied()
+    }
+
+    /// Selects `template_name` if the current framework supports it, falling
+    /// back to the first available template.
+    fn select_template(&mut self) {
+        let index = self
+            .available_templates()
+            .iter()
+            .position(|template| template.manifest.name == self.template_name)
+            .unwrap_or(0);
+        self.template.select(Some(index));
+    }
+
+    fn prepare_variables(&mut self, supplied: &BTreeMap<String, String>) {
+        let previous: BTreeMap<String, String> = self
+            .variables
+            .drain(..)
+            .map(|(variable, value)| (variable.name, value))
+            .collect();
+        self.variables = self
+            .selected_template()
+            .map(|template| template.manifest.variables.clone())
+            .unwrap_or_default()
+            .into_iter()
+            .map(|variable| {
+                let value = previous
+                    .get(&variable.name)
+                    .or_else(|| supplied.get(&variable.name))
+                    .cloned()
+                    .or_else(|| variable.default.clone())
+                    .unwrap_or_default();
+                (variable, value)
+            })
+            .collect();
+        self.variable_index = 0;
+    }
+
+    fn answers(&self) -> WizardAnswers {
+        WizardAnswers {
+            name: self.name.trim().to_string(),
+            framework: self.selected_framework().to_string(),
+            template: self
+                .selected_template()
+                .map(|template| template.manifest.name.clone())
+                .unwrap_or_else(|| "default".to_string()),
+            variables: self
+                .variables
+                .iter()
+                .map(|(variable, value)| (variable.name.clone(), value.clone()))
+                .collect(),
+            ai: self.ai,
+            ai_model: AI_MODELS[self.model.selected().unwrap_or(0)].to_string(),
+        }
+    }
+
+    fn handle_key(&mut self, key: KeyEvent, defaults: &WizardDefaults) -> Action {
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return Action::Cancel;
+        }
+
+        if key.code == KeyCode::Esc {
+            self.error = None;
+            match self.previous_step() {
+                Some(step) => self.step = step,
+                None => return Action::Cancel,
+            }
+            return Action::Continue;
+        }
+
+        match self.step {
+            Step::Name => match key.code {
+                KeyCode::Char(c) => {
+                    self.name.push(c);
+                    self.error = None;
+                }
+                KeyCode::Backspace => {
+                    self.name.pop();
+                    self.error = None;
+                }
+                KeyCode::Enter => match validate_new_project(self.name.trim()) {
+                    Ok(()) => self.step = Step::Framework,
+                    Err(e) => self.error = Some(e.to_string()),
+                },
+                _ => {}
+            },
+            Step::Framework => match key.code {
+                KeyCode::Up => move_selection(&mut self.framework, FRAMEWORK_CHOICES.len(), -1),
+                KeyCode::Down => move_selection(&mut self.framework, FRAMEWORK_CHOICES.len(), 1),
+                KeyCode::Enter => {
+                    self.select_template();
+                    self.step = Step::Template;
+                }
+                _ => {}
+            },
+            Step::Template => {
+                let count = self.available_templates().len();
+                match key.code {
+                    KeyCode::Up => move_selection(&mut self.template, count, -1),
+                    KeyCode::Down => move_selection(&mut self.template, count, 1),
+                    KeyCode::Enter if count > 0 => {
+                        if let Some(template) = self.selected_template() {
+                            self.template_name = template.manifest.name.clone();
+                        }
+                        self.prepare_variables(defaults.variables);
+                        self.step = if self.variables.is_empty() {
+                            Step::Ai
+                        } else {
+                            Step::Variables
+                        };
+                    }
+                    _ => {}
+                }
+            }
+            Step::Variables => {
+                let count = self.variables.len();
+                match key.code {
+                    KeyCode::Up => self.variable_index = self.variable_index.saturating_sub(1),
+                    KeyCode::Down | KeyCode::Tab => {
+                        self.variable_index = (self.variable_index + 1).min(count - 1)
+                    }
+                    KeyCode::Char(c) => {
+                        self.variables[self.variable_index].1.push(c);
+                        self.error = None;
+                    }
+                    KeyCode::Backspace => {
+                        self.variables[self.variable_index].1.pop();
+                        self.error = None;
+                    }
+                    KeyCode::Enter => {
+                        let missing = self
+                            .variables
+                            .iter()
+                            .position(|(variable, value)| {
+                                variable.is_required() && value.trim().is_empty()
+                            });
+                        match missing {
+                            Some(index) => {
+                                self.variable_index = index;
+                                self.error = Some(format!(
+                                    "{} is required",
+                                    self.variables[index].0.name
+                                ));
+                            }
+                            None if self.variable_index + 1 < count => self.variable_index += 1,
+                            None => self.step = Step::Ai,
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Step::Ai => match key.code {
+                KeyCode::Char(' ') | KeyCode::Left | KeyCode::Right => self.ai = !self.ai,
+                KeyCode::Char('y') => self.ai = true,
+                KeyCode::Char('n') => self.ai = false,
+                KeyCode::Up if self.ai => move_selection(&mut self.model, AI_MODELS.len(), -1),
+                KeyCode::Down if self.ai => move_selection(&mut self.model, AI_MODELS.len(), 1),
+                KeyCode::Enter => self.step = Step::Confirm,
+                _ => {}
+            },
+            Step::Confirm => match key.code {
+                KeyCode::Enter | KeyCode::Char('y') | KeyCode::Char('Y') => return Action::Finish,
+                KeyCode::Char('n') | KeyCode::Char('N') => self.step = Step::Ai,
+                _ => {}
+            },
+        }
+        Action::Continue
+    }
+
+    fn previous_step(&self) -> Option<Step> {
+        match self.step {
+            Step::Name => None,
+            Step::Framework => Some(Step::Name),
+            Step::Template => Some(Step::Framework),
+            Step::Variables => Some(Step::Template),
+            Step::Ai if self.variables.is_empty() => Some(Step::Template),
+            Step::Ai => Some(Step::Variables),
+            Step::Confirm => Some(Step::Ai),
+        }
+    }
+}
+
+fn move_selection(state: &mut ListState, len: usize, delta: isize) {
+    if len == 0 {
+        return;
+    }
+    let current = state.selected().unwrap_or(0) as isize;
+    state.select(Some((current + delta).rem_euclid(len as isize) as usize));
+}
+
+fn validate_new_project(name: &str) -> Result<()> {
+    validate_name(name)?;
+    if Path::new(name).exists() {
+        anyhow::bail!("'{}' already exists in this directory", name);
+    }
+    Ok(())
+}
+
+/// Runs the wizard in the alternate screen. Returns `None` if it was cancelled.
+pub fn run(defaults: &WizardDefaults) -> Result<Option<WizardAnswers>> {
+    let mut wizard = Wizard::new(defaults)?;
+
+    enable_raw_mode()?;
+    let _guard = TerminalGuard;
+    execute!(io::stdout(), EnterAlternateScreen)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(io::stdout()))?;
+    terminal.hide_cursor()?;
+
+    loop {
+        terminal.draw(|frame| {
+            let area = frame.size();
+            frame.render_widget(WizardView(&mut wizard), area);
+        })?;
+
+        if let Event::Key(key) = event::read()? {
+            // Windows reports key releases as well as presses.
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match wizard.handle_key(key, defaults) {
+                Action::Continue => {}
+                Action::Cancel => return Ok(None),
+                Action::Finish => return Ok(Some(wizard.answers())),
+            }
+        }
+    }
+}
+
+/// Restores the terminal even when the wizard exits with an error.
+struct TerminalGuard;
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = disable_raw_mode();
+        let _ = execute!(io::stdout(), LeaveAlternateScreen, crossterm::cursor::Show);
+    }
+}
+
+struct WizardView<'a>(&'a mut Wizard);
+
+impl Widget for WizardView<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let wizard = self.0;
+        let outer = Block::default()
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(Color::Blue))
+            .title(Span::styled(
+                " NEW PROJECT WIZARD ",
+                Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
+            ));
+        let inner = outer.inner(area);
+        outer.render(area, buf);
+
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .margin(1)
+            .constraints(vec![
+                Constraint::Length(2),
+                Constraint::Min(5),
+                Constraint::Length(1),
+                Constraint::Length(1),
+            ])
+            .split(inner);
+
+        Paragraph::new(Line::from(Span::styled(
+            wizard.step.title(),
+            Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
+        )))
+        .render(rows[0], buf);
+
+        match wizard.step {
+            Step::Name => render_name(wizard, rows[1], buf),
+            Step::Framework => render_framework(wizard, rows[1], buf),
+            Step::Template => render_template(wizard, rows[1], buf),
+            Step::Variables => render_variables(wizard, rows[1], buf),
+            Step::Ai => render_ai(wizard, rows[1], buf),
+            Step::Confirm => render_summary(wizard, rows[1], buf),
+        }
+
+        if let Some(error) = &wizard.error {
+            Paragraph::new(Span::styled(format!("✖ {}", error), Style::default().fg(Color::Red)))
+                .render(rows[2], buf);
+        }
+        Paragraph::new(Span::styled(wizard.step.help(), Style::default().fg(Color::DarkGray)))
+            .render(rows[3], buf);
+    }
+}
+
+fn highlight() -> Style {
+    Style::default().fg(Color::Black).bg(Color::Cyan)
+}
+
+fn input_line<'a>(label: &'a str, value: &'a str, active: bool) -> Line<'a> {
+    let label_style = if active {
+        Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)
+    } else {
+        Style::default()
+    };
+    let mut spans = vec![
+        Span::styled(format!("{}: ", label), label_style),
+        Span::raw(value),
+    ];
+    if active {
+        spans.push(Span::styled("█", Style::default().fg(Color::Cyan)));
+    }
+    Line::from(spans)
+}
+
+fn render_name(wizard: &Wizard, area: Rect, buf: &mut Buffer) {
+    let lines = vec![
+        input_line("Project name", &wizard.name, true),
+        Line::from(""),
+        Line::from(Span::styled(
+            "Letters, digits, '-', '_' and '.'. A directory with this name is created here.",
+            Style::default().fg(Color::DarkGray),
+        )),
+    ];
+    Paragraph::new(lines).render(area, buf);
+}
+
+fn render_framework(wizard: &mut Wizard, area: Rect, buf: &mut Buffer) {
+    let items: Vec<ListItem> = FRAMEWORK_CHOICES
+        .iter()
+        .map(|(value, label)| {
+            ListItem::new(Line::from(vec![
+                Span::raw(format!("{:<26}", label)),
+                Span::styled(*value, Style::default().fg(Color::DarkGray)),
+            ]))
+        })
+        .collect();
+    let list = List::new(items)
+        .highlight_style(highlight())
+        .highlight_symbol("› ");
+    StatefulWidget::render(list, area, buf, &mut wizard.framework);
+}
+
+fn render_template(wizard: &mut Wizard, area: Rect, buf: &mut Buffer) {
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(vec![Constraint::Percentage(35), Constraint::Percentage(65)])
+        .split(area);
+
+    let items: Vec<ListItem> = wizard
+        .available_templates()
+        .iter()
+        .map(|template| {
+            let origin = if template.is_builtin() { "" } else { " (installed)" };
+            ListItem::new(format!("{}{}", template.manifest.name, origin))
+        })
+        .collect();
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(" Templates "))
+        .highlight_style(highlight())
+        .highlight_symbol("› ");
+    StatefulWidget::render(list, columns[0], buf, &mut wizard.template);
+
+    let framework = wizard.selected_framework();
+    let preview = match wizard.selected_template() {
+        Some(template) => template_preview(template, framework),
+        None => vec![Line::from(format!("No templates support {}", framework))],
+    };
+    Paragraph::new(preview)
+        .block(Block::default().borders(Borders::ALL).title(" Preview "))
+        .wrap(Wrap { trim: false })
+        .render(columns[1], buf);
+}
+
+fn template_preview<'a>(template: &'a ProjectTemplate, framework: &str) -> Vec<Line<'a>> {
+    let manifest = &template.manifest;
+    let bold = Style::default().add_modifier(Modifier::BOLD);
+    let mut lines = vec![
+        Line::from(Span::styled(manifest.name.as_str(), bold.fg(Color::Cyan))),
+        Line::from(manifest.description.as_str()),
+        Line::from(""),
+    ];
+
+    if !manifest.variables.is_empty() {
+        lines.push(Line::from(Span::styled("Variables", bold)));
+        for variable in &manifest.variables {
+            let requirement = match &variable.default {
+                Some(default) => format!("default: {}", default),
+                None => "required".to_string(),
+            };
+            lines.push(Line::from(format!("  {} ({})", variable.name, requirement)));
+        }
+        lines.push(Line::from(""));
+    }
+
+    lines.push(Line::from(Span::styled("Files", bold)));
+    match template.files_for(framework) {
+        Ok(files) => lines.extend(files.into_iter().map(|file| Line::from(format!("  {}", file)))),
+        Err(e) => lines.push(Line::from(Span::styled(
+            format!("  {:#}", e),
+            Style::default().fg(Color::Red),
+        ))),
+    }
+    lines.push(Line::from(Span::styled(
+        format!("  + {} starter files", framework),
+        Style::default().fg(Color::DarkGray),
+    )));
+    lines
+}
+
+fn render_variables(wizard: &Wizard, area: Rect, buf: &mut Buffer) {
+    let mut lines = Vec::new();
+    for (index, (variable, value)) in wizard.variables.iter().enumerate() {
+        let active = index == wizard.variable_index;
+        lines.push(input_line(&variable.name, value, active));
+        if !variable.description.is_empty() {
+            lines.push(Line::from(Span::styled(
+                format!("  {}", variable.description),
+                Style::default().fg(Color::DarkGray),
+            )));
+        }
+    }
+    Paragraph::new(lines).render(area, buf);
+}
+
+fn render_ai(wizard: &mut Wizard, area: Rect, buf: &mut Buffer) {
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(vec![Constraint::Length(2), Constraint::Min(3)])
+        .split(area);
+
+    let toggle = if wizard.ai {
+        Span::styled("[x] Enabled", Style::default().fg(Color::Green))
+    } else {
+        Span::styled("[ ] Disabled", Style::default().fg(Color::DarkGray))
+    };
+    Paragraph::new(Line::from(vec![Span::raw("AI assistance: "), toggle])).render(rows[0], buf);
+
+    if wizard.ai {
+        let items: Vec<ListItem> = AI_MODELS.iter().map(|model| ListItem::new(*model)).collect();
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(" Model "))
+            .highlight_style(highlight())
+            .highlight_symbol("› ");
+        StatefulWidget::render(list, rows[1], buf, &mut wizard.model);
+    }
+}
+
+fn render_summary(wizard: &Wizard, area: Rect, buf: &mut Buffer) {
+    let answers = wizard.answers();
+    let label = Style::default().fg(Color::Cyan);
+    let row = |name: &'static str, value: String| {
+        Line::from(vec![Span::styled(format!("{:<14}", name), label), Span::raw(value)])
+    };
+
+    let mut lines = vec![
+        row("Project", answers.name.clone()),
+        row("Framework", answers.framework.clone()),
+        row("Template", answers.template.clone()),
+    ];
+    for (name, value) in &answers.variables {
+        lines.push(row("  var", format!("{} = {}", name, value)));
+    }
+    lines.push(row(
+        "AI Assistance",
+        if answers.ai {
+            format!("Enabled ({})", answers.ai_model)
+        } else {
+            "Disabled".to_string()
+        },
+    ));
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "Create project with these settings? (Y/n)",
+        Style::default().add_modifier(Modifier::BOLD),
+    )));
+    Paragraph::new(lines).render(area, buf);
+}
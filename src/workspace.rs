@@ -0,0 +1,256 @@
+use crate::project::{self, Project};
+use anyhow::{Context, Result};
+use colored::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+pub const WORKSPACE_FILE: &str = "nexus-workspace.yaml";
+
+/// Contents of `nexus-workspace.yaml`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WorkspaceConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    pub members: Vec<MemberConfig>,
+}
+
+/// A member project, relative to the workspace root. Dependencies refer to
+/// other members by the `name` in their `nexus.yaml`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MemberConfig {
+    pub path: PathBuf,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends_on: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Workspace {
+    pub root: PathBuf,
+    pub config: WorkspaceConfig,
+    /// Member projects in the order they are listed.
+    pub members: Vec<Project>,
+}
+
+impl Workspace {
+    pub fn load(root: impl AsRef<Path>) -> Result<Self> {
+        let root = root.as_ref().to_path_buf();
+        let config_path = root.join(WORKSPACE_FILE);
+        let contents = std::fs::read_to_string(&config_path)
+            .with_context(|| format!("Missing workspace config at {}", config_path.display()))?;
+        let config: WorkspaceConfig = serde_yaml::from_str(&contents)
+            .with_context(|| format!("Invalid workspace config {}", config_path.display()))?;
+
+        let mut members: Vec<Project> = Vec::new();
+        for member in &config.members {
+            let project = Project::load(root.join(&member.path)).with_context(|| {
+                format!("Failed to load workspace member {}", member.path.display())
+            })?;
+            if let Some(other) = members.iter().find(|p| p.config.name == project.config.name) {
+                anyhow::bail!(
+                    "Workspace members {} and {} are both named '{}'",
+                    other.root.display(),
+                    project.root.display(),
+                    project.config.name
+                );
+            }
+            members.push(project);
+        }
+
+        let workspace = Self { root, config, members };
+        for (member, project) in workspace.config.members.iter().zip(&workspace.members) {
+            for dependency in &member.depends_on {
+                if workspace.member(dependency).is_none() {
+                    anyhow::bail!(
+                        "Workspace member '{}' depends on unknown member '{}'",
+                        project.config.name,
+                        dependency
+                    );
+                }
+            }
+        }
+        Ok(workspace)
+    }
+
+    /// The `name` from the workspace file, or the root directory's name.
+    pub fn display_name(&self) -> String {
+        self.config.name.clone().unwrap_or_else(|| {
+            self.root
+                .file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| self.root.display().to_string())
+        })
+    }
+
+    pub fn member(&self, name: &str) -> Option<&Project> {
+        self.members.iter().find(|project| project.config.name == name)
+    }
+
+    pub fn member_names(&self) -> Vec<&str> {
+        self.members.iter().map(|project| project.config.name.as_str()).collect()
+    }
+
+    /// The members `name` depends on, directly or through other members.
+    pub fn dependencies_of(&self, name: &str) -> Vec<&Project> {
+        let Some(project) = self.member(name) else {
+            return Vec::new();
+        };
+        let mut wanted = Vec::new();
+        self.collect_dependencies(&project.config.name, &mut wanted);
+        wanted
+            .into_iter()
+            .filter(|dependency| *dependency != name)
+            .filter_map(|dependency| self.member(dependency))
+            .collect()
+    }
+
+    fn dependencies(&self, name: &str) -> &[String] {
+        self.members
+            .iter()
+            .position(|project| project.config.name == name)
+            .map(|index| self.config.members[index].depends_on.as_slice())
+            .unwrap_or_default()
+    }
+
+    /// Members in dependency order, dependencies first. With no names every
+    /// member is returned; otherwise only the named members, plus everything
+    /// they depend on when `with_dependencies` is set.
+    pub fn ordered(&self, names: &[String], with_dependencies: bool) -> Result<Vec<&Project>> {
+        for name in names {
+            if self.member(name).is_none() {
+                anyhow::bail!(
+                    "Unknown workspace member '{}'. Members: {}",
+                    name,
+                    self.member_names().join(", ")
+                );
+            }
+        }
+
+        let mut marks = BTreeMap::new();
+        let mut order = Vec::new();
+        for project in &self.members {
+            self.visit(&project.config.name, &mut marks, &mut order)?;
+        }
+
+        let selected: Vec<&str> = if names.is_empty() {
+            order
+        } else if with_dependencies {
+            let mut wanted = Vec::new();
+            for name in names {
+                self.collect_dependencies(name, &mut wanted);
+            }
+            order.into_iter().filter(|name| wanted.contains(name)).collect()
+        } else {
+            order
+                .into_iter()
+                .filter(|name| names.iter().any(|n| n == name))
+                .collect()
+        };
+        Ok(selected
+            .into_iter()
+            .filter_map(|name| self.member(name))
+            .collect())
+    }
+
+    fn visit<'a>(
+        &'a self,
+        name: &'a str,
+        marks: &mut BTreeMap<&'a str, bool>,
+        order: &mut Vec<&'a str>,
+    ) -> Result<()> {
+        // false: on the current path, true: already ordered.
+        match marks.get(name) {
+            Some(true) => return Ok(()),
+            Some(false) => anyhow::bail!("Workspace dependency cycle involving '{}'", name),
+            None => {}
+        }
+        marks.insert(name, false);
+        for dependency in self.dependencies(name) {
+            self.visit(dependency, marks, order)?;
+        }
+        marks.insert(name, true);
+        order.push(name);
+        Ok(())
+    }
+
+    fn collect_dependencies<'a>(&'a self, name: &'a str, wanted: &mut Vec<&'a str>) {
+        if wanted.contains(&name) {
+            return;
+        }
+        wanted.push(name);
+        for dependency in self.dependencies(name) {
+            self.collect_dependencies(dependency, wanted);
+        }
+    }
+}
+
+/// Walks up from `start` looking for a directory containing `nexus-workspace.yaml`.
+pub fn find_root(start: &Path) -> Option<PathBuf> {
+    let start = start.canonicalize().ok()?;
+    let start = if start.is_file() { start.parent()?.to_path_buf() } else { start };
+    start
+        .ancestors()
+        .find(|dir| dir.join(WORKSPACE_FILE).is_file())
+        .map(Path::to_path_buf)
+}
+
+/// The projects a build, serve or deploy command operates on: the project
+/// containing `start` by default, or workspace members when `start` is not
+/// inside a project, `members` are named, or `all` is set.
+pub fn select_projects(
+    start: &Path,
+    members: &[String],
+    all: bool,
+    with_dependencies: bool,
+) -> Result<Vec<Project>> {
+    let project_root = project::find_root(start);
+    let workspace_root = find_root(start);
+    // The nearer of the two wins, so running inside a member targets that
+    // member and running elsewhere in the workspace targets the workspace.
+    let in_project = match (&project_root, &workspace_root) {
+        (Some(project), Some(workspace)) => project.starts_with(workspace),
+        (Some(_), None) => true,
+        (None, _) => false,
+    };
+
+    let workspace = if let (true, Some(project_root)) = (in_project, project_root) {
+        let project = Project::load(project_root)?;
+        if members.is_empty() && !all {
+            return Ok(vec![project]);
+        }
+        let root = project.workspace_root.with_context(|| {
+            format!(
+                "--member and --workspace need a {} in this or a parent directory",
+                WORKSPACE_FILE
+            )
+        })?;
+        Workspace::load(root)?
+    } else {
+        let root = workspace_root.with_context(|| {
+            format!(
+                "Not in a Nexus project or workspace: no nexus.yaml or {} in {} or any parent directory. Run 'nexus new' first.",
+                WORKSPACE_FILE,
+                start.display()
+            )
+        })?;
+        Workspace::load(root)?
+    };
+
+    let selected: Vec<Project> = workspace
+        .ordered(members, with_dependencies)?
+        .into_iter()
+        .cloned()
+        .collect();
+    println!(
+        "🗂️  Workspace {}: {}",
+        workspace.display_name().cyan().bold(),
+        selected
+            .iter()
+            .map(|project| project.config.name.as_str())
+            .collect::<Vec<_>>()
+            .join(" → ")
+    );
+    Ok(selected)
+}
@@ -0,0 +1,446 @@
+use anyhow::{Context, Result};
+use colored::*;
+use similar::{ChangeTag, DiffOp, TextDiff};
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+/// What to do when a generated file already exists with different contents.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// Keep the existing file
+    Skip,
+    /// Replace the existing file
+    Overwrite,
+    /// Ask for each conflicting file (skips when stdin is not a terminal)
+    #[default]
+    Prompt,
+    /// Write both versions separated by git-style conflict markers
+    #[value(name = "merge-markers")]
+    MergeMarkers,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct WriteOptions {
+    /// Record planned writes without touching the filesystem.
+    pub dry_run: bool,
+    /// Print unified diffs against existing files. Implies `dry_run`.
+    pub diff: bool,
+    pub conflict: ConflictPolicy,
+}
+
+impl WriteOptions {
+    pub fn is_preview(&self) -> bool {
+        self.dry_run || self.diff
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WriteOutcome {
+    Created,
+    Overwritten,
+    Unchanged,
+    Skipped,
+    Merged,
+}
+
+impl WriteOutcome {
+    fn marker(&self) -> ColoredString {
+        match self {
+            WriteOutcome::Created => "+".green(),
+            WriteOutcome::Overwritten => "~".yellow(),
+            WriteOutcome::Unchanged => "=".dimmed(),
+            WriteOutcome::Skipped => "-".red(),
+            WriteOutcome::Merged => "!".magenta(),
+        }
+    }
+}
+
+/// Writes generated files under a root directory, honouring dry-run, diff and
+/// conflict settings. Clones share the record of what was written.
+#[derive(Clone, Debug)]
+pub struct FileWriter {
+    root: PathBuf,
+    options: WriteOptions,
+    records: Arc<Mutex<Vec<(PathBuf, WriteOutcome)>>>,
+}
+
+impl FileWriter {
+    pub fn new(root: impl Into<PathBuf>, options: WriteOptions) -> Self {
+        Self {
+            root: root.into(),
+            options,
+            records: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    pub fn is_preview(&self) -> bool {
+        self.options.is_preview()
+    }
+
+    pub fn conflict(&self) -> ConflictPolicy {
+        self.options.conflict
+    }
+
+    /// A writer that shares this one's record of written files but resolves
+    /// conflicts with `policy`, for edits the user asked for explicitly.
+    pub fn with_conflict(&self, policy: ConflictPolicy) -> Self {
+        Self {
+            root: self.root.clone(),
+            options: WriteOptions {
+                conflict: policy,
+                ..self.options
+            },
+            records: Arc::clone(&self.records),
+        }
+    }
+
+    pub fn create_dir(&self, relative: impl AsRef<Path>) -> Result<()> {
+        if self.is_preview() {
+            return Ok(());
+        }
+        let path = self.root.join(relative);
+        std::fs::create_dir_all(&path)
+            .with_context(|| format!("Failed to create {}", path.display()))
+    }
+
+    pub fn write(&self, relative: impl AsRef<Path>, contents: &str) -> Result<WriteOutcome> {
+        let relative = relative.as_ref();
+        let path = self.root.join(relative);
+        let existing = match read_existing(&path)? {
+            Some(bytes) => match String::from_utf8(bytes) {
+                Ok(text) => Some(text),
+                // Text cannot be diffed or merged with a binary file.
+                Err(e) => return self.write_binary(relative, Some(e.into_bytes()), contents.as_bytes()),
+            },
+            None => None,
+        };
+
+        if self.options.diff {
+            print_diff(relative, existing.as_deref().unwrap_or(""), contents);
+        }
+
+        // Replacing a file this writer produced earlier is not a conflict, and
+        // the file keeps the outcome it was first recorded with.
+        let (outcome, final_contents) = match (self.recorded_write(relative), existing.as_deref()) {
+            (Some(previous), _) => (previous, Some(contents.to_string())),
+            (None, None) => (WriteOutcome::Created, Some(contents.to_string())),
+            (None, Some(old)) if old == contents => (WriteOutcome::Unchanged, None),
+            (None, Some(old)) => self.resolve_conflict(relative, old, contents)?,
+        };
+
+        if let Some(final_contents) = final_contents {
+            self.store(&path, final_contents.as_bytes())?;
+        }
+        self.record(relative, outcome);
+        Ok(outcome)
+    }
+
+    /// Writes a file copied rather than generated, such as an image from a
+    /// template. Text goes through `write`. Binary contents cannot be diffed
+    /// or merged, so an existing file is either kept or replaced.
+    pub fn write_bytes(&self, relative: impl AsRef<Path>, contents: &[u8]) -> Result<WriteOutcome> {
+        if let Ok(text) = std::str::from_utf8(contents) {
+            return self.write(relative, text);
+        }
+        let relative = relative.as_ref();
+        let existing = read_existing(&self.root.join(relative))?;
+        self.write_binary(relative, existing, contents)
+    }
+
+    fn write_binary(&self, relative: &Path, existing: Option<Vec<u8>>, contents: &[u8]) -> Result<WriteOutcome> {
+        let path = self.root.join(relative);
+        if self.options.diff && existing.as_deref() != Some(contents) {
+            print_binary_diff(relative, existing.is_some());
+        }
+
+        let (outcome, replace) = match (self.recorded_write(relative), existing) {
+            (Some(previous), _) => (previous, true),
+            (None, None) => (WriteOutcome::Created, true),
+            (None, Some(old)) if old == contents => (WriteOutcome::Unchanged, false),
+            (None, Some(_)) => {
+                let outcome = self.resolve_binary_conflict(relative)?;
+                (outcome, outcome == WriteOutcome::Overwritten)
+            }
+        };
+        if replace {
+            self.store(&path, contents)?;
+        }
+        self.record(relative, outcome);
+        Ok(outcome)
+    }
+
+    fn store(&self, path: &Path, contents: &[u8]) -> Result<()> {
+        if self.is_preview() {
+            return Ok(());
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, contents).with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    fn record(&self, relative: &Path, outcome: WriteOutcome) {
+        let mut records = self.records.lock().unwrap();
+        records.retain(|(path, _)| path != relative);
+        records.push((relative.to_path_buf(), outcome));
+    }
+
+    /// How an earlier call created or modified `relative`, if it did.
+    fn recorded_write(&self, relative: &Path) -> Option<WriteOutcome> {
+        self.records
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|(path, _)| path == relative)
+            .map(|(_, outcome)| *outcome)
+            .filter(|outcome| {
+                matches!(
+                    outcome,
+                    WriteOutcome::Created | WriteOutcome::Overwritten | WriteOutcome::Merged
+                )
+            })
+    }
+
+    fn resolve_conflict(
+        &self,
+        relative: &Path,
+        old: &str,
+        new: &str,
+    ) -> Result<(WriteOutcome, Option<String>)> {
+        let policy = match self.options.conflict {
+            // Previews never block on input; report the file as overwritten.
+            ConflictPolicy::Prompt if self.is_preview() => ConflictPolicy::Overwrite,
+            ConflictPolicy::Prompt => prompt_policy(relative, Some((old, new)))?,
+            policy => policy,
+        };
+
+        Ok(match policy {
+            ConflictPolicy::Skip | ConflictPolicy::Prompt => (WriteOutcome::Skipped, None),
+            ConflictPolicy::Overwrite => (WriteOutcome::Overwritten, Some(new.to_string())),
+            ConflictPolicy::MergeMarkers => (WriteOutcome::Merged, Some(merge_markers(old, new))),
+        })
+    }
+
+    /// Like `resolve_conflict`, but merge markers are not an option: the file
+    /// is kept unless the policy is to overwrite it.
+    fn resolve_binary_conflict(&self, relative: &Path) -> Result<WriteOutcome> {
+        let policy = match self.options.conflict {
+            ConflictPolicy::Prompt if self.is_preview() => ConflictPolicy::Overwrite,
+            ConflictPolicy::Prompt => prompt_policy(relative, None)?,
+            policy => policy,
+        };
+        Ok(match policy {
+            ConflictPolicy::Overwrite => WriteOutcome::Overwritten,
+            ConflictPolicy::MergeMarkers => {
+                println!("⚠️  {} is binary; keeping it instead of writing merge markers", relative.display());
+                WriteOutcome::Skipped
+            }
+            ConflictPolicy::Skip | ConflictPolicy::Prompt => WriteOutcome::Skipped,
+        })
+    }
+
+    /// Whether any file was created or modified on disk.
+    pub fn wrote_anything(&self) -> bool {
+        !self.is_preview()
+            && self.records.lock().unwrap().iter().any(|(_, outcome)| {
+                matches!(
+                    outcome,
+                    WriteOutcome::Created | WriteOutcome::Overwritten | WriteOutcome::Merged
+                )
+            })
+    }
+
+    /// Prints the planned file tree for previews and a note about skipped or
+    /// merged files otherwise.
+    pub fn finish(&self) {
+        let records = self.records.lock().unwrap();
+
+        if self.is_preview() {
+            println!(
+                "\n📋 {} ({} files, nothing written):",
+                "Planned changes".yellow().bold(),
+                records.len()
+            );
+            print_planned_tree(&self.root, &records);
+            println!(
+                "   {} create  {} overwrite  {} unchanged  {} skip  {} merge markers",
+                WriteOutcome::Created.marker(),
+                WriteOutcome::Overwritten.marker(),
+                WriteOutcome::Unchanged.marker(),
+                WriteOutcome::Skipped.marker(),
+                WriteOutcome::Merged.marker()
+            );
+            return;
+        }
+
+        for (path, outcome) in records.iter() {
+            match outcome {
+                WriteOutcome::Skipped => {
+                    println!("⏭️  Kept existing {}", self.root.join(path).display());
+                }
+                WriteOutcome::Merged => {
+                    println!(
+                        "⚠️  Conflict markers written to {}",
+                        self.root.join(path).display().to_string().yellow()
+                    );
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Asks how to resolve a conflict. `texts` holds the old and new contents of
+/// a text file; binary files can only be overwritten or skipped.
+/// The contents of an existing file. An unreadable file is an error rather
+/// than an empty one, which would be overwritten as if nothing were there.
+fn read_existing(path: &Path) -> Result<Option<Vec<u8>>> {
+    if !path.is_file() {
+        return Ok(None);
+    }
+    std::fs::read(path)
+        .map(Some)
+        .with_context(|| format!("Failed to read existing {}", path.display()))
+}
+
+fn prompt_policy(relative: &Path, texts: Option<(&str, &str)>) -> Result<ConflictPolicy> {
+    if !io::stdin().is_terminal() {
+        println!(
+            "⚠️  {} exists; skipping (use --on-conflict to choose)",
+            relative.display()
+        );
+        return Ok(ConflictPolicy::Skip);
+    }
+
+    loop {
+        let choices = match texts {
+            Some(_) => "[o]verwrite, [s]kip, [m]erge markers, [d]iff",
+            None => "[o]verwrite, [s]kip",
+        };
+        print!(
+            "⚠️  {} already exists. {}? ",
+            relative.display().to_string().yellow(),
+            choices
+        );
+        io::stdout().flush()?;
+
+        let mut answer = String::new();
+        io::stdin().lock().read_line(&mut answer)?;
+        match (answer.trim().to_lowercase().as_str(), texts) {
+            ("o" | "overwrite", _) => return Ok(ConflictPolicy::Overwrite),
+            ("s" | "skip" | "", _) => return Ok(ConflictPolicy::Skip),
+            ("m" | "merge", Some(_)) => return Ok(ConflictPolicy::MergeMarkers),
+            ("d" | "diff", Some((old, new))) => print_diff(relative, old, new),
+            (_, Some(_)) => println!("Please answer o, s, m or d."),
+            (_, None) => println!("Please answer o or s."),
+        }
+    }
+}
+
+fn print_diff(relative: &Path, old: &str, new: &str) {
+    let name = relative.display().to_string();
+    let diff = TextDiff::from_lines(old, new);
+    let unified = diff
+        .unified_diff()
+        .context_radius(3)
+        .header(&format!("a/{}", name), &format!("b/{}", name))
+        .to_string();
+
+    if unified.is_empty() {
+        return;
+    }
+
+    for line in unified.lines() {
+        if line.starts_with("+++") || line.starts_with("---") {
+            println!("{}", line.bold());
+        } else if line.starts_with('+') {
+            println!("{}", line.green());
+        } else if line.starts_with('-') {
+            println!("{}", line.red());
+        } else if line.starts_with("@@") {
+            println!("{}", line.cyan());
+        } else {
+            println!("{}", line);
+        }
+    }
+}
+
+/// Git's one-line summary for a binary file, which has no line diff.
+fn print_binary_diff(relative: &Path, exists: bool) {
+    let name = relative.display();
+    let old = if exists { format!("a/{}", name) } else { "/dev/null".to_string() };
+    println!("{}", format!("Binary files {} and b/{} differ", old, name).bold());
+}
+
+/// Keeps unchanged lines as-is and wraps each changed region in conflict markers.
+fn merge_markers(old: &str, new: &str) -> String {
+    let diff = TextDiff::from_lines(old, new);
+    let mut merged = String::new();
+
+    for op in diff.ops() {
+        if let DiffOp::Equal { .. } = op {
+            for change in diff.iter_changes(op) {
+                merged.push_str(change.value());
+            }
+            continue;
+        }
+
+        let mut ours = String::new();
+        let mut theirs = String::new();
+        for change in diff.iter_changes(op) {
+            match change.tag() {
+                ChangeTag::Delete => ours.push_str(change.value()),
+                ChangeTag::Insert => theirs.push_str(change.value()),
+                ChangeTag::Equal => {}
+            }
+        }
+        for side in [&mut ours, &mut theirs] {
+            if !side.is_empty() && !side.ends_with('\n') {
+                side.push('\n');
+            }
+        }
+
+        merged.push_str("<<<<<<< existing\n");
+        merged.push_str(&ours);
+        merged.push_str("=======\n");
+        merged.push_str(&theirs);
+        merged.push_str(">>>>>>> nexus\n");
+    }
+
+    merged
+}
+
+fn print_planned_tree(root: &Path, records: &[(PathBuf, WriteOutcome)]) {
+    let mut sorted: Vec<_> = records.iter().collect();
+    sorted.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let root_name = root
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| ".".to_string());
+    println!("{}/", root_name.cyan());
+
+    let mut printed_dirs: Vec<PathBuf> = Vec::new();
+    for (path, outcome) in sorted {
+        let mut dir = PathBuf::new();
+        let components: Vec<_> = path.components().collect();
+        for (depth, component) in components.iter().enumerate() {
+            let indent = "  ".repeat(depth + 1);
+            if depth + 1 == components.len() {
+                println!(
+                    "{}{} {}",
+                    indent,
+                    outcome.marker(),
+                    component.as_os_str().to_string_lossy()
+                );
+            } else {
+                dir.push(component);
+                if !printed_dirs.contains(&dir) {
+                    println!("{}{}/", indent, component.as_os_str().to_string_lossy().cyan());
+                    printed_dirs.push(dir.clone());
+                }
+            }
+        }
+    }
+}
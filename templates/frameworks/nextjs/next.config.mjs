@@ -0,0 +1,9 @@
+/** @type {import('next').NextConfig} */
+const nextConfig = {
+  output: "export",
+  distDir: "dist",
+  // Public variables from nexus build, available as process.env.NAME.
+  env: JSON.parse(process.env.NEXUS_CLIENT_ENV ?? "{}"),
+}
+
+export default nextConfig
@@ -0,0 +1,10 @@
+import adapter from "@sveltejs/adapter-static"
+
+/** @type {import('@sveltejs/kit').Config} */
+const config = {
+  kit: {
+    adapter: adapter({ pages: "dist", assets: "dist" }),
+  },
+}
+
+export default config
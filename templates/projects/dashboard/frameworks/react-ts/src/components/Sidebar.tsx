@@ -0,0 +1,22 @@
+const links = ["Overview", "Analytics", "Customers", "Settings"];
+
+interface SidebarProps {
+  title: string;
+}
+
+export default function Sidebar({ title }: SidebarProps) {
+  return (
+    <aside className="sidebar">
+      <h2 className="sidebar__title">{title}</h2>
+      <nav>
+        <ul>
+          {links.map((link) => (
+            <li key={link}>
+              <a href={`#${link.toLowerCase()}`}>{link}</a>
+            </li>
+          ))}
+        </ul>
+      </nav>
+    </aside>
+  );
+}
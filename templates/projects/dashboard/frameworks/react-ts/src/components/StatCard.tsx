@@ -0,0 +1,13 @@
+interface StatCardProps {
+  label: string;
+  value: string;
+}
+
+export default function StatCard({ label, value }: StatCardProps) {
+  return (
+    <div className="stat-card">
+      <span className="stat-card__label">{label}</span>
+      <strong className="stat-card__value">{value}</strong>
+    </div>
+  );
+}
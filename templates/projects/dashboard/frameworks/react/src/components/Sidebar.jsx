@@ -0,0 +1,18 @@
+const links = ["Overview", "Analytics", "Customers", "Settings"];
+
+export default function Sidebar({ title }) {
+  return (
+    <aside className="sidebar">
+      <h2 className="sidebar__title">{title}</h2>
+      <nav>
+        <ul>
+          {links.map((link) => (
+            <li key={link}>
+              <a href={`#${link.toLowerCase()}`}>{link}</a>
+            </li>
+          ))}
+        </ul>
+      </nav>
+    </aside>
+  );
+}
@@ -0,0 +1,8 @@
+export default function StatCard({ label, value }) {
+  return (
+    <div className="stat-card">
+      <span className="stat-card__label">{label}</span>
+      <strong className="stat-card__value">{value}</strong>
+    </div>
+  );
+}
@@ -0,0 +1,9 @@
+const { Router } = require("express");
+
+const router = Router();
+
+router.get("/", (_req, res) => {
+  res.json({ status: "ok", time: new Date().toISOString() });
+});
+
+module.exports = router;
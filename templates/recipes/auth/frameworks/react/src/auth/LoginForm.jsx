@@ -0,0 +1,32 @@
+import { useState } from "react"
+import { useAuth } from "./AuthContext"
+
+export default function LoginForm() {
+  const { login, logout, isAuthenticated } = useAuth()
+  const [email, setEmail] = useState("")
+  const [password, setPassword] = useState("")
+  const [error, setError] = useState(null)
+
+  if (isAuthenticated) {
+    return <button onClick={logout}>Sign out</button>
+  }
+
+  async function handleSubmit(event) {
+    event.preventDefault()
+    setError(null)
+    try {
+      await login(email, password)
+    } catch (e) {
+      setError(e.message)
+    }
+  }
+
+  return (
+    <form onSubmit={handleSubmit}>
+      <input type="email" placeholder="Email" value={email} onChange={(e) => setEmail(e.target.value)} required />
+      <input type="password" placeholder="Password" value={password} onChange={(e) => setPassword(e.target.value)} required />
+      <button type="submit">Sign in</button>
+      {error && <p role="alert">{error}</p>}
+    </form>
+  )
+}
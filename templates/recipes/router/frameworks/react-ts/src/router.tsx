@@ -0,0 +1,10 @@
+import { createBrowserRouter, type RouteObject } from "react-router-dom"
+import App from "./App"
+// nexus:route-imports
+
+export const routes: RouteObject[] = [
+  { path: "/", element: <App /> },
+  // nexus:routes
+]
+
+export const router = createBrowserRouter(routes)
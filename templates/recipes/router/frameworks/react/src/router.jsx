@@ -0,0 +1,10 @@
+import { createBrowserRouter } from "react-router-dom"
+import App from "./App"
+// nexus:route-imports
+
+export const routes = [
+  { path: "/", element: <App /> },
+  // nexus:routes
+]
+
+export const router = createBrowserRouter(routes)
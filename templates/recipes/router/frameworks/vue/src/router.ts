@@ -0,0 +1,13 @@
+import { createRouter, createWebHistory, type RouteRecordRaw } from "vue-router"
+import HomeView from "./views/HomeView.vue"
+// nexus:route-imports
+
+export const routes: RouteRecordRaw[] = [
+  { path: "/", name: "home", component: HomeView },
+  // nexus:routes
+]
+
+export const router = createRouter({
+  history: createWebHistory(),
+  routes,
+})
@@ -0,0 +1,13 @@
+import { create } from "zustand"
+
+interface AppState {
+  count: number
+  increment: () => void
+  reset: () => void
+}
+
+export const useAppStore = create<AppState>()((set) => ({
+  count: 0,
+  increment: () => set((state) => ({ count: state.count + 1 })),
+  reset: () => set({ count: 0 }),
+}))
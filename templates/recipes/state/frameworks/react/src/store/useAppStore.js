@@ -0,0 +1,7 @@
+import { create } from "zustand"
+
+export const useAppStore = create((set) => ({
+  count: 0,
+  increment: () => set((state) => ({ count: state.count + 1 })),
+  reset: () => set({ count: 0 }),
+}))
@@ -0,0 +1,15 @@
+import { defineStore } from "pinia"
+
+export const useAppStore = defineStore("app", {
+  state: () => ({
+    count: 0,
+  }),
+  actions: {
+    increment() {
+      this.count++
+    },
+    reset() {
+      this.count = 0
+    },
+  },
+})
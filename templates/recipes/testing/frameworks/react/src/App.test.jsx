@@ -0,0 +1,10 @@
+import { render, screen } from "@testing-library/react"
+import { describe, expect, it } from "vitest"
+import App from "./App"
+
+describe("App", () => {
+  it("renders a heading", () => {
+    render(<App />)
+    expect(screen.getAllByRole("heading").length).toBeGreaterThan(0)
+  })
+})
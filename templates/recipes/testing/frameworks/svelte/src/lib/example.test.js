@@ -0,0 +1,8 @@
+import { describe, expect, it } from "vitest"
+
+// Replace with tests for your own modules in src/lib.
+describe("example", () => {
+  it("runs", () => {
+    expect([1, 2, 3].map((n) => n * 2)).toEqual([2, 4, 6])
+  })
+})
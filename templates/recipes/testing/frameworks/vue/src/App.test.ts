@@ -0,0 +1,10 @@
+import { render, screen } from "@testing-library/vue"
+import { describe, expect, it } from "vitest"
+import App from "./App.vue"
+
+describe("App", () => {
+  it("renders a heading", () => {
+    render(App)
+    expect(screen.getAllByRole("heading").length).toBeGreaterThan(0)
+  })
+})
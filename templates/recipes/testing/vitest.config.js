@@ -0,0 +1,12 @@
+import { defineConfig, mergeConfig } from "vitest/config"
+import viteConfig from "./vite.config"
+
+export default mergeConfig(
+  viteConfig,
+  defineConfig({
+    test: {
+      environment: "jsdom",
+      include: ["src/**/*.test.{js,jsx,ts,tsx}"],
+    },
+  })
+)
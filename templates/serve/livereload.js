@@ -0,0 +1,68 @@
+// Injected into pages by nexus serve. Reloads the page, swaps stylesheets or
+// shows the build error the dev server reports.
+(() => {
+  const url = `${location.protocol === "https:" ? "wss" : "ws"}://${location.host}/__nexus/livereload`;
+  let overlay = null;
+  let connected = false;
+
+  function showError(message) {
+    hideError();
+    overlay = document.createElement("div");
+    overlay.setAttribute("style", [
+      "position:fixed", "inset:0", "z-index:2147483647", "overflow:auto", "padding:32px",
+      "background:rgba(24,24,27,0.92)", "color:#fafafa",
+      "font:14px/1.5 ui-monospace,SFMono-Regular,Menlo,Consolas,monospace",
+    ].join(";"));
+    const title = document.createElement("div");
+    title.setAttribute("style", "color:#f87171;font-weight:bold;font-size:16px;margin-bottom:12px");
+    title.textContent = "Build failed";
+    const details = document.createElement("pre");
+    details.setAttribute("style", "white-space:pre-wrap;margin:0");
+    details.textContent = message;
+    const hint = document.createElement("div");
+    hint.setAttribute("style", "color:#a1a1aa;margin-top:16px");
+    hint.textContent = "Fix the error and save; the page reloads once the build succeeds.";
+    overlay.append(title, details, hint);
+    document.body.append(overlay);
+  }
+
+  function hideError() {
+    if (overlay) {
+      overlay.remove();
+      overlay = null;
+    }
+  }
+
+  function swapStylesheets(paths) {
+    const links = [...document.querySelectorAll('link[rel="stylesheet"]')];
+    const stale = links.filter((link) => paths.includes(new URL(link.href).pathname));
+    if (stale.length === 0) {
+      location.reload();
+      return;
+    }
+    for (const link of stale) {
+      const next = link.cloneNode();
+      next.href = `${new URL(link.href).pathname}?t=${Date.now()}`;
+      next.addEventListener("load", () => link.remove());
+      link.after(next);
+    }
+  }
+
+  function connect() {
+    const socket = new WebSocket(url);
+    socket.addEventListener("open", () => {
+      // The server restarted: its build may be newer than this page.
+      if (connected) location.reload();
+      connected = true;
+    });
+    socket.addEventListener("message", (event) => {
+      const message = JSON.parse(event.data);
+      if (message.type === "reload") location.reload();
+      else if (message.type === "css") swapStylesheets(message.paths);
+      else if (message.type === "error") showError(message.message);
+    });
+    socket.addEventListener("close", () => setTimeout(connect, 1000));
+  }
+
+  connect();
+})();